@@ -6,22 +6,84 @@ use server::{ rest_bodies, rest_responses };
 
 use crate::web_connection::WebConnection;
 
+// The number of orders requested at a time when walking through all of a table's orders
+const ORDER_PAGE_SIZE: u32 = 100;
+
 pub fn get_all_orders(web_connection: &dyn WebConnection, host: String, table_number: u32) -> Result<Vec<rest_responses::Order>, String> {
-    let web_response = web_connection.get(format!("{host}/tables/{table_number}/orders"))
+    iter_orders(web_connection, host, table_number, ORDER_PAGE_SIZE).collect()
+}
+
+// Gets a single page of a table's orders. The page's next_cursor is passed as the cursor to get the page after it.
+pub fn get_orders_page(web_connection: &dyn WebConnection, host: &str, table_number: u32, limit: u32, cursor: Option<&str>) -> Result<rest_responses::Orders, String> {
+    let cursor = match cursor {
+        Some(cursor) => format!("&cursor={cursor}"),
+        None => "".to_string()
+    };
+    let web_response = web_connection.get(format!("{host}/tables/{table_number}/orders?limit={limit}{cursor}"))
         .map_err(|e| e.text)?;
     
     match web_response.status {
         StatusCode::OK => {
             let body = web_response.body
                 .map_err(|e| e.to_string())?;
-            Ok(from_str::<rest_responses::Orders>(&body)
-                .map_err(|e| e.to_string())?
-                .orders)
+            from_str::<rest_responses::Orders>(&body)
+                .map_err(|e| e.to_string())
         },
         status => Result::Err(status.as_str().to_string())
     }
 }
 
+// Walks through all of a table's orders, getting the next page from the server only once the previous one has been used up
+pub fn iter_orders(web_connection: &dyn WebConnection, host: String, table_number: u32, page_size: u32) -> OrderIterator<'_> {
+    OrderIterator {
+        web_connection,
+        host,
+        table_number,
+        page_size,
+        page: Vec::new().into_iter(),
+        next_cursor: None,
+        finished: false
+    }
+}
+
+pub struct OrderIterator<'a> {
+    web_connection: &'a dyn WebConnection,
+    host: String,
+    table_number: u32,
+    page_size: u32,
+    page: std::vec::IntoIter<rest_responses::Order>,
+    next_cursor: Option<String>,
+    finished: bool
+}
+
+impl Iterator for OrderIterator<'_> {
+    type Item = Result<rest_responses::Order, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(order) = self.page.next() {
+                return Some(Ok(order));
+            }
+            if self.finished {
+                return None;
+            }
+
+            // an error ends the iteration, since there is no cursor to carry on from
+            match get_orders_page(self.web_connection, &self.host, self.table_number, self.page_size, self.next_cursor.as_deref()) {
+                Ok(page) => {
+                    self.finished = page.next_cursor.is_none();
+                    self.next_cursor = page.next_cursor;
+                    self.page = page.orders.into_iter();
+                },
+                Err(e) => {
+                    self.finished = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
 pub fn get_order(web_connection: &dyn WebConnection, host: String, table_number: u32, order_id: u32) -> Result<rest_responses::Order, String> {
     let web_response = web_connection.get(format!("{host}/tables/{table_number}/orders/{order_id}"))
         .map_err(|e| e.text)?;
@@ -39,27 +101,35 @@ pub fn get_order(web_connection: &dyn WebConnection, host: String, table_number:
 
 pub fn add_orders<F>(web_connection: &dyn WebConnection, host: String, table_number: u32, menu_item_ids: Vec<u32>, should_retry: F) -> Result<Vec<rest_responses::Order>, String>
                         where F: Fn() -> bool {
+    // the idempotency key is sent in the Idempotency-Key header rather than the body
+    let idempotency_key = Uuid::new_v4().to_string();
     let orders = rest_bodies::Orders {
-        idempotency_key: Option::Some(Uuid::new_v4().to_string()),
+        idempotency_key: Option::None,
         orders: menu_item_ids.iter().map(|i| rest_bodies::Order {
             menu_item_id: *i,
+            seat: None,
+            modifiers: None,
+            note: None
         }).collect()
     };
 
     let web_response = loop {
         let resp = web_connection.post(
             format!("{host}/tables/{table_number}/orders"),
-            to_string(&orders).map_err(|e| e.to_string())?);
+            to_string(&orders).map_err(|e| e.to_string())?,
+            Some(&idempotency_key));
         let retry = resp
             .as_ref()
             .err()
-            .map_or(false, |e|
+            .is_some_and(|e|
                 e.is_timeout && should_retry());
         if !retry {
             break resp.map_err(|e| e.text)?;
         }
     };
 
+    // a retry reuses the idempotency key, so if an earlier attempt reached the server, the server
+    // replays its response with 200 rather than adding the orders again
     match web_response.status {
         StatusCode::OK => {
             let body = web_response.body
@@ -72,11 +142,52 @@ pub fn add_orders<F>(web_connection: &dyn WebConnection, host: String, table_num
     }
 }
 
-pub fn delete_order(web_connection: &dyn WebConnection, host: String, table_number: u32, order_id: u32) -> Result<(), String> {
+pub fn update_order(web_connection: &dyn WebConnection, host: String, table_number: u32, order_id: u32, update: rest_bodies::OrderUpdate) -> Result<rest_responses::Order, String> {
+    let web_response = web_connection.patch(
+        format!("{host}/tables/{table_number}/orders/{order_id}"),
+        to_string(&update).map_err(|e| e.to_string())?)
+        .map_err(|e| e.text)?;
+
+    match web_response.status {
+        StatusCode::OK => {
+            let body = web_response.body
+                .map_err(|e| e.to_string())?;
+            from_str::<rest_responses::Order>(&body)
+                .map_err(|e| e.to_string())
+        },
+        status => Result::Err(status.as_str().to_string())
+    }
+}
+
+// Returns whether the order was deleted. Deleting an order which does not exist (or is on another table)
+// is not an error, but returns false so that the caller can tell that nothing was deleted.
+pub fn delete_order(web_connection: &dyn WebConnection, host: String, table_number: u32, order_id: u32) -> Result<bool, String> {
     let web_response = web_connection.delete(format!("{host}/tables/{table_number}/orders/{order_id}"))
         .map_err(|e| e.text)?;
     match web_response.status {
-        StatusCode::NO_CONTENT => Ok(()),
+        StatusCode::NO_CONTENT => Ok(true),
+        StatusCode::NOT_FOUND => Ok(false),
+        status => Result::Err(status.as_str().to_string())
+    }
+}
+
+// Deletes all of the table's open orders, or only those with the given ids, returning the deleted orders
+pub fn clear_table(web_connection: &dyn WebConnection, host: String, table_number: u32, order_ids: Option<Vec<u32>>) -> Result<Vec<rest_responses::Order>, String> {
+    let query = match order_ids {
+        Some(ids) => format!("?ids={}", ids.iter().map(|id| id.to_string()).collect::<Vec<String>>().join(",")),
+        None => "".to_string()
+    };
+    let web_response = web_connection.delete(format!("{host}/tables/{table_number}/orders{query}"))
+        .map_err(|e| e.text)?;
+
+    match web_response.status {
+        StatusCode::OK => {
+            let body = web_response.body
+                .map_err(|e| e.to_string())?;
+            Ok(from_str::<rest_responses::Orders>(&body)
+                .map_err(|e| e.to_string())?
+                .orders)
+        },
         status => Result::Err(status.as_str().to_string())
     }
 }
@@ -72,7 +72,7 @@ pub fn add_random_order<T>(params: &mut SimInjectionParams<T>, client_number: u3
         Err(e) => format!("Client {} encountered an error trying to add orders to table {}: {}",
             client_number,
             table_number,
-            e.to_string())
+            e)
     }
 }
 
@@ -82,7 +82,7 @@ fn add_to_table<T>(params: &SimInjectionParams<T>, table_number: u32, menu_item_
     let menu_items = params.client_functions.get_menu_items(&connection, HOST.to_string())?;
     let item_ids = menu_item_names
         .iter()
-        .map(|n| menu_items.menu_items.iter().find(|m| m.name == n.to_string()))
+        .map(|n| menu_items.menu_items.iter().find(|m| m.name == *n))
         .filter(|x| x.is_some())
         .map(|x| x.unwrap().id)
         .collect::<Vec<u32>>();
@@ -95,12 +95,19 @@ pub fn delete_random_order<T>(params: &mut SimInjectionParams<T>, client_number:
     match added_items.choose(&mut params.rng) {
         Some(item_to_delete) => {
             match params.client_functions.delete_order(&connection, HOST.to_string(), item_to_delete.table_id, item_to_delete.order_id) {
-                Ok(()) => {
-                    let ret = format!("Client {} deleted order {} from table {}.",
-                        client_number,
-                        item_to_delete.order_id,
-                        item_to_delete.table_id);
-                        
+                Ok(deleted) => {
+                    let ret = match deleted {
+                        true => format!("Client {} deleted order {} from table {}.",
+                            client_number,
+                            item_to_delete.order_id,
+                            item_to_delete.table_id),
+                        false => format!("Client {} tried to delete order {} from table {}, but it no longer existed.",
+                            client_number,
+                            item_to_delete.order_id,
+                            item_to_delete.table_id)
+                    };
+
+                    // either way, the order is gone from the table
                     if let Some(item_index) = added_items.iter().position(|i|
                         i.table_id == item_to_delete.table_id && i.order_id == item_to_delete.order_id) {
                             added_items.remove(item_index);
@@ -124,7 +131,7 @@ pub fn query_random_table<T>(params: &mut SimInjectionParams<T>, client_number:
     let table_number = params.rng.gen_range(1..TABLE_COUNT + 1);
     match params.client_functions.get_all_orders(&connection, HOST.to_string(), table_number) {
         Ok(orders) => {
-            format!("Client {} queried orders for table {}, which had {} orders, including {} for {} minutes.",
+            format!("Client {} queried orders for table {}, which had {} orders, including {} with {} minutes remaining.",
                 client_number,
                 table_number,
                 orders.len(),
@@ -133,33 +140,33 @@ pub fn query_random_table<T>(params: &mut SimInjectionParams<T>, client_number:
                     _ => "N/A".to_string()
                 },
                 match orders.first() {
-                    Some(order) => order.minutes_to_cook.to_string(),
+                    Some(order) => order.minutes_remaining.to_string(),
                     _ => "N/A".to_string()
                 })
         },
         Err(e) => format!("Client {} encountered an error trying to query orders for table {}: {}",
             client_number,
             table_number,
-            e.to_string())
+            e)
     }
 }
 
-pub fn query_random_table_item<T>(params: &mut SimInjectionParams<T>, client_number: u32, added_items: &Vec<TableOrderPair>) -> String
+pub fn query_random_table_item<T>(params: &mut SimInjectionParams<T>, client_number: u32, added_items: &[TableOrderPair]) -> String
         where T: ClientFunctionInterface {
     let connection = DefaultWebConnection {};
     if let Some(item_to_query) = added_items.choose(&mut params.rng) {
         match params.client_functions.get_order(&connection, HOST.to_string(), item_to_query.table_id, item_to_query.order_id) {
             Ok(order) => format!(
-                "Client {} queried order with ID {} for table {}: order id {}, {}, {} minutes",
+                "Client {} queried order with ID {} for table {}: order id {}, {}, {} minutes remaining",
                 client_number,
                 item_to_query.table_id,
                 item_to_query.order_id,
                 order.id,
                 order.menu_item_name,
-                order.minutes_to_cook),
+                order.minutes_remaining),
             Err(e) => format!("Client {} encountered an error trying to query a random table order: {}",
                 client_number,
-                e.to_string())
+                e)
         }
     }
     else {
@@ -12,7 +12,9 @@ pub struct WebError {
 
 pub trait WebConnection {
     fn get(&self, path: String) -> Result<WebResponse, WebError>;
-    fn post(&self, path: String, body: String) -> Result<WebResponse, WebError>;
+    // The idempotency key, if any, is sent in the Idempotency-Key header
+    fn post(&self, path: String, body: String, idempotency_key: Option<&str>) -> Result<WebResponse, WebError>;
+    fn patch(&self, path: String, body: String) -> Result<WebResponse, WebError>;
     fn delete(&self, path: String) -> Result<WebResponse, WebError>;
 }
 
@@ -31,9 +33,29 @@ impl WebConnection for DefaultWebConnection {
         })
     }
 
-    fn post(&self, path: String, body: String) -> Result<WebResponse, WebError> {
+    fn post(&self, path: String, body: String, idempotency_key: Option<&str>) -> Result<WebResponse, WebError> {
         let client = reqwest::blocking::Client::new();
-        let response = client.post(path)
+        let mut request = client.post(path)
+            .header("Content-Type", "application/json");
+        if let Some(key) = idempotency_key {
+            request = request.header("Idempotency-Key", key);
+        }
+        let response = request
+            .body(body)
+            .send()
+            .map_err(|e| WebError {
+                is_timeout: e.is_timeout(),
+                text: e.to_string()
+            })?;
+        Ok(WebResponse {
+            status: response.status(),
+            body: response.text()
+        })
+    }
+
+    fn patch(&self, path: String, body: String) -> Result<WebResponse, WebError> {
+        let client = reqwest::blocking::Client::new();
+        let response = client.patch(path)
             .header("Content-Type", "application/json")
             .body(body)
             .send()
@@ -3,6 +3,7 @@ mod mock_web_connection;
 #[cfg(test)]
 mod tests {
     use client::client_functions;
+    use server::rest_bodies;
     use reqwest::StatusCode;
 
     use crate::mock_web_connection::{Method, MockWebConnection};
@@ -10,7 +11,7 @@ mod tests {
     #[test]
     fn get_all_orders_success() -> Result<(), String> {
         let connection = MockWebConnection::new(
-            Method::GET,
+            Method::Get,
             StatusCode::OK,
             false,
             "{
@@ -20,7 +21,22 @@ mod tests {
                                     \"table_number\": 2,
                                     \"menu_item_id\": 3,
                                     \"menu_item_name\": \"test\",
-                                    \"minutes_to_cook\": 4
+                                    \"minutes_to_cook\": 4,
+                                    \"status\": \"placed\",
+                                    \"status_times\": {
+                                        \"placed_at\": 1700000000,
+                                        \"cooking_at\": null,
+                                        \"ready_at\": null,
+                                        \"served_at\": null,
+                                        \"cancelled_at\": null
+                                    },
+                                    \"created_at\": 1700000000,
+                                    \"minutes_remaining\": 4,
+                                    \"ready_at\": 1700000240,
+                                    \"price\": 500,
+                                    \"seat\": null,
+                                    \"modifiers\": [],
+                                    \"note\": null
                                 }
                             ]
                         }".to_string()
@@ -43,7 +59,7 @@ mod tests {
     #[test]
     fn get_all_orders_failure() -> Result<(), String> {
         let connection = MockWebConnection::new(
-            Method::GET,
+            Method::Get,
             StatusCode::INTERNAL_SERVER_ERROR,
             false,
             "{ \"error\": \"error\" }".to_string()
@@ -57,10 +73,59 @@ mod tests {
         Ok(())
     }
     
+    #[test]
+    fn get_all_orders_pages() -> Result<(), String> {
+        let order_json = |id: u32| format!("{{
+                                    \"id\": {id},
+                                    \"table_number\": 2,
+                                    \"menu_item_id\": 3,
+                                    \"menu_item_name\": \"test\",
+                                    \"minutes_to_cook\": 4,
+                                    \"status\": \"placed\",
+                                    \"status_times\": {{
+                                        \"placed_at\": 1700000000,
+                                        \"cooking_at\": null,
+                                        \"ready_at\": null,
+                                        \"served_at\": null,
+                                        \"cancelled_at\": null
+                                    }},
+                                    \"created_at\": 1700000000,
+                                    \"minutes_remaining\": 4,
+                                    \"ready_at\": 1700000240,
+                                    \"price\": 500,
+                                    \"seat\": null,
+                                    \"modifiers\": [],
+                                    \"note\": null
+                                }}");
+        let connection = MockWebConnection::new(
+            Method::Get,
+            StatusCode::OK,
+            false,
+            "".to_string()
+        );
+        connection.queued_get_bodies.borrow_mut().extend([
+            format!("{{ \"orders\": [{}, {}], \"next_cursor\": \"2\" }}", order_json(1), order_json(2)),
+            format!("{{ \"orders\": [{}], \"next_cursor\": null }}", order_json(3))
+        ]);
+
+        let orders = client_functions::iter_orders(
+            &connection,
+            "".to_string(),
+            2,
+            2)
+            .collect::<Result<Vec<_>, String>>()?;
+        assert_eq!(orders.iter().map(|o| o.id).collect::<Vec<u32>>(), vec![1, 2, 3]);
+        assert_eq!(*connection.get_paths.borrow(), vec![
+            "/tables/2/orders?limit=2".to_string(),
+            "/tables/2/orders?limit=2&cursor=2".to_string()
+        ]);
+        Ok(())
+    }
+
     #[test]
     fn get_order_success() -> Result<(), String> {
         let connection = MockWebConnection::new(
-            Method::GET,
+            Method::Get,
             StatusCode::OK,
             false,
             "{
@@ -68,7 +133,22 @@ mod tests {
                             \"table_number\": 2,
                             \"menu_item_id\": 3,
                             \"menu_item_name\": \"test\",
-                            \"minutes_to_cook\": 4
+                            \"minutes_to_cook\": 4,
+                            \"status\": \"placed\",
+                            \"status_times\": {
+                                \"placed_at\": 1700000000,
+                                \"cooking_at\": null,
+                                \"ready_at\": null,
+                                \"served_at\": null,
+                                \"cancelled_at\": null
+                            },
+                            \"created_at\": 1700000000,
+                            \"minutes_remaining\": 4,
+                            \"ready_at\": 1700000240,
+                            \"price\": 500,
+                            \"seat\": null,
+                            \"modifiers\": [],
+                            \"note\": null
                         }".to_string()
         );
         let result = client_functions::get_order(
@@ -89,7 +169,7 @@ mod tests {
     #[test]
     fn get_order_failure() -> Result<(), String> {
         let connection = MockWebConnection::new(
-            Method::GET,
+            Method::Get,
             StatusCode::INTERNAL_SERVER_ERROR,
             false,
             "{ \"error\": \"error\" }".to_string()
@@ -107,7 +187,7 @@ mod tests {
     #[test]
     fn add_orders_success() -> Result<(), String> {
         let connection = MockWebConnection::new(
-            Method::POST,
+            Method::Post,
             StatusCode::OK,
             false,
             "{
@@ -117,7 +197,22 @@ mod tests {
                                     \"table_number\": 2,
                                     \"menu_item_id\": 3,
                                     \"menu_item_name\": \"test\",
-                                    \"minutes_to_cook\": 4
+                                    \"minutes_to_cook\": 4,
+                                    \"status\": \"placed\",
+                                    \"status_times\": {
+                                        \"placed_at\": 1700000000,
+                                        \"cooking_at\": null,
+                                        \"ready_at\": null,
+                                        \"served_at\": null,
+                                        \"cancelled_at\": null
+                                    },
+                                    \"created_at\": 1700000000,
+                                    \"minutes_remaining\": 4,
+                                    \"ready_at\": 1700000240,
+                                    \"price\": 500,
+                                    \"seat\": null,
+                                    \"modifiers\": [],
+                                    \"note\": null
                                 }
                             ]
                         }".to_string()
@@ -143,7 +238,7 @@ mod tests {
     #[test]
     fn add_order_failure() -> Result<(), String> {
         let connection = MockWebConnection::new(
-            Method::GET,
+            Method::Get,
             StatusCode::INTERNAL_SERVER_ERROR,
             false,
             "{ \"error\": \"error\" }".to_string()
@@ -163,7 +258,7 @@ mod tests {
     #[test]
     fn add_order_retry() -> Result<(), String> {
         let connection = MockWebConnection::new(
-            Method::POST,
+            Method::Post,
             StatusCode::CONFLICT,
             true,
             "{ \"error\": \"error\" }".to_string()
@@ -186,10 +281,145 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn add_order_retry_replayed() -> Result<(), String> {
+        // the first post times out after the server added the orders, so the retry is answered with a replay
+        let connection = MockWebConnection::new(
+            Method::Post,
+            StatusCode::OK,
+            false,
+            "{
+                            \"orders\": [
+                                {
+                                    \"id\": 1,
+                                    \"table_number\": 2,
+                                    \"menu_item_id\": 3,
+                                    \"menu_item_name\": \"test\",
+                                    \"minutes_to_cook\": 4,
+                                    \"status\": \"placed\",
+                                    \"status_times\": {
+                                        \"placed_at\": 1700000000,
+                                        \"cooking_at\": null,
+                                        \"ready_at\": null,
+                                        \"served_at\": null,
+                                        \"cancelled_at\": null
+                                    },
+                                    \"created_at\": 1700000000,
+                                    \"minutes_remaining\": 4,
+                                    \"ready_at\": 1700000240,
+                                    \"price\": 500,
+                                    \"seat\": null,
+                                    \"modifiers\": [],
+                                    \"note\": null
+                                }
+                            ]
+                        }".to_string()
+        );
+        *connection.timeouts_remaining.borrow_mut() = 1;
+
+        let retry_count = std::cell::Cell::new(0);
+        let result = client_functions::add_orders(
+            &connection,
+            "".to_string(),
+            1,
+            vec![3],
+        || {
+            retry_count.set(retry_count.get() + 1);
+            true
+        });
+        assert!(result.is_ok());
+        assert_eq!(retry_count.get(), 1);
+        // the retry is sent with the same idempotency key in the header
+        let keys = connection.idempotency_keys.borrow();
+        assert_eq!(keys.len(), 2);
+        assert!(keys[0].is_some());
+        assert_eq!(keys[0], keys[1]);
+        let orders = result.unwrap();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].id, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn update_order_success() -> Result<(), String> {
+        let connection = MockWebConnection::new(
+            Method::Patch,
+            StatusCode::OK,
+            false,
+            "{
+                            \"id\": 1,
+                            \"table_number\": 2,
+                            \"menu_item_id\": 3,
+                            \"menu_item_name\": \"test\",
+                            \"minutes_to_cook\": 30,
+                            \"status\": \"placed\",
+                            \"status_times\": {
+                                \"placed_at\": 1700000000,
+                                \"cooking_at\": null,
+                                \"ready_at\": null,
+                                \"served_at\": null,
+                                \"cancelled_at\": null
+                            },
+                            \"created_at\": 1700000000,
+                            \"minutes_remaining\": 30,
+                            \"ready_at\": 1700001800,
+                            \"price\": 500,
+                            \"seat\": null,
+                            \"modifiers\": [],
+                            \"note\": null
+                        }".to_string()
+        );
+        let result = client_functions::update_order(
+            &connection,
+            "".to_string(),
+            1,
+            1,
+            rest_bodies::OrderUpdate {
+                menu_item_id: None,
+                table_number: None,
+                minutes_to_cook: Some(30),
+                seat: None,
+                modifiers: None,
+                note: None
+            });
+        assert!(*connection.was_patch_called.borrow());
+        assert!(result.is_ok());
+        let order = result.unwrap();
+        assert_eq!(order.id, 1);
+        assert_eq!(order.minutes_to_cook, 30);
+        Ok(())
+    }
+
+    #[test]
+    fn update_order_failure() -> Result<(), String> {
+        let connection = MockWebConnection::new(
+            Method::Patch,
+            StatusCode::UNPROCESSABLE_ENTITY,
+            false,
+            "{ \"error\": \"error\" }".to_string()
+        );
+        let result = client_functions::update_order(
+            &connection,
+            "".to_string(),
+            1,
+            1,
+            rest_bodies::OrderUpdate {
+                menu_item_id: Some(999),
+                table_number: None,
+                minutes_to_cook: None,
+                seat: None,
+                modifiers: None,
+                note: None
+            });
+        assert!(*connection.was_patch_called.borrow());
+        assert!(result.is_err());
+        Ok(())
+    }
+
     #[test]
     fn delete_orders_success() -> Result<(), String> {
         let connection = MockWebConnection::new(
-            Method::DELETE,
+            Method::Delete,
             StatusCode::NO_CONTENT,
             false,
             "{}".to_string()
@@ -200,14 +430,32 @@ mod tests {
             1,
         1);
         assert!(*connection.was_delete_called.borrow());
-        assert!(result.is_ok());
+        assert_eq!(result, Ok(true));
+        Ok(())
+    }
+
+    #[test]
+    fn delete_order_not_found() -> Result<(), String> {
+        let connection = MockWebConnection::new(
+            Method::Delete,
+            StatusCode::NOT_FOUND,
+            false,
+            "{ \"error\": \"Provided order does not exist for provided table.\" }".to_string()
+        );
+        let result = client_functions::delete_order(
+            &connection,
+            "".to_string(),
+            1,
+        1);
+        assert!(*connection.was_delete_called.borrow());
+        assert_eq!(result, Ok(false));
         Ok(())
     }
     
     #[test]
     fn delete_failure() -> Result<(), String> {
         let connection = MockWebConnection::new(
-            Method::DELETE,
+            Method::Delete,
             StatusCode::INTERNAL_SERVER_ERROR,
             false,
             "{ \"error\": \"error\" }".to_string()
@@ -220,4 +468,68 @@ mod tests {
         assert!(result.is_err());
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn clear_table_success() -> Result<(), String> {
+        let connection = MockWebConnection::new(
+            Method::Delete,
+            StatusCode::OK,
+            false,
+            "{
+                            \"orders\": [
+                                {
+                                    \"id\": 1,
+                                    \"table_number\": 2,
+                                    \"menu_item_id\": 3,
+                                    \"menu_item_name\": \"test\",
+                                    \"minutes_to_cook\": 4,
+                                    \"status\": \"placed\",
+                                    \"status_times\": {
+                                        \"placed_at\": 1700000000,
+                                        \"cooking_at\": null,
+                                        \"ready_at\": null,
+                                        \"served_at\": null,
+                                        \"cancelled_at\": null
+                                    },
+                                    \"created_at\": 1700000000,
+                                    \"minutes_remaining\": 4,
+                                    \"ready_at\": 1700000240,
+                                    \"price\": 500,
+                                    \"seat\": null,
+                                    \"modifiers\": [],
+                                    \"note\": null
+                                }
+                            ]
+                        }".to_string()
+        );
+        let result = client_functions::clear_table(
+            &connection,
+            "".to_string(),
+            2,
+            Some(vec![1, 5]));
+        assert_eq!(*connection.delete_path.borrow(), "/tables/2/orders?ids=1,5");
+        assert!(result.is_ok());
+        let orders = result.unwrap();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].id, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn clear_table_failure() -> Result<(), String> {
+        let connection = MockWebConnection::new(
+            Method::Delete,
+            StatusCode::NOT_FOUND,
+            false,
+            "{ \"error\": \"error\" }".to_string()
+        );
+        let result = client_functions::clear_table(
+            &connection,
+            "".to_string(),
+            2,
+            None);
+        assert_eq!(*connection.delete_path.borrow(), "/tables/2/orders");
+        assert!(result.is_err());
+        Ok(())
+    }
+}
@@ -1,53 +1,76 @@
 use std::cell::RefCell;
 
 use client::{client_function_interface::ClientFunctionInterface, web_connection::WebConnection};
-use server::rest_responses;
+use server::{rest_bodies, rest_responses};
+use server::order_status::OrderStatus;
 
 pub struct MockClientFunctionInterface {
     pub should_fail: bool,
     pub was_delete_order_called: RefCell<bool>,
-    pub was_add_orders_called: RefCell<bool>
+    pub was_add_orders_called: RefCell<bool>,
+    pub was_update_order_called: RefCell<bool>,
+    pub was_clear_table_called: RefCell<bool>
 }
 
 pub const DEFAULT_RETURN_ORDER_ID: u32 = 5;
+pub const DEFAULT_RETURN_ORDER_TABLE_NUMBER: u32 = 1;
 pub const DEFAULT_RETURN_ORDER_MENU_ID: u32 = 10;
 pub const DEFAULT_RETURN_ORDER_MENU_NAME: &str = "Food";
 pub const DEFAULT_RETURN_ORDER_MINUTES_TO_COOK: u32 = 20;
+pub const DEFAULT_RETURN_ORDER_PRICE: u32 = 500;
 
 
 pub fn new() -> MockClientFunctionInterface {
     MockClientFunctionInterface {
         should_fail: false,
         was_add_orders_called: RefCell::new(false),
-        was_delete_order_called: RefCell::new(false)
+        was_update_order_called: RefCell::new(false),
+        was_delete_order_called: RefCell::new(false),
+        was_clear_table_called: RefCell::new(false)
     }
 }
 
 fn new_default_return() -> rest_responses::Order {
     rest_responses::Order {
         id: DEFAULT_RETURN_ORDER_ID,
+        table_number: DEFAULT_RETURN_ORDER_TABLE_NUMBER,
         menu_item_id: DEFAULT_RETURN_ORDER_MENU_ID,
         menu_item_name: DEFAULT_RETURN_ORDER_MENU_NAME.to_string(),
-        minutes_to_cook: DEFAULT_RETURN_ORDER_MINUTES_TO_COOK
+        minutes_to_cook: DEFAULT_RETURN_ORDER_MINUTES_TO_COOK,
+        status: OrderStatus::Placed,
+        status_times: rest_responses::StatusTimes {
+            placed_at: Some(0),
+            cooking_at: None,
+            ready_at: None,
+            served_at: None,
+            cancelled_at: None
+        },
+        created_at: 0,
+        minutes_remaining: DEFAULT_RETURN_ORDER_MINUTES_TO_COOK,
+        ready_at: u64::from(DEFAULT_RETURN_ORDER_MINUTES_TO_COOK) * 60,
+        price: DEFAULT_RETURN_ORDER_PRICE,
+        seat: None,
+        modifiers: Vec::new(),
+        note: None
     }
 }
 
 impl ClientFunctionInterface for MockClientFunctionInterface {
-    fn get_all_orders(&self, web_connection: &dyn WebConnection, host: String, table_number: u32) -> Result<Vec<rest_responses::Order>, String> {
+    fn get_all_orders(&self, _web_connection: &dyn WebConnection, _host: String, _table_number: u32) -> Result<Vec<rest_responses::Order>, String> {
         match self.should_fail {
             true => Err("".to_string()),
             false => Ok(vec![new_default_return()])
         }
     }
 
-    fn get_order(&self, web_connection: &dyn WebConnection, host: String, table_number: u32, order_id: u32) -> Result<rest_responses::Order, String> {
+    fn get_order(&self, _web_connection: &dyn WebConnection, _host: String, _table_number: u32, _order_id: u32) -> Result<rest_responses::Order, String> {
         match self.should_fail {
             true => Err("".to_string()),
             false => Ok(new_default_return())
         }
     }
 
-    fn add_orders<F>(&self, web_connection: &dyn WebConnection, host: String, table_number: u32, menu_item_ids: Vec<u32>, should_retry: F) -> Result<Vec<rest_responses::Order>, String>
+    fn add_orders<F>(&self, _web_connection: &dyn WebConnection, _host: String, _table_number: u32, _menu_item_ids: Vec<u32>, _should_retry: F) -> Result<Vec<rest_responses::Order>, String>
             where F: Fn() -> bool {
         *self.was_add_orders_called.borrow_mut() = true;
         match self.should_fail {
@@ -56,21 +79,42 @@ impl ClientFunctionInterface for MockClientFunctionInterface {
         }
     }
 
-    fn delete_order(&self, web_connection: &dyn WebConnection, host: String, table_number: u32, order_id: u32) -> Result<(), String> {
+    fn update_order(&self, _web_connection: &dyn WebConnection, _host: String, _table_number: u32, _order_id: u32, _update: rest_bodies::OrderUpdate) -> Result<rest_responses::Order, String> {
+        *self.was_update_order_called.borrow_mut() = true;
+        match self.should_fail {
+            true => Err("".to_string()),
+            false => Ok(new_default_return())
+        }
+    }
+
+    fn delete_order(&self, _web_connection: &dyn WebConnection, _host: String, _table_number: u32, _order_id: u32) -> Result<bool, String> {
         *self.was_delete_order_called.borrow_mut() = true;
         match self.should_fail {
             true => Err("".to_string()),
-            false => Ok(())
+            false => Ok(true)
+        }
+    }
+
+    fn clear_table(&self, _web_connection: &dyn WebConnection, _host: String, _table_number: u32, _order_ids: Option<Vec<u32>>) -> Result<Vec<rest_responses::Order>, String> {
+        *self.was_clear_table_called.borrow_mut() = true;
+        match self.should_fail {
+            true => Err("".to_string()),
+            false => Ok(vec![new_default_return()])
         }
     }
 
-    fn get_menu_items(&self, web_connection: &dyn WebConnection, host: String) -> Result<rest_responses::MenuItems, String> {
+    fn get_menu_items(&self, _web_connection: &dyn WebConnection, _host: String) -> Result<rest_responses::MenuItems, String> {
         match self.should_fail {
             true => Err("".to_string()),
             false => Ok(rest_responses::MenuItems {
                 menu_items: vec![rest_responses::MenuItem {
                     id: DEFAULT_RETURN_ORDER_MENU_ID,
-                    name: DEFAULT_RETURN_ORDER_MENU_NAME.to_string()
+                    name: DEFAULT_RETURN_ORDER_MENU_NAME.to_string(),
+                    min_minutes_to_cook: DEFAULT_RETURN_ORDER_MINUTES_TO_COOK,
+                    max_minutes_to_cook: DEFAULT_RETURN_ORDER_MINUTES_TO_COOK,
+                    price: DEFAULT_RETURN_ORDER_PRICE,
+                    retired: false,
+                    modifiers: Vec::new()
                 }]
             })
         }
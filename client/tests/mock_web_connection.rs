@@ -1,10 +1,11 @@
 use std::cell::RefCell;
+use std::collections::VecDeque;
 
 use client::web_connection::{WebConnection, WebError, WebResponse};
 use reqwest::StatusCode;
 
 pub enum Method {
-    GET, POST, DELETE
+    Get, Post, Patch, Delete
 }
 
 pub struct MockWebConnection {
@@ -12,21 +13,38 @@ pub struct MockWebConnection {
     pub status: StatusCode,
     pub return_body_text: String,
     pub is_timeout: bool,
+    // bodies returned by successive gets, before falling back to return_body_text
+    pub queued_get_bodies: RefCell<VecDeque<String>>,
+    // the path of each get
+    pub get_paths: RefCell<Vec<String>>,
+    // the number of posts which time out before the response is returned, when is_timeout is false
+    pub timeouts_remaining: RefCell<u32>,
+    // the idempotency key sent with each post
+    pub idempotency_keys: RefCell<Vec<Option<String>>>,
     pub was_get_called: RefCell<bool>,
     pub was_post_called: RefCell<bool>,
-    pub was_delete_called: RefCell<bool>
+    pub was_patch_called: RefCell<bool>,
+    pub was_delete_called: RefCell<bool>,
+    // the path of the last delete
+    pub delete_path: RefCell<String>
 }
 
 impl MockWebConnection {
     pub fn new(method: Method, status: StatusCode, is_timeout: bool, return_body_text: String) -> Self {
         MockWebConnection {
-            method: method,
-            status: status,
-            return_body_text: return_body_text,
-            is_timeout: is_timeout,
+            method,
+            status,
+            return_body_text,
+            is_timeout,
+            queued_get_bodies: RefCell::new(VecDeque::new()),
+            get_paths: RefCell::new(Vec::new()),
+            timeouts_remaining: RefCell::new(0),
+            idempotency_keys: RefCell::new(Vec::new()),
             was_get_called: RefCell::new(false),
             was_post_called: RefCell::new(false),
-            was_delete_called: RefCell::new(false)
+            was_patch_called: RefCell::new(false),
+            was_delete_called: RefCell::new(false),
+            delete_path: RefCell::new(String::new())
         }
     }
 }
@@ -34,10 +52,11 @@ impl MockWebConnection {
 impl WebConnection for MockWebConnection {
     fn get(&self, path: String) -> Result<WebResponse, WebError> {
         *self.was_get_called.borrow_mut() = true;
+        self.get_paths.borrow_mut().push(path);
         match self.method {
-            Method::GET => Ok(WebResponse {
+            Method::Get => Ok(WebResponse {
                 status: self.status,
-                body: Ok(self.return_body_text.to_string())
+                body: Ok(self.queued_get_bodies.borrow_mut().pop_front().unwrap_or_else(|| self.return_body_text.to_string()))
             }),
             _ => Err(WebError {
                 is_timeout: false,
@@ -46,9 +65,14 @@ impl WebConnection for MockWebConnection {
         }
     }
 
-    fn post(&self, path: String, body: String) -> Result<WebResponse, WebError> {
+    fn post(&self, _path: String, _body: String, idempotency_key: Option<&str>) -> Result<WebResponse, WebError> {
         *self.was_post_called.borrow_mut() = true;
-        if self.is_timeout {
+        self.idempotency_keys.borrow_mut().push(idempotency_key.map(|k| k.to_string()));
+        let timeouts_remaining = *self.timeouts_remaining.borrow();
+        if timeouts_remaining > 0 {
+            *self.timeouts_remaining.borrow_mut() = timeouts_remaining - 1;
+        }
+        if self.is_timeout || timeouts_remaining > 0 {
             Err(WebError {
                 is_timeout: true,
                 text: "Timeout".to_string()
@@ -56,7 +80,7 @@ impl WebConnection for MockWebConnection {
         }
         else {
             match self.method {
-                Method::POST => Ok(WebResponse {
+                Method::Post => Ok(WebResponse {
                     status: self.status,
                     body: Ok(self.return_body_text.to_string())
                 }),
@@ -68,10 +92,25 @@ impl WebConnection for MockWebConnection {
         }
     }
 
+    fn patch(&self, _path: String, _body: String) -> Result<WebResponse, WebError> {
+        *self.was_patch_called.borrow_mut() = true;
+        match self.method {
+            Method::Patch => Ok(WebResponse {
+                status: self.status,
+                body: Ok(self.return_body_text.to_string())
+            }),
+            _ => Err(WebError {
+                is_timeout: false,
+                text: "PATCH failed".to_string()
+            })
+        }
+    }
+
     fn delete(&self, path: String) -> Result<WebResponse, WebError> {
         *self.was_delete_called.borrow_mut() = true;
+        *self.delete_path.borrow_mut() = path;
         match self.method {
-            Method::DELETE => Ok(WebResponse {
+            Method::Delete => Ok(WebResponse {
                 status: self.status,
                 body: Ok(self.return_body_text.to_string())
             }),
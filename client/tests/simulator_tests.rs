@@ -27,7 +27,7 @@ mod tests {
         // assert
         assert!(ret.contains(mock_client_function_interface::DEFAULT_RETURN_ORDER_MENU_NAME));
         assert_eq!(added_item_cache.len(), 1);
-        assert_eq!(added_item_cache.get(0).unwrap().order_id, mock_client_function_interface::DEFAULT_RETURN_ORDER_ID);
+        assert_eq!(added_item_cache.first().unwrap().order_id, mock_client_function_interface::DEFAULT_RETURN_ORDER_ID);
         assert!(*injection_params.client_functions.was_add_orders_called.borrow());
     }
     
@@ -125,13 +125,13 @@ mod tests {
     fn query_random_table_item_success() {
         // setup
         let mut injection_params = get_mock_injection_params();
-        let mut added_item_cache = vec![TableOrderPair {
+        let added_item_cache = vec![TableOrderPair {
             table_id: 3,
             order_id: 2
         }];
 
         // execute
-        let ret = sim::query_random_table_item(&mut injection_params, 1, &mut added_item_cache);
+        let ret = sim::query_random_table_item(&mut injection_params, 1, &added_item_cache);
         
         // assert
         assert!(!ret.contains("including"));
@@ -144,13 +144,13 @@ mod tests {
         // setup
         let mut injection_params = get_mock_injection_params();
         *injection_params.client_functions.should_fail.borrow_mut() = true;
-        let mut added_item_cache = vec![TableOrderPair {
+        let added_item_cache = vec![TableOrderPair {
             table_id: 3,
             order_id: 2
         }];
 
         // execute
-        let ret = sim::query_random_table_item(&mut injection_params, 1, &mut added_item_cache);
+        let ret = sim::query_random_table_item(&mut injection_params, 1, &added_item_cache);
         
         // assert
         assert!(ret.contains("error"));
@@ -160,10 +160,10 @@ mod tests {
     fn query_random_table_item_none_to_query() {
         // setup
         let mut injection_params = get_mock_injection_params();
-        let mut added_item_cache = Vec::<TableOrderPair>::new();
+        let added_item_cache = Vec::<TableOrderPair>::new();
 
         // execute
-        let ret = sim::query_random_table_item(&mut injection_params, 1, &mut added_item_cache);
+        let ret = sim::query_random_table_item(&mut injection_params, 1, &added_item_cache);
         
         // assert
         assert!(ret.contains("none to query"));
@@ -0,0 +1,168 @@
+use std::cmp::Reverse;
+use std::collections::HashSet;
+
+use crate::errors::server_error::ServerError;
+use crate::rest_responses;
+
+pub enum BillSplit {
+    // One sub-bill per seat, plus one for orders without a seat
+    Seat,
+    // The total divided evenly between the given number of guests
+    Even(u32),
+    // One sub-bill per group of order IDs, plus one for any orders left out of the groups
+    Orders(Vec<Vec<u32>>)
+}
+
+impl BillSplit {
+    // Builds a split from the `by`, `ways` and `groups` query parameters of the split bill endpoint.
+    // Each group is a comma separated list of order IDs.
+    pub fn from_query(by: &str, ways: Option<u32>, groups: &[String]) -> Result<BillSplit, ServerError> {
+        match by {
+            "seat" => Result::Ok(BillSplit::Seat),
+            "even" => match ways {
+                Some(ways) if ways > 0 => Result::Ok(BillSplit::Even(ways)),
+                _ => Err(ServerError::InvalidSplit("An even split requires ways to be at least 1.".to_string()))
+            },
+            "orders" => groups.iter()
+                .map(|group| group.split(',')
+                    .map(|id| id.trim().parse::<u32>())
+                    .collect::<Result<Vec<u32>, _>>())
+                .collect::<Result<Vec<Vec<u32>>, _>>()
+                .map(BillSplit::Orders)
+                .map_err(|_| ServerError::InvalidSplit("Groups must be comma separated lists of order IDs.".to_string())),
+            _ => Err(ServerError::InvalidSplit("by must be one of seat, even or orders.".to_string()))
+        }
+    }
+}
+
+// Splits a bill into sub-bills whose subtotals, taxes and totals add up exactly to those of the original bill
+pub fn split_bill(bill: rest_responses::Bill, split: BillSplit) -> Result<rest_responses::SplitBill, ServerError> {
+    let sub_bills = match split {
+        BillSplit::Seat => split_by_seat(&bill),
+        BillSplit::Even(ways) => split_evenly(&bill, ways),
+        BillSplit::Orders(groups) => split_by_orders(&bill, groups)?
+    };
+
+    Result::Ok(
+        rest_responses::SplitBill {
+            table_number: bill.table_number,
+            bills: sub_bills,
+            subtotal: bill.subtotal,
+            tax: bill.tax,
+            total: bill.total
+        }
+    )
+}
+
+fn split_by_seat(bill: &rest_responses::Bill) -> Vec<rest_responses::SubBill> {
+    let mut seats = bill.line_items.iter()
+        .filter_map(|i| i.seat)
+        .collect::<Vec<u32>>();
+    seats.sort();
+    seats.dedup();
+
+    let mut groups = seats.iter()
+        .map(|seat| (Some(*seat), bill.line_items.iter().filter(|i| i.seat == Some(*seat)).cloned().collect()))
+        .collect::<Vec<(Option<u32>, Vec<rest_responses::BillLineItem>)>>();
+
+    let unassigned = bill.line_items.iter()
+        .filter(|i| i.seat.is_none())
+        .cloned()
+        .collect::<Vec<rest_responses::BillLineItem>>();
+    if !unassigned.is_empty() {
+        groups.push((None, unassigned));
+    }
+
+    allocate_tax(bill.tax, groups)
+}
+
+fn split_evenly(bill: &rest_responses::Bill, ways: u32) -> Vec<rest_responses::SubBill> {
+    let subtotals = divide_evenly(bill.subtotal, ways);
+    let taxes = divide_evenly(bill.tax, ways);
+    subtotals.into_iter()
+        .zip(taxes)
+        .map(|(subtotal, tax)| rest_responses::SubBill {
+            seat: None,
+            line_items: Vec::new(),
+            subtotal,
+            tax,
+            total: subtotal + tax
+        })
+        .collect()
+}
+
+fn split_by_orders(bill: &rest_responses::Bill, groups: Vec<Vec<u32>>) -> Result<Vec<rest_responses::SubBill>, ServerError> {
+    let mut seen = HashSet::new();
+    let mut line_item_groups = Vec::new();
+    for group in groups {
+        let mut line_items = Vec::new();
+        for order_id in group {
+            if !seen.insert(order_id) {
+                return Err(ServerError::InvalidSplit(format!("Order {order_id} is in more than one group.")));
+            }
+            match bill.line_items.iter().find(|i| i.order_id == order_id) {
+                Some(line_item) => line_items.push(line_item.clone()),
+                None => return Err(ServerError::InvalidSplit(format!("Order {order_id} is not on the bill for this table.")))
+            }
+        }
+        line_item_groups.push((None, line_items));
+    }
+
+    let remaining = bill.line_items.iter()
+        .filter(|i| !seen.contains(&i.order_id))
+        .cloned()
+        .collect::<Vec<rest_responses::BillLineItem>>();
+    if !remaining.is_empty() {
+        line_item_groups.push((None, remaining));
+    }
+
+    Result::Ok(allocate_tax(bill.tax, line_item_groups))
+}
+
+// Divides an amount into the given number of parts. Any remainder is given
+// one unit at a time to the first parts, so that the parts add up to the amount.
+fn divide_evenly(amount: u64, ways: u32) -> Vec<u64> {
+    let ways = u64::from(ways);
+    (0..ways)
+        .map(|i| amount / ways + u64::from(i < amount % ways))
+        .collect()
+}
+
+// Gives each group a share of the bill's tax in proportion to its subtotal. Shares are rounded down,
+// and the units lost to rounding are given to the groups which lost the most, so that the shares
+// add up to the bill's tax.
+fn allocate_tax(tax: u64, groups: Vec<(Option<u32>, Vec<rest_responses::BillLineItem>)>) -> Vec<rest_responses::SubBill> {
+    let subtotals = groups.iter()
+        .map(|(_, line_items)| line_items.iter().map(|i| u64::from(i.price)).sum::<u64>())
+        .collect::<Vec<u64>>();
+    let subtotal = subtotals.iter().sum::<u64>();
+
+    let mut taxes = subtotals.iter()
+        .map(|s| match subtotal {
+            0 => 0,
+            _ => tax * s / subtotal
+        })
+        .collect::<Vec<u64>>();
+
+    let mut by_remainder = (0..subtotals.len()).collect::<Vec<usize>>();
+    by_remainder.sort_by_key(|i| match subtotal {
+        0 => Reverse(0),
+        _ => Reverse(tax * subtotals[*i] % subtotal)
+    });
+    let leftover = tax - taxes.iter().sum::<u64>();
+    for i in by_remainder.into_iter().take(leftover as usize) {
+        taxes[i] += 1;
+    }
+
+    groups.into_iter()
+        .zip(subtotals)
+        .zip(taxes)
+        .map(|(((seat, line_items), subtotal), tax)| rest_responses::SubBill {
+            seat,
+            line_items,
+            subtotal,
+            tax,
+            total: subtotal + tax
+        })
+        .collect()
+}
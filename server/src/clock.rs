@@ -0,0 +1,16 @@
+use std::time::{ SystemTime, UNIX_EPOCH };
+
+pub trait Clock: Send + Sync {
+    // The current time as a unix timestamp in seconds
+    fn now(&self) -> u64;
+}
+
+pub struct DefaultClock {}
+
+impl Clock for DefaultClock {
+    fn now(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,48 @@
+use std::path::Path;
+use std::time::Duration;
+use r2d2::{ Pool, PooledConnection };
+use r2d2_sqlite::SqliteConnectionManager;
+
+use crate::errors::server_error::ServerError;
+
+// How long a connection waits for another connection's write to finish before giving up with SQLITE_BUSY
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+// A connection taken from the pool. It is returned to the pool when dropped.
+pub type DatabaseConnection = PooledConnection<SqliteConnectionManager>;
+
+// A pool of connections to a SQLite database, which are opened as they are needed and then reused.
+// Connections use WAL journaling, so that reads are not blocked by a write, and wait for other writes to finish
+// rather than failing straight away. Clones share the same pool.
+#[derive(Clone)]
+pub struct DatabasePool {
+    pool: Pool<SqliteConnectionManager>
+}
+
+impl DatabasePool {
+    pub fn new(path: impl AsRef<Path>, size: u32) -> Result<DatabasePool, ServerError> {
+        if size == 0 {
+            return Err(ServerError::StorageError("The database pool size must be at least 1".to_string()));
+        }
+
+        let manager = SqliteConnectionManager::file(path)
+            .with_init(|connection| {
+                connection.busy_timeout(BUSY_TIMEOUT)?;
+                connection.execute_batch("PRAGMA journal_mode = WAL;")
+            });
+        // connections are only opened once they are first needed, so that a pool can be created without blocking
+        let pool = Pool::builder()
+            .max_size(size)
+            .min_idle(Some(0))
+            .build_unchecked(manager);
+
+        Result::Ok(DatabasePool {
+            pool
+        })
+    }
+
+    // Waits for a connection to become free if they are all in use
+    pub fn open(&self) -> Result<DatabaseConnection, ServerError> {
+        self.pool.get().map_err(|e| ServerError::StorageError(e.to_string()))
+    }
+}
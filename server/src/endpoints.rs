@@ -1,18 +1,66 @@
-use rocket::{ catch, delete, get, post };
+use std::io;
+use std::sync::Arc;
+use rocket::{ catch, delete, get, patch, post, put };
 use rusqlite::Result;
 use rocket::http::{ Status, ContentType };
 use rocket::serde::json::{ Json, to_string };
+use rocket::futures::{ SinkExt, StreamExt };
+use rocket::response::stream::{ Event, EventStream };
+use rocket::tokio::{ self, select };
 use rocket::Request;
+use rocket::Shutdown;
 use rocket::State;
+use rocket_ws::{ Channel, Message, WebSocket };
 
 use crate::rest_bodies;
 use crate::errors::server_error::ServerError;
-use crate::server_functions;
-use crate::database_connector::DatabaseConnector;
+use crate::repositories::{ IdempotencyRepository, MenuRepository, OrderRepository, TableRepository };
+use crate::clock::Clock;
+use crate::random_number_generator::RandomNumberGenerator;
+use crate::bill_split::BillSplit;
+use crate::order_query::{ OrderQuery, OrderQueryParams, TableFilter };
+use crate::settings::Settings;
+use crate::rules::validate_cook_time;
+use crate::idempotency::{ idempotent, IdempotencyKey, IdempotentResponse };
+use crate::order_events::{ LastEventId, OrderEventNotifier, ORDER_EVENT_BATCH_SIZE, ORDER_EVENT_POLL_INTERVAL };
+use crate::kitchen::{ self, KitchenMessage };
 
-#[get("/tables/<table_number>/orders")]
-pub fn get_table_orders(table_number: u32, database_connector: &State<Box<dyn DatabaseConnector>>) -> (Status, (ContentType, String)) {
-    match server_functions::get_orders(database_connector.inner().as_ref(), table_number) {
+// A status with a JSON error message, for endpoints which otherwise respond with something other than JSON.
+// It is boxed in results, since it is much larger than a stream.
+type ErrorResponse = (Status, (ContentType, String));
+
+// Orders are sorted by id unless sort=minutes_to_cook is given. With a limit, next_cursor is set when there are more
+// orders, and passing it as the cursor returns the next page.
+#[get("/tables/<table_number>/orders?<params..>")]
+pub fn get_table_orders(table_number: u32, params: OrderQueryParams, order_repository: &State<Arc<dyn OrderRepository>>, clock: &State<Arc<dyn Clock>>) -> (Status, (ContentType, String)) {
+    let query = match OrderQuery::from_params(&params) {
+        Result::Ok(query) => query,
+        Result::Err(ServerError::InvalidOrderQuery(message)) => return (Status::UnprocessableEntity, (ContentType::JSON, error_json(&message))),
+        Result::Err(_) => return (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to get data.\" }".to_string()))
+    };
+
+    match order_repository.get_orders(clock.inner().as_ref(), table_number, &query) {
+        Result::Ok(items) => match to_string(&items) {
+            Result::Ok(item_string) => (Status::Ok, (ContentType::JSON, item_string)),
+            Result::Err(_) => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to get data.\" }".to_string()))
+        },
+        Result::Err(_) => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to get data.\" }".to_string()))
+    }
+}
+
+// Lists open orders across the whole restaurant, for kitchen screens and managers. Each order includes its table_number.
+// Takes the same filters, sorting and pagination as get_table_orders, along with ?tables=1,2,10-20 to only include some tables.
+#[get("/orders?<tables>&<params..>")]
+pub fn get_orders(tables: Option<&str>, params: OrderQueryParams, order_repository: &State<Arc<dyn OrderRepository>>, clock: &State<Arc<dyn Clock>>) -> (Status, (ContentType, String)) {
+    let query = TableFilter::from_param(tables)
+        .and_then(|tables| OrderQuery::from_params(&params).map(|query| (tables, query)));
+    let (tables, query) = match query {
+        Result::Ok(query) => query,
+        Result::Err(ServerError::InvalidOrderQuery(message)) => return (Status::UnprocessableEntity, (ContentType::JSON, error_json(&message))),
+        Result::Err(_) => return (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to get data.\" }".to_string()))
+    };
+
+    match order_repository.get_all_orders(clock.inner().as_ref(), &tables, &query) {
         Result::Ok(items) => match to_string(&items) {
             Result::Ok(item_string) => (Status::Ok, (ContentType::JSON, item_string)),
             Result::Err(_) => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to get data.\" }".to_string()))
@@ -21,49 +69,370 @@ pub fn get_table_orders(table_number: u32, database_connector: &State<Box<dyn Da
     }
 }
 
+// Streams order_created, order_updated and order_deleted events as Server-Sent Events, each carrying the order as JSON
+// and the event's id. A client reconnecting with the Last-Event-ID header is sent every event it missed since then;
+// otherwise the stream starts with the next change. ?tables=1,2,10-20 only includes orders on (or moved off) those tables.
+#[get("/events?<tables>")]
+pub fn get_events<'r>(tables: Option<&str>, last_event_id: LastEventId, order_repository: &'r State<Arc<dyn OrderRepository>>, notifier: &'r State<OrderEventNotifier>, mut shutdown: Shutdown) -> Result<EventStream![Event + 'r], Box<ErrorResponse>> {
+    let tables = match TableFilter::from_param(tables) {
+        Result::Ok(tables) => tables,
+        Result::Err(ServerError::InvalidOrderQuery(message)) => return Err(Box::new((Status::UnprocessableEntity, (ContentType::JSON, error_json(&message))))),
+        Result::Err(_) => return Err(Box::new((Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to get data.\" }".to_string()))))
+    };
+    let mut last_id = match last_event_id.0 {
+        Some(id) => id,
+        None => order_repository.get_last_order_event_id()
+            .map_err(|_| Box::new((Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to get data.\" }".to_string()))))?
+    };
+
+    let order_repository = Arc::clone(order_repository.inner());
+    Result::Ok(EventStream! {
+        loop {
+            // listen for changes before reading the log, so that a change made in between is not missed
+            let notified = notifier.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            // the log is read on a blocking thread, so that waiting on the database does not hold up other requests
+            let (repository, filter) = (Arc::clone(&order_repository), tables.clone());
+            let events = match tokio::task::spawn_blocking(move || repository.get_order_events(&filter, last_id, ORDER_EVENT_BATCH_SIZE)).await {
+                Result::Ok(Result::Ok(events)) => events,
+                Result::Ok(Result::Err(e)) => {
+                    eprintln!("Failed to read order events: {e}");
+                    Vec::new()
+                },
+                Result::Err(e) => {
+                    eprintln!("Failed to read order events: {e}");
+                    Vec::new()
+                }
+            };
+            let caught_up = events.len() < ORDER_EVENT_BATCH_SIZE as usize;
+            for event in events {
+                last_id = event.id;
+                yield Event::data(event.data).event(event.kind.as_str()).id(event.id.to_string());
+            }
+            if !caught_up {
+                continue;
+            }
+
+            select! {
+                _ = &mut notified => {},
+                _ = tokio::time::sleep(ORDER_EVENT_POLL_INTERVAL) => {},
+                _ = &mut shutdown => break
+            }
+        }
+    })
+}
+
+// A WebSocket channel for the kitchen display, which is sent order events once it subscribes and acknowledges orders
+// as they are started and finished. The JSON message protocol is described in kitchen.rs.
+#[get("/kitchen")]
+pub fn kitchen_channel<'r>(ws: WebSocket, order_repository: &'r State<Arc<dyn OrderRepository>>, clock: &'r State<Arc<dyn Clock>>, notifier: &'r State<OrderEventNotifier>, mut shutdown: Shutdown) -> Channel<'r> {
+    let order_repository = Arc::clone(order_repository.inner());
+    let clock = Arc::clone(clock.inner());
+    ws.channel(move |mut stream| Box::pin(async move {
+        let mut subscription = None;
+        let mut caught_up = true;
+        loop {
+            // listen for changes before reading the log, so that a change made in between is not missed
+            let notified = notifier.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            // the database is only used from blocking threads, as in the event stream. The subscription is handed to
+            // the thread and back, since reading events advances it.
+            if let Some(current) = subscription.take() {
+                let repository = Arc::clone(&order_repository);
+                let (next, read) = tokio::task::spawn_blocking(move || {
+                    let mut next = current;
+                    let read = kitchen::next_events(repository.as_ref(), &mut next);
+                    (next, read)
+                }).await.map_err(|e| io::Error::other(e.to_string()))?;
+                subscription = Some(next);
+                match read {
+                    Result::Ok((messages, events_caught_up)) => {
+                        caught_up = events_caught_up;
+                        for message in messages {
+                            stream.send(Message::Text(message.to_json())).await?;
+                        }
+                    },
+                    Result::Err(e) => eprintln!("Failed to read order events: {e}")
+                }
+            }
+            if !caught_up {
+                continue;
+            }
+
+            select! {
+                message = stream.next() => match message {
+                    Some(Result::Ok(Message::Text(text))) => {
+                        let (repository, clock, current) = (Arc::clone(&order_repository), Arc::clone(&clock), subscription.take());
+                        let (next, reply) = tokio::task::spawn_blocking(move || {
+                            let mut next = current;
+                            let reply = kitchen::handle_request(repository.as_ref(), clock.as_ref(), &text, &mut next);
+                            (next, reply)
+                        }).await.map_err(|e| io::Error::other(e.to_string()))?;
+                        subscription = next;
+                        // acks do not go through an HTTP request, so event streams are woken up here instead
+                        if let KitchenMessage::Acked { .. } = reply {
+                            notifier.notify();
+                        }
+                        stream.send(Message::Text(reply.to_json())).await?;
+                    },
+                    Some(Result::Ok(Message::Close(_))) | None => break,
+                    Some(Result::Ok(_)) => {},
+                    Some(Result::Err(e)) => return Err(e)
+                },
+                _ = &mut notified => {},
+                _ = tokio::time::sleep(ORDER_EVENT_POLL_INTERVAL) => {},
+                _ = &mut shutdown => break
+            }
+        }
+        Result::Ok(())
+    }))
+}
+
 #[post("/tables/<table_id>/orders", format = "json", data = "<orders_data>")]
-pub fn post_table_order(table_id: u32, orders_data: Json<rest_bodies::Orders>, database_connector: &State<Box<dyn DatabaseConnector>>) -> (Status, (ContentType, String)) {
-    match server_functions::add_orders(database_connector.inner().as_ref(), table_id, orders_data.into_inner()) {
+pub fn post_table_order(table_id: u32, orders_data: Json<rest_bodies::Orders>, idempotency_key: IdempotencyKey, order_repository: &State<Arc<dyn OrderRepository>>, clock: &State<Arc<dyn Clock>>, rng: &State<Box<dyn RandomNumberGenerator>>, settings: &State<Settings>) -> IdempotentResponse {
+    let orders = orders_data.into_inner();
+    let idempotency_key = idempotency_key.or_body_key(orders.idempotency_key.clone());
+    let body = to_string(&orders.orders).unwrap_or_default();
+    idempotent(&idempotency_key, &body, || {
+        match order_repository.add_orders(clock.inner().as_ref(), rng.inner().as_ref(), settings.inner(), table_id, orders) {
+            Result::Ok(items) => match to_string(&items) {
+                Result::Ok(item_string) => (Status::Ok, (ContentType::JSON, item_string)),
+                Result::Err(_) => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to add data.\" }".to_string()))
+            },
+            Result::Err(e) => match e {
+                ServerError::TableNotFound => (Status::NotFound, (ContentType::JSON, "{ \"error\": \"Provided table does not exist.\" }".to_string())),
+                ServerError::TableClosed => (Status::Conflict, (ContentType::JSON, "{ \"error\": \"Provided table is closed.\" }".to_string())),
+                ServerError::MenuItemNotFound => (Status::UnprocessableEntity, (ContentType::JSON, "{ \"error\": \"The provided menu_item_id does not exist or has been retired.\" }".to_string())),
+                ServerError::InvalidOrder(message) => (Status::UnprocessableEntity, (ContentType::JSON, error_json(&message))),
+                _ => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to add data.\" }".to_string()))
+            }
+        }
+    })
+}
+
+#[get("/tables/<table_number>/orders/<order_id>")]
+pub fn get_table_order(table_number: u32, order_id: u32, order_repository: &State<Arc<dyn OrderRepository>>, clock: &State<Arc<dyn Clock>>) -> (Status, (ContentType, String)) {
+    match order_repository.get_order(clock.inner().as_ref(), table_number, order_id) {
         Result::Ok(items) => match to_string(&items) {
             Result::Ok(item_string) => (Status::Ok, (ContentType::JSON, item_string)),
+            Result::Err(_) => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to get data.\" }".to_string()))
+        },
+        Result::Err(e) => match e {
+            ServerError::DataNotFound => (Status::NotFound, (ContentType::JSON, "{ \"error\": \"Provided order does not exist for provided table.\" }".to_string())),
+            _ => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to get data.\" }".to_string()))
+        }
+    }
+}
+
+// The ids parameter is a comma separated list of order IDs, e.g. ?ids=1,2,3. Without it, all of the table's open orders are deleted.
+#[delete("/tables/<table_number>/orders?<ids>")]
+pub fn delete_table_orders(table_number: u32, ids: Option<&str>, idempotency_key: IdempotencyKey, order_repository: &State<Arc<dyn OrderRepository>>, clock: &State<Arc<dyn Clock>>) -> IdempotentResponse {
+    idempotent(&idempotency_key, "", || {
+        let ids = match ids.map(|ids| ids.split(',').map(|id| id.trim().parse::<u32>()).collect::<Result<Vec<u32>, _>>()).transpose() {
+            Result::Ok(ids) => ids,
+            Result::Err(_) => return (Status::UnprocessableEntity, (ContentType::JSON, "{ \"error\": \"ids must be a comma separated list of order IDs.\" }".to_string()))
+        };
+        match order_repository.delete_orders(clock.inner().as_ref(), table_number, ids.as_deref()) {
+            Result::Ok(items) => match to_string(&items) {
+                Result::Ok(item_string) => (Status::Ok, (ContentType::JSON, item_string)),
+                Result::Err(_) => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to delete data.\" }".to_string()))
+            },
+            Result::Err(e) => match e {
+                ServerError::DataNotFound => (Status::NotFound, (ContentType::JSON, "{ \"error\": \"One or more of the provided orders do not exist for provided table.\" }".to_string())),
+                _ => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to delete data.\" }".to_string()))
+            }
+        }
+    })
+}
+
+// Deleting an order which does not exist is a 404, unless missing_ok is set, in which case it is treated as already deleted
+#[delete("/tables/<table_number>/orders/<order_id>?<missing_ok>")]
+pub fn delete_table_order(table_number: u32, order_id: u32, missing_ok: Option<bool>, idempotency_key: IdempotencyKey, order_repository: &State<Arc<dyn OrderRepository>>, clock: &State<Arc<dyn Clock>>) -> IdempotentResponse {
+    idempotent(&idempotency_key, "", || {
+        match order_repository.delete_order(clock.inner().as_ref(), table_number, order_id) {
+            Result::Ok(()) => (Status::NoContent, (ContentType::JSON, "{}".to_string())),
+            Result::Err(e) => match e {
+                ServerError::DataNotFound if missing_ok.unwrap_or(false) => (Status::NoContent, (ContentType::JSON, "{}".to_string())),
+                ServerError::DataNotFound => (Status::NotFound, (ContentType::JSON, "{ \"error\": \"Provided order does not exist for provided table.\" }".to_string())),
+                _ => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to delete data.\" }".to_string()))
+            }
+        }
+    })
+}
+
+#[patch("/tables/<table_number>/orders/<order_id>", format = "json", data = "<update_data>")]
+pub fn patch_table_order(table_number: u32, order_id: u32, update_data: Json<rest_bodies::OrderUpdate>, order_repository: &State<Arc<dyn OrderRepository>>, clock: &State<Arc<dyn Clock>>, rng: &State<Box<dyn RandomNumberGenerator>>, settings: &State<Settings>) -> (Status, (ContentType, String)) {
+    match order_repository.update_order(clock.inner().as_ref(), rng.inner().as_ref(), settings.inner(), table_number, order_id, update_data.into_inner()) {
+        Result::Ok(item) => match to_string(&item) {
+            Result::Ok(item_string) => (Status::Ok, (ContentType::JSON, item_string)),
+            Result::Err(_) => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to update data.\" }".to_string()))
+        },
+        Result::Err(e) => match e {
+            ServerError::DataNotFound => (Status::NotFound, (ContentType::JSON, "{ \"error\": \"Provided order does not exist for provided table.\" }".to_string())),
+            ServerError::MenuItemNotFound => (Status::UnprocessableEntity, (ContentType::JSON, "{ \"error\": \"The provided menu_item_id does not exist or has been retired.\" }".to_string())),
+            ServerError::TableNotFound | ServerError::TableClosed => (Status::UnprocessableEntity, (ContentType::JSON, "{ \"error\": \"The provided table_number does not exist or is closed.\" }".to_string())),
+            ServerError::InvalidOrder(message) => (Status::UnprocessableEntity, (ContentType::JSON, error_json(&message))),
+            ServerError::OrderFinal(status) => (Status::Conflict, (ContentType::JSON, error_json(&format!("A {} order can no longer be changed.", status.as_str())))),
+            _ => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to update data.\" }".to_string()))
+        }
+    }
+}
+
+#[put("/tables/<table_number>/orders/<order_id>/status", format = "json", data = "<status_data>")]
+pub fn put_table_order_status(table_number: u32, order_id: u32, status_data: Json<rest_bodies::OrderStatus>, order_repository: &State<Arc<dyn OrderRepository>>, clock: &State<Arc<dyn Clock>>) -> (Status, (ContentType, String)) {
+    match order_repository.update_order_status(clock.inner().as_ref(), table_number, order_id, status_data.into_inner().status) {
+        Result::Ok(item) => match to_string(&item) {
+            Result::Ok(item_string) => (Status::Ok, (ContentType::JSON, item_string)),
+            Result::Err(_) => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to update data.\" }".to_string()))
+        },
+        Result::Err(e) => match e {
+            ServerError::DataNotFound => (Status::NotFound, (ContentType::JSON, "{ \"error\": \"Provided order does not exist for provided table.\" }".to_string())),
+            ServerError::InvalidStatusTransition(from, to) => (Status::Conflict, (ContentType::JSON, error_json(&format!("An order cannot go from {} to {}.", from.as_str(), to.as_str())))),
+            _ => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to update data.\" }".to_string()))
+        }
+    }
+}
+
+#[get("/tables/<table_number>/bill")]
+pub fn get_table_bill(table_number: u32, order_repository: &State<Arc<dyn OrderRepository>>) -> (Status, (ContentType, String)) {
+    match order_repository.get_bill(table_number) {
+        Result::Ok(bill) => match to_string(&bill) {
+            Result::Ok(bill_string) => (Status::Ok, (ContentType::JSON, bill_string)),
+            Result::Err(_) => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to get data.\" }".to_string()))
+        },
+        Result::Err(_) => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to get data.\" }".to_string()))
+    }
+}
+
+// Each groups parameter is a comma separated list of order IDs, e.g. ?by=orders&groups=1,2&groups=3
+#[get("/tables/<table_number>/bill/split?<by>&<ways>&<groups>")]
+pub fn get_table_split_bill(table_number: u32, by: &str, ways: Option<u32>, groups: Vec<String>, order_repository: &State<Arc<dyn OrderRepository>>) -> (Status, (ContentType, String)) {
+    let split = match BillSplit::from_query(by, ways, &groups) {
+        Result::Ok(split) => split,
+        Result::Err(ServerError::InvalidSplit(message)) => return (Status::UnprocessableEntity, (ContentType::JSON, error_json(&message))),
+        Result::Err(_) => return (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to get data.\" }".to_string()))
+    };
+
+    match order_repository.get_split_bill(table_number, split) {
+        Result::Ok(split_bill) => match to_string(&split_bill) {
+            Result::Ok(split_bill_string) => (Status::Ok, (ContentType::JSON, split_bill_string)),
+            Result::Err(_) => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to get data.\" }".to_string()))
+        },
+        Result::Err(e) => match e {
+            ServerError::InvalidSplit(message) => (Status::UnprocessableEntity, (ContentType::JSON, error_json(&message))),
+            _ => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to get data.\" }".to_string()))
+        }
+    }
+}
+
+#[post("/tables/<table_number>/transfer", format = "json", data = "<transfer_data>")]
+pub fn post_table_transfer(table_number: u32, transfer_data: Json<rest_bodies::Transfer>, idempotency_key: IdempotencyKey, order_repository: &State<Arc<dyn OrderRepository>>, clock: &State<Arc<dyn Clock>>, settings: &State<Settings>) -> IdempotentResponse {
+    let transfer = transfer_data.into_inner();
+    let body = to_string(&transfer).unwrap_or_default();
+    idempotent(&idempotency_key, &body, || {
+        match order_repository.transfer_orders(clock.inner().as_ref(), settings.inner(), table_number, transfer) {
+            Result::Ok(items) => match to_string(&items) {
+                Result::Ok(item_string) => (Status::Ok, (ContentType::JSON, item_string)),
+                Result::Err(_) => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to transfer orders.\" }".to_string()))
+            },
+            Result::Err(e) => match e {
+                ServerError::DataNotFound => (Status::NotFound, (ContentType::JSON, "{ \"error\": \"One or more of the provided orders do not exist for provided table.\" }".to_string())),
+                ServerError::TableNotFound | ServerError::TableClosed => (Status::UnprocessableEntity, (ContentType::JSON, "{ \"error\": \"The provided table_number does not exist or is closed.\" }".to_string())),
+                ServerError::InvalidTable(message) => (Status::UnprocessableEntity, (ContentType::JSON, error_json(&message))),
+                _ => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to transfer orders.\" }".to_string()))
+            }
+        }
+    })
+}
+
+#[post("/tables/<table_number>/checkout")]
+pub fn post_table_checkout(table_number: u32, order_repository: &State<Arc<dyn OrderRepository>>, clock: &State<Arc<dyn Clock>>) -> (Status, (ContentType, String)) {
+    match order_repository.checkout(clock.inner().as_ref(), table_number) {
+        Result::Ok(bill) => match to_string(&bill) {
+            Result::Ok(bill_string) => (Status::Ok, (ContentType::JSON, bill_string)),
+            Result::Err(_) => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to check out.\" }".to_string()))
+        },
+        Result::Err(e) => match e {
+            ServerError::DataNotFound => (Status::NotFound, (ContentType::JSON, "{ \"error\": \"Provided table has no open orders.\" }".to_string())),
+            _ => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to check out.\" }".to_string()))
+        }
+    }
+}
+
+#[get("/tables")]
+pub fn get_tables(table_repository: &State<Arc<dyn TableRepository>>) -> (Status, (ContentType, String)) {
+    match table_repository.get_tables() {
+        Result::Ok(tables) => match to_string(&tables) {
+            Result::Ok(tables_string) => (Status::Ok, (ContentType::JSON, tables_string)),
+            Result::Err(_) => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to get data.\" }".to_string()))
+        },
+        Result::Err(_) => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to get data.\" }".to_string()))
+    }
+}
+
+#[post("/tables", format = "json", data = "<table_data>")]
+pub fn post_table(table_data: Json<rest_bodies::Table>, table_repository: &State<Arc<dyn TableRepository>>) -> (Status, (ContentType, String)) {
+    match table_repository.add_table(table_data.into_inner()) {
+        Result::Ok(table) => match to_string(&table) {
+            Result::Ok(table_string) => (Status::Created, (ContentType::JSON, table_string)),
             Result::Err(_) => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to add data.\" }".to_string()))
         },
         Result::Err(e) => match e {
-            ServerError::Idempotency => (Status::Conflict, (ContentType::JSON, "{ \"error\": \"This order has already been added.\" }".to_string())),
-            ServerError::DataNotFound => (Status::UnprocessableEntity, (ContentType::JSON, "{ \"error\": \"The provided menu_item_id does not exist.\" }".to_string())),
+            ServerError::DuplicateTable => (Status::Conflict, (ContentType::JSON, "{ \"error\": \"A table with this number already exists.\" }".to_string())),
+            ServerError::InvalidTable(message) => (Status::UnprocessableEntity, (ContentType::JSON, error_json(&message))),
             _ => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to add data.\" }".to_string()))
         }
     }
 }
 
-#[get("/tables/<table_number>/orders/<order_id>")]
-pub fn get_table_order(table_number: u32, order_id: u32, database_connector: &State<Box<dyn DatabaseConnector>>) -> (Status, (ContentType, String)) {
-    match server_functions::get_order(database_connector.inner().as_ref(), table_number, order_id) {
-        Result::Ok(items) => match to_string(&items) {
-            Result::Ok(item_string) => (Status::Ok, (ContentType::JSON, item_string)),
+#[get("/tables/<table_number>")]
+pub fn get_table(table_number: u32, table_repository: &State<Arc<dyn TableRepository>>) -> (Status, (ContentType, String)) {
+    match table_repository.get_table(table_number) {
+        Result::Ok(table) => match to_string(&table) {
+            Result::Ok(table_string) => (Status::Ok, (ContentType::JSON, table_string)),
             Result::Err(_) => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to get data.\" }".to_string()))
         },
         Result::Err(e) => match e {
-            ServerError::DataNotFound => (Status::NotFound, (ContentType::JSON, "{ \"error\": \"Provided order does not exist for provided table.\" }".to_string())),
+            ServerError::DataNotFound => (Status::NotFound, (ContentType::JSON, "{ \"error\": \"Provided table does not exist.\" }".to_string())),
             _ => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to get data.\" }".to_string()))
         }
     }
 }
 
-#[delete("/tables/<table_number>/orders/<order_id>")]
-pub fn delete_table_order(table_number: u32, order_id: u32, database_connector: &State<Box<dyn DatabaseConnector>>) -> (Status, (ContentType, String)) {
-    match server_functions::delete_order(database_connector.inner().as_ref(), table_number, order_id) {
-        Result::Ok(items) => match to_string(&items) {
-            Result::Ok(_) => (Status::NoContent, (ContentType::JSON, "{}".to_string())),
-            Result::Err(_) => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to delete data.\" }".to_string()))
+#[patch("/tables/<table_number>", format = "json", data = "<update_data>")]
+pub fn patch_table(table_number: u32, update_data: Json<rest_bodies::TableUpdate>, table_repository: &State<Arc<dyn TableRepository>>) -> (Status, (ContentType, String)) {
+    match table_repository.update_table(table_number, update_data.into_inner()) {
+        Result::Ok(table) => match to_string(&table) {
+            Result::Ok(table_string) => (Status::Ok, (ContentType::JSON, table_string)),
+            Result::Err(_) => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to update data.\" }".to_string()))
         },
-        Result::Err(_) => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to delete data.\" }".to_string()))
+        Result::Err(e) => match e {
+            ServerError::DataNotFound => (Status::NotFound, (ContentType::JSON, "{ \"error\": \"Provided table does not exist.\" }".to_string())),
+            ServerError::InvalidTable(message) => (Status::UnprocessableEntity, (ContentType::JSON, error_json(&message))),
+            _ => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to update data.\" }".to_string()))
+        }
+    }
+}
+
+#[delete("/tables/<table_number>")]
+pub fn delete_table(table_number: u32, table_repository: &State<Arc<dyn TableRepository>>) -> (Status, (ContentType, String)) {
+    match table_repository.delete_table(table_number) {
+        Result::Ok(_) => (Status::NoContent, (ContentType::JSON, "{}".to_string())),
+        Result::Err(e) => match e {
+            ServerError::DataNotFound => (Status::NotFound, (ContentType::JSON, "{ \"error\": \"Provided table does not exist.\" }".to_string())),
+            ServerError::TableHasOpenOrders => (Status::Conflict, (ContentType::JSON, "{ \"error\": \"Provided table still has open orders.\" }".to_string())),
+            _ => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to delete data.\" }".to_string()))
+        }
     }
 }
 
-#[get("/menu-items")]
-pub fn get_menu_items(database_connector: &State<Box<dyn DatabaseConnector>>) -> (Status, (ContentType, String)) {
-    match server_functions::get_menu_items(database_connector.inner().as_ref()) {
+#[get("/menu-items?<include_retired>")]
+pub fn get_menu_items(include_retired: Option<bool>, menu_repository: &State<Arc<dyn MenuRepository>>) -> (Status, (ContentType, String)) {
+    match menu_repository.get_menu_items(include_retired.unwrap_or(false)) {
         Result::Ok(items) => match to_string(&items) {
             Result::Ok(item_string) => (Status::Ok, (ContentType::JSON, item_string)),
             Result::Err(_) => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to get data.\" }".to_string()))
@@ -72,6 +441,69 @@ pub fn get_menu_items(database_connector: &State<Box<dyn DatabaseConnector>>) ->
     }
 }
 
+#[post("/menu-items", format = "json", data = "<menu_item_data>")]
+pub fn post_menu_item(menu_item_data: Json<rest_bodies::MenuItem>, menu_repository: &State<Arc<dyn MenuRepository>>, settings: &State<Settings>) -> (Status, (ContentType, String)) {
+    let menu_item = menu_item_data.into_inner();
+    let added = validate_cook_time(&settings.cook_time_range, menu_item.min_minutes_to_cook)
+        .and_then(|_| validate_cook_time(&settings.cook_time_range, menu_item.max_minutes_to_cook))
+        .and_then(|_| menu_repository.add_menu_item(menu_item));
+    match added {
+        Result::Ok(item) => match to_string(&item) {
+            Result::Ok(item_string) => (Status::Created, (ContentType::JSON, item_string)),
+            Result::Err(_) => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to add data.\" }".to_string()))
+        },
+        Result::Err(e) => match e {
+            ServerError::DuplicateMenuItem => (Status::Conflict, (ContentType::JSON, "{ \"error\": \"A menu item with this name already exists.\" }".to_string())),
+            ServerError::InvalidMenuItem(message) => (Status::UnprocessableEntity, (ContentType::JSON, error_json(&message))),
+            _ => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to add data.\" }".to_string()))
+        }
+    }
+}
+
+#[patch("/menu-items/<menu_item_id>", format = "json", data = "<update_data>")]
+pub fn patch_menu_item(menu_item_id: u32, update_data: Json<rest_bodies::MenuItemUpdate>, menu_repository: &State<Arc<dyn MenuRepository>>, settings: &State<Settings>) -> (Status, (ContentType, String)) {
+    let update = update_data.into_inner();
+    // only the cooking times being changed are checked, so that items from before the range was narrowed can still be edited
+    let updated = [update.min_minutes_to_cook, update.max_minutes_to_cook].into_iter()
+        .flatten()
+        .try_for_each(|minutes| validate_cook_time(&settings.cook_time_range, minutes))
+        .and_then(|_| menu_repository.update_menu_item(menu_item_id, update));
+    match updated {
+        Result::Ok(item) => match to_string(&item) {
+            Result::Ok(item_string) => (Status::Ok, (ContentType::JSON, item_string)),
+            Result::Err(_) => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to update data.\" }".to_string()))
+        },
+        Result::Err(e) => match e {
+            ServerError::DataNotFound => (Status::NotFound, (ContentType::JSON, "{ \"error\": \"Provided menu item does not exist.\" }".to_string())),
+            ServerError::DuplicateMenuItem => (Status::Conflict, (ContentType::JSON, "{ \"error\": \"A menu item with this name already exists.\" }".to_string())),
+            ServerError::InvalidMenuItem(message) => (Status::UnprocessableEntity, (ContentType::JSON, error_json(&message))),
+            _ => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to update data.\" }".to_string()))
+        }
+    }
+}
+
+#[delete("/menu-items/<menu_item_id>")]
+pub fn delete_menu_item(menu_item_id: u32, menu_repository: &State<Arc<dyn MenuRepository>>) -> (Status, (ContentType, String)) {
+    match menu_repository.retire_menu_item(menu_item_id) {
+        Result::Ok(_) => (Status::NoContent, (ContentType::JSON, "{}".to_string())),
+        Result::Err(e) => match e {
+            ServerError::DataNotFound => (Status::NotFound, (ContentType::JSON, "{ \"error\": \"Provided menu item does not exist.\" }".to_string())),
+            _ => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to delete data.\" }".to_string()))
+        }
+    }
+}
+
+#[get("/admin/idempotency-keys")]
+pub fn get_idempotency_keys(idempotency_repository: &State<Arc<dyn IdempotencyRepository>>) -> (Status, (ContentType, String)) {
+    match idempotency_repository.get_idempotency_keys() {
+        Result::Ok(keys) => match to_string(&keys) {
+            Result::Ok(keys_string) => (Status::Ok, (ContentType::JSON, keys_string)),
+            Result::Err(_) => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to get data.\" }".to_string()))
+        },
+        Result::Err(_) => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to get data.\" }".to_string()))
+    }
+}
+
 #[catch(400)]
 pub fn internal_error() -> &'static str {
     "{ \"error\": \"Request format could not be understood\" }"
@@ -89,5 +521,11 @@ pub fn unprocessable_entity(_: &Request) -> &'static str {
 
 #[catch(default)]
 pub fn default(status: Status, _: &Request) -> String {
-    format!("{{ \"error\": \"{status}\" }}")
-}
\ No newline at end of file
+    error_json(&status.to_string())
+}
+
+// Error messages are serialized rather than formatted into the response, so that quotes in them
+// (such as in a modifier or menu item name) cannot break the JSON
+fn error_json(message: &str) -> String {
+    format!("{{ \"error\": {} }}", to_string(&message).unwrap_or_else(|_| "\"\"".to_string()))
+}
@@ -1,10 +1,29 @@
 use std::fmt;
 use rusqlite;
 
+use crate::order_status::OrderStatus;
+
 pub enum ServerError {
     NoRowsReturned,
     DataNotFound,
+    MenuItemNotFound,
+    DuplicateMenuItem,
+    TableNotFound,
+    TableClosed,
+    DuplicateTable,
+    TableHasOpenOrders,
+    InvalidTable(String),
+    InvalidMenuItem(String),
+    InvalidOrder(String),
     Idempotency,
+    IdempotencyKeyReused,
+    InvalidStatusTransition(OrderStatus, OrderStatus),
+    // The order is served or cancelled, so it can no longer be edited
+    OrderFinal(OrderStatus),
+    InvalidSplit(String),
+    InvalidOrderQuery(String),
+    // A failure of storage other than a SQL database
+    StorageError(String),
     SqlError(rusqlite::Error)
 }
 
@@ -13,7 +32,22 @@ impl fmt::Debug for ServerError {
         let val = match self {
             ServerError::NoRowsReturned => "NoRowsReturned",
             ServerError::DataNotFound => "DataNotFound",
+            ServerError::MenuItemNotFound => "MenuItemNotFound",
+            ServerError::DuplicateMenuItem => "DuplicateMenuItem",
+            ServerError::TableNotFound => "TableNotFound",
+            ServerError::TableClosed => "TableClosed",
+            ServerError::DuplicateTable => "DuplicateTable",
+            ServerError::TableHasOpenOrders => "TableHasOpenOrders",
+            ServerError::InvalidTable(message) => &format!("InvalidTable({message})"),
+            ServerError::InvalidMenuItem(message) => &format!("InvalidMenuItem({message})"),
+            ServerError::InvalidOrder(message) => &format!("InvalidOrder({message})"),
             ServerError::Idempotency => "Idempotency",
+            ServerError::IdempotencyKeyReused => "IdempotencyKeyReused",
+            ServerError::InvalidStatusTransition(from, to) => &format!("InvalidStatusTransition({}, {})", from.as_str(), to.as_str()),
+            ServerError::OrderFinal(status) => &format!("OrderFinal({})", status.as_str()),
+            ServerError::InvalidSplit(message) => &format!("InvalidSplit({message})"),
+            ServerError::InvalidOrderQuery(message) => &format!("InvalidOrderQuery({message})"),
+            ServerError::StorageError(message) => &format!("StorageError({message})"),
             ServerError::SqlError(e) => &e.to_string()
         };
         write!(f, "{val}")
@@ -25,7 +59,22 @@ impl fmt::Display for ServerError {
         let val = match self {
             ServerError::NoRowsReturned => "NoRowsReturned",
             ServerError::DataNotFound => "DataNotFound",
+            ServerError::MenuItemNotFound => "MenuItemNotFound",
+            ServerError::DuplicateMenuItem => "DuplicateMenuItem",
+            ServerError::TableNotFound => "TableNotFound",
+            ServerError::TableClosed => "TableClosed",
+            ServerError::DuplicateTable => "DuplicateTable",
+            ServerError::TableHasOpenOrders => "TableHasOpenOrders",
+            ServerError::InvalidTable(message) => &format!("InvalidTable({message})"),
+            ServerError::InvalidMenuItem(message) => &format!("InvalidMenuItem({message})"),
+            ServerError::InvalidOrder(message) => &format!("InvalidOrder({message})"),
             ServerError::Idempotency => "Idempotency",
+            ServerError::IdempotencyKeyReused => "IdempotencyKeyReused",
+            ServerError::InvalidStatusTransition(from, to) => &format!("InvalidStatusTransition({}, {})", from.as_str(), to.as_str()),
+            ServerError::OrderFinal(status) => &format!("OrderFinal({})", status.as_str()),
+            ServerError::InvalidSplit(message) => &format!("InvalidSplit({message})"),
+            ServerError::InvalidOrderQuery(message) => &format!("InvalidOrderQuery({message})"),
+            ServerError::StorageError(message) => &format!("StorageError({message})"),
             ServerError::SqlError(e) => &e.to_string()
         };
         write!(f, "{val}")
@@ -0,0 +1,126 @@
+use std::sync::Arc;
+use rocket::http::{ ContentType, Status };
+use rocket::request::{ FromRequest, Outcome };
+use rocket::response::{ self, Responder };
+use rocket::Request;
+
+use crate::errors::server_error::ServerError;
+use crate::repositories::IdempotencyRepository;
+use crate::clock::Clock;
+
+// The name of the standard request header carrying an idempotency key
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+// The name of the response header which is set when a response is replayed
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "Idempotent-Replayed";
+
+// A request guard reading the Idempotency-Key header, along with the method and route that the key applies to.
+// The guard always succeeds when the header is missing, since keys are optional; an empty key is rejected with a 400.
+// It also holds the managed repository and clock which keys are stored with.
+pub struct IdempotencyKey<'r> {
+    pub key: Option<String>,
+    method: String,
+    route: String,
+    uri: String,
+    repository: &'r dyn IdempotencyRepository,
+    clock: &'r dyn Clock
+}
+
+impl IdempotencyKey<'_> {
+    // Chooses the key for a request which may also carry a key in its body.
+    // The header takes precedence, so the body's key is only used when no header was sent.
+    pub fn or_body_key(self, body_key: Option<String>) -> Self {
+        IdempotencyKey {
+            key: self.key.or(body_key),
+            ..self
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for IdempotencyKey<'r> {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let rocket = request.rocket();
+        let (repository, clock) = match (rocket.state::<Arc<dyn IdempotencyRepository>>(), rocket.state::<Arc<dyn Clock>>()) {
+            (Some(repository), Some(clock)) => (repository.as_ref(), clock.as_ref()),
+            _ => return Outcome::Error((Status::InternalServerError, ()))
+        };
+        let key = match request.headers().get_one(IDEMPOTENCY_KEY_HEADER) {
+            None => None,
+            Some(key) if key.trim().is_empty() => return Outcome::Error((Status::BadRequest, ())),
+            Some(key) => Some(key.to_string())
+        };
+
+        Outcome::Success(IdempotencyKey {
+            key,
+            method: request.method().as_str().to_string(),
+            // keys are scoped to the route rather than the URI, so that reusing a key with other path
+            // parameters is caught as a different request rather than treated as a new one
+            route: request.route().map(|r| r.uri.to_string()).unwrap_or_default(),
+            uri: request.uri().to_string(),
+            repository,
+            clock
+        })
+    }
+}
+
+// A response which tells the client, with the Idempotent-Replayed header, when it is a replay
+// of the response to an earlier request with the same idempotency key.
+pub struct IdempotentResponse {
+    response: (Status, (ContentType, String)),
+    replayed: bool
+}
+
+impl<'r> Responder<'r, 'static> for IdempotentResponse {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        let mut response = self.response.respond_to(request)?;
+        if self.replayed {
+            response.set_raw_header(IDEMPOTENT_REPLAYED_HEADER, "true");
+        }
+        Ok(response)
+    }
+}
+
+// Runs an endpoint's handler at most once per idempotency key. A request made with a key which has already been used
+// for the same request gets the stored status and response back, while reusing a key for a different request (a
+// different URI or body) is rejected with a 422. Requests without a key are always handled.
+// Server errors are not stored, so that a request which failed with one can be retried with the same key.
+pub fn idempotent<F>(idempotency_key: &IdempotencyKey, body: &str, handler: F) -> IdempotentResponse
+                    where F: FnOnce() -> (Status, (ContentType, String)) {
+    let key = match &idempotency_key.key {
+        Some(key) => key,
+        None => return IdempotentResponse { response: handler(), replayed: false }
+    };
+    let IdempotencyKey { method, route, uri, repository, clock, .. } = idempotency_key;
+    let request = format!("{uri}\n{body}");
+
+    match repository.begin_idempotent_request(*clock, method, route, key, &request) {
+        Result::Ok(Some((status, response))) => IdempotentResponse {
+            response: (Status::new(status), (ContentType::JSON, response)),
+            replayed: true
+        },
+        Result::Ok(None) => {
+            let response = handler();
+            let (status, (_, body)) = &response;
+            let stored = match status.class().is_server_error() {
+                true => repository.release_idempotent_request(method, route, key),
+                false => repository.complete_idempotent_request(method, route, key, status.code, body)
+            };
+            // the request has been handled either way, so its response is still returned
+            if let Err(e) = stored {
+                eprintln!("Failed to store the response for idempotency key {key}: {e}");
+            }
+            IdempotentResponse { response, replayed: false }
+        },
+        Result::Err(e) => IdempotentResponse {
+            response: match e {
+                ServerError::Idempotency => (Status::Conflict, (ContentType::JSON, "{ \"error\": \"A request with this idempotency key is already being processed.\" }".to_string())),
+                ServerError::IdempotencyKeyReused => (Status::UnprocessableEntity, (ContentType::JSON, "{ \"error\": \"The provided idempotency key has already been used for a different request.\" }".to_string())),
+                _ => (Status::InternalServerError, (ContentType::JSON, "{ \"error\": \"Server error. Failed to check the idempotency key.\" }".to_string()))
+            },
+            replayed: false
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use rocket::serde::{ Deserialize, Serialize };
+use rocket::serde::json::{ from_str, to_string };
+
+use crate::errors::server_error::ServerError;
+use crate::repositories::OrderRepository;
+use crate::clock::Clock;
+use crate::order_status::OrderStatus;
+use crate::order_query::TableFilter;
+use crate::order_events::ORDER_EVENT_BATCH_SIZE;
+use crate::rest_responses;
+
+// The JSON message protocol of the kitchen display's WebSocket channel. Every message is an object whose "type"
+// names the message, e.g. { "type": "ack", "table_number": 1, "order_id": 7, "status": "started" }.
+// Each request gets exactly one reply; event messages are sent in between as orders change.
+
+// Messages sent by the kitchen
+#[derive(Debug)]
+#[derive(Deserialize)]
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum KitchenRequest {
+    // Starts sending order events, replacing any earlier subscription. The tables use the same format as /orders,
+    // and without a last_event_id the events start with the next change.
+    Subscribe {
+        tables: Option<String>,
+        last_event_id: Option<u64>
+    },
+    // Tells the server that the kitchen has started or finished cooking an order
+    Ack {
+        table_number: u32,
+        order_id: u32,
+        status: KitchenAck
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Deserialize)]
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "lowercase")]
+pub enum KitchenAck {
+    Started,
+    Ready
+}
+
+impl KitchenAck {
+    // The status which an order is moved to when it is acknowledged
+    pub fn order_status(&self) -> OrderStatus {
+        match self {
+            KitchenAck::Started => OrderStatus::Cooking,
+            KitchenAck::Ready => OrderStatus::Ready
+        }
+    }
+}
+
+// Messages sent to the kitchen
+#[derive(Debug)]
+#[derive(Deserialize)]
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum KitchenMessage {
+    // The reply to a subscribe request, with the id of the event which the subscription starts after
+    Subscribed {
+        last_event_id: u64
+    },
+    // An order event, the same as those streamed from /events
+    Event {
+        id: u64,
+        event: String,
+        order: rest_responses::Order
+    },
+    // The reply to an ack request which was applied, with the updated order
+    Acked {
+        order: rest_responses::Order
+    },
+    // The reply to a request which could not be handled. The order id is set for acks.
+    Error {
+        error: String,
+        order_id: Option<u32>
+    }
+}
+
+impl KitchenMessage {
+    pub fn to_json(&self) -> String {
+        to_string(self).unwrap_or_default()
+    }
+}
+
+// The events a kitchen has subscribed to, and the last one it has been sent
+#[derive(Debug, Clone)]
+pub struct KitchenSubscription {
+    pub tables: TableFilter,
+    pub last_event_id: u64
+}
+
+// Handles a message from the kitchen, returning the reply. A subscribe request replaces the subscription.
+// Acks are applied with update_order_status, exactly like PUT /tables/{table-number}/orders/{order-id}/status.
+pub fn handle_request(order_repository: &dyn OrderRepository, clock: &dyn Clock, message: &str, subscription: &mut Option<KitchenSubscription>) -> KitchenMessage {
+    let request = match from_str::<KitchenRequest>(message) {
+        Result::Ok(request) => request,
+        Result::Err(_) => return KitchenMessage::Error {
+            error: "Message could not be understood.".to_string(),
+            order_id: None
+        }
+    };
+
+    match request {
+        KitchenRequest::Subscribe { tables, last_event_id } => {
+            let tables = match TableFilter::from_param(tables.as_deref()) {
+                Result::Ok(tables) => tables,
+                Result::Err(e) => return KitchenMessage::Error {
+                    error: match e {
+                        ServerError::InvalidOrderQuery(message) => message,
+                        _ => "Server error. Failed to subscribe.".to_string()
+                    },
+                    order_id: None
+                }
+            };
+            let last_event_id = match last_event_id {
+                Some(id) => id,
+                None => match order_repository.get_last_order_event_id() {
+                    Result::Ok(id) => id,
+                    Result::Err(_) => return KitchenMessage::Error {
+                        error: "Server error. Failed to subscribe.".to_string(),
+                        order_id: None
+                    }
+                }
+            };
+            *subscription = Some(KitchenSubscription { tables, last_event_id });
+            KitchenMessage::Subscribed { last_event_id }
+        },
+        KitchenRequest::Ack { table_number, order_id, status } => {
+            match order_repository.update_order_status(clock, table_number, order_id, status.order_status()) {
+                Result::Ok(order) => KitchenMessage::Acked { order },
+                Result::Err(e) => KitchenMessage::Error {
+                    error: match e {
+                        ServerError::DataNotFound => "Provided order does not exist for provided table.".to_string(),
+                        ServerError::InvalidStatusTransition(from, to) => format!("An order cannot go from {} to {}.", from.as_str(), to.as_str()),
+                        _ => "Server error. Failed to update data.".to_string()
+                    },
+                    order_id: Some(order_id)
+                }
+            }
+        }
+    }
+}
+
+// Reads the next events for a subscription from the event log, moving the subscription past them.
+// Also returns whether the subscription has caught up, i.e. whether there are no more events to read yet.
+pub fn next_events(order_repository: &dyn OrderRepository, subscription: &mut KitchenSubscription) -> Result<(Vec<KitchenMessage>, bool), ServerError> {
+    let events = order_repository.get_order_events(&subscription.tables, subscription.last_event_id, ORDER_EVENT_BATCH_SIZE)?;
+    let caught_up = events.len() < ORDER_EVENT_BATCH_SIZE as usize;
+
+    let mut messages = Vec::new();
+    for event in events {
+        subscription.last_event_id = event.id;
+        // events which can no longer be read as an order are skipped rather than ending the channel
+        if let Result::Ok(order) = from_str::<rest_responses::Order>(&event.data) {
+            messages.push(KitchenMessage::Event {
+                id: event.id,
+                event: event.kind.as_str().to_string(),
+                order
+            });
+        }
+    }
+    Result::Ok((messages, caught_up))
+}
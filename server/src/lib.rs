@@ -1,6 +1,20 @@
 pub mod rest_bodies;
 pub mod rest_responses;
 pub mod server_functions;
-pub mod database_connector;
+pub mod database_pool;
+pub mod clock;
+pub mod random_number_generator;
 pub mod errors;
+pub mod settings;
+pub mod idempotency;
+pub mod purge;
+pub mod order_status;
+pub mod bill_split;
+pub mod order_query;
+pub mod order_events;
+pub mod rules;
+pub mod seed;
+pub mod migrations;
+pub mod repositories;
+pub mod kitchen;
 pub mod endpoints;
\ No newline at end of file
@@ -1,28 +1,139 @@
 #[macro_use] extern crate rocket;
 
-use rocket::{ routes, launch };
+use std::process::ExitCode;
+use std::sync::Arc;
+use rocket::{ routes, Build, Rocket };
+use rocket::figment::Figment;
 
-use server::server_functions;
-use server::database_connector::{ DatabaseConnector, DefaultDatabaseConnector };
+use server::database_pool::DatabasePool;
+use server::clock::{ Clock, DefaultClock };
+use server::random_number_generator::{ RandomNumberGenerator, DefaultRandomNumberGenerator };
+use server::settings::{ Database, Settings, Storage };
+use server::seed::Seed;
+use server::repositories::{ self, DatabaseRepository, Repository };
+use server::repositories::sqlite::SqliteRepository;
+use server::repositories::in_memory::InMemoryRepository;
+#[cfg(feature = "postgres")]
+use server::repositories::postgres::PostgresRepository;
+use server::purge;
+use server::order_events;
 use server::endpoints::*;
 
-const DATABASE_PATH: &str = "database.db";
+// Problems with the settings or the database are reported before exiting, rather than panicking,
+// so that whoever is starting the server can see everything they need to fix
+#[rocket::main]
+async fn main() -> ExitCode {
+    let rocket = match build(rocket::Config::figment()) {
+        Ok(Some(rocket)) => rocket,
+        Ok(None) => return ExitCode::SUCCESS,
+        Err(problems) => {
+            eprintln!("The server could not be started:");
+            for problem in problems {
+                eprintln!("  - {problem}");
+            }
+            return ExitCode::FAILURE;
+        }
+    };
+    match rocket.launch().await {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("The server failed to launch: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// Builds the server from its settings, or returns None for a migration dry run, which only reports the migrations
+fn build(figment: Figment) -> Result<Option<Rocket<Build>>, Vec<String>> {
+    let settings = figment.extract::<Settings>()
+        .map_err(|err| err.into_iter().map(|e| e.to_string()).collect::<Vec<_>>())?;
+    settings.validate()?;
+    let seed = settings.seed().map_err(|e| vec![e])?;
+
+    let rocket = rocket::custom(figment)
+        .manage(Arc::new(DefaultClock {}) as Arc<dyn Clock>)
+        .manage(Box::new(DefaultRandomNumberGenerator {}) as Box<dyn RandomNumberGenerator>)
+        .manage(settings.clone());
+    let rocket = match (settings.storage, settings.database()) {
+        (Storage::Database, Database::Sqlite(path)) => {
+            let pool = DatabasePool::new(path, settings.database_pool_size)
+                .map_err(|e| vec![format!("Failed to create the database pool: {e}")])?;
+            with_database(rocket, &settings, &seed, SqliteRepository::new(pool))?
+        },
+        #[cfg(feature = "postgres")]
+        (Storage::Database, Database::Postgres(url)) => {
+            let repository = PostgresRepository::from_url(&url, settings.database_pool_size)
+                .map_err(|e| vec![format!("Invalid database_url: {e}")])?;
+            with_database(rocket, &settings, &seed, repository)?
+        },
+        #[cfg(not(feature = "postgres"))]
+        (Storage::Database, Database::Postgres(_)) => {
+            return Err(vec!["A postgres:// database_url requires the server to be built with the postgres feature".to_string()]);
+        },
+        (Storage::Memory, _) => Some(with_repository(rocket, InMemoryRepository::with_seed(&seed)))
+    };
+    let Some(rocket) = rocket else {
+        return Result::Ok(None);
+    };
 
-#[launch]
-fn rocket() -> _ {
-    let database_connector = DefaultDatabaseConnector {
-        path: DATABASE_PATH.to_string()
+    let rocket = match settings.event_stream_enabled {
+        true => rocket.mount("/", routes![get_events]),
+        false => rocket
     };
-    match server_functions::setup_database(&database_connector) {
-        Ok(_) => {},
-        Err(err) => panic!("Failed to setup database: {}", err),
+    let rocket = match settings.kitchen_channel_enabled {
+        true => rocket.mount("/", routes![kitchen_channel]),
+        false => rocket
     };
-    rocket::build()
-        .manage(Box::new(database_connector) as Box<dyn DatabaseConnector>)
+    Result::Ok(Some(rocket
+        .attach(order_events::fairing())
         .mount("/", routes![get_table_orders])
+        .mount("/", routes![get_orders])
         .mount("/", routes![post_table_order])
         .mount("/", routes![get_table_order])
         .mount("/", routes![delete_table_order])
+        .mount("/", routes![delete_table_orders])
+        .mount("/", routes![patch_table_order])
+        .mount("/", routes![put_table_order_status])
+        .mount("/", routes![get_table_bill])
+        .mount("/", routes![get_table_split_bill])
+        .mount("/", routes![post_table_checkout])
+        .mount("/", routes![post_table_transfer])
+        .mount("/", routes![get_tables])
+        .mount("/", routes![post_table])
+        .mount("/", routes![get_table])
+        .mount("/", routes![patch_table])
+        .mount("/", routes![delete_table])
         .mount("/", routes![get_menu_items])
-        .register("/", catchers![internal_error, not_found, default, unprocessable_entity])
-}
\ No newline at end of file
+        .mount("/", routes![post_menu_item])
+        .mount("/", routes![patch_menu_item])
+        .mount("/", routes![delete_menu_item])
+        .mount("/", routes![get_idempotency_keys])
+        .register("/", catchers![internal_error, not_found, default, unprocessable_entity])))
+}
+
+// Migrates and seeds the database before managing it as the repositories.
+// For a dry run, the pending migrations are listed instead, and there is no server to start.
+fn with_database<R: DatabaseRepository + Clone + 'static>(rocket: Rocket<Build>, settings: &Settings, seed: &Seed, repository: R) -> Result<Option<Rocket<Build>>, Vec<String>> {
+    if settings.migrate_dry_run {
+        let migrations = repository.migrate(true)
+            .map_err(|e| vec![format!("Failed to migrate the database: {e}")])?;
+        if migrations.is_empty() {
+            println!("The database is up to date");
+        }
+        for migration in migrations {
+            println!("Migration {} would be applied: {}", migration.version, migration.description);
+        }
+        return Result::Ok(None);
+    }
+
+    repository.setup(seed)
+        .map_err(|e| vec![format!("Failed to set up the database: {e}")])?;
+    Result::Ok(Some(with_repository(rocket, repository)))
+}
+
+// Manages the repositories, and purges their expired data in the background
+fn with_repository<R: Repository + Clone + 'static>(rocket: Rocket<Build>, repository: R) -> Rocket<Build> {
+    repositories::manage(rocket, repository.clone())
+        .attach(purge::idempotency_keys(Box::new(repository.clone()), Box::new(DefaultClock {})))
+        .attach(purge::order_events(Box::new(repository), Box::new(DefaultClock {})))
+}
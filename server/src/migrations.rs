@@ -0,0 +1,278 @@
+use crate::errors::server_error::ServerError;
+
+// A numbered change to the database's schema. Migrations are applied in order, and each is only applied once;
+// the versions which have been applied are recorded in the schema_version table.
+// A migration must never be changed once it has been released, since databases which have already applied it
+// will not apply it again. Add a new migration instead.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    pub sql: &'static str
+}
+
+// Migrations of SQLite databases, oldest first.
+// The first migration creates the tables exactly as they were before migrations were introduced. It only creates
+// tables which do not exist yet, so that databases created back then are brought under version control, and are
+// then upgraded by the later migrations like any other database.
+pub const SQLITE_MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "Create the tables",
+        sql: "
+            CREATE TABLE IF NOT EXISTS menu_items (
+                id INTEGER PRIMARY KEY,
+                name TEXT UNIQUE);
+            CREATE TABLE IF NOT EXISTS orders (
+                id INTEGER PRIMARY KEY,
+                menu_item_id INTEGER,
+                table_number INTEGER,
+                minutes_to_cook INTEGER,
+                FOREIGN KEY(menu_item_id) REFERENCES menu_items(id));
+            CREATE TABLE IF NOT EXISTS idempotent_requests (
+                idempotency_key TEXT PRIMARY KEY);"
+    },
+    Migration {
+        version: 2,
+        description: "Add cook time ranges, prices, retirement and modifiers to menu items",
+        sql: "
+            ALTER TABLE menu_items ADD COLUMN min_minutes_to_cook INTEGER NOT NULL DEFAULT 5;
+            ALTER TABLE menu_items ADD COLUMN max_minutes_to_cook INTEGER NOT NULL DEFAULT 15;
+            ALTER TABLE menu_items ADD COLUMN price INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE menu_items ADD COLUMN retired INTEGER NOT NULL DEFAULT 0;
+            CREATE TABLE menu_item_modifiers (
+                menu_item_id INTEGER,
+                name TEXT,
+                price_delta INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY(menu_item_id, name),
+                FOREIGN KEY(menu_item_id) REFERENCES menu_items(id));"
+    },
+    Migration {
+        version: 3,
+        description: "Add prices, statuses, status times, check-out times, seats, modifiers and notes to orders",
+        sql: "
+            ALTER TABLE orders ADD COLUMN price INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE orders ADD COLUMN created_at INTEGER;
+            ALTER TABLE orders ADD COLUMN status TEXT NOT NULL DEFAULT 'placed';
+            ALTER TABLE orders ADD COLUMN placed_at INTEGER;
+            ALTER TABLE orders ADD COLUMN cooking_at INTEGER;
+            ALTER TABLE orders ADD COLUMN ready_at INTEGER;
+            ALTER TABLE orders ADD COLUMN served_at INTEGER;
+            ALTER TABLE orders ADD COLUMN cancelled_at INTEGER;
+            ALTER TABLE orders ADD COLUMN checked_out_at INTEGER;
+            ALTER TABLE orders ADD COLUMN seat INTEGER;
+            ALTER TABLE orders ADD COLUMN modifiers TEXT NOT NULL DEFAULT '[]';
+            ALTER TABLE orders ADD COLUMN note TEXT;
+            -- when existing orders were placed was never recorded, so they count as placed now
+            UPDATE orders SET created_at = CAST(strftime('%s', 'now') AS INTEGER), placed_at = CAST(strftime('%s', 'now') AS INTEGER);"
+    },
+    Migration {
+        version: 4,
+        description: "Record tables and order events",
+        sql: "
+            CREATE TABLE tables (
+                number INTEGER PRIMARY KEY,
+                capacity INTEGER NOT NULL,
+                section TEXT,
+                open INTEGER NOT NULL DEFAULT 1);
+            -- ids are never reused, even after old events are purged, since clients resume streams from them
+            CREATE TABLE order_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                kind TEXT NOT NULL,
+                order_id INTEGER NOT NULL,
+                table_number INTEGER NOT NULL,
+                previous_table_number INTEGER,
+                created_at INTEGER NOT NULL,
+                data TEXT NOT NULL);"
+    },
+    Migration {
+        version: 5,
+        description: "Key idempotent requests by method and route, and record their requests and responses",
+        sql: "
+            CREATE TABLE new_idempotent_requests (
+                method TEXT NOT NULL,
+                route TEXT NOT NULL,
+                idempotency_key TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                -- null for keys recorded before requests were stored, which match any request
+                request TEXT,
+                status INTEGER,
+                response TEXT,
+                PRIMARY KEY(method, route, idempotency_key));
+            -- existing keys were all for adding orders, and repeating one got a 409, which is what a retry still gets
+            INSERT INTO new_idempotent_requests (method, route, idempotency_key, created_at, request, status, response)
+                SELECT 'POST', '/tables/<table_id>/orders', idempotency_key, CAST(strftime('%s', 'now') AS INTEGER),
+                    NULL, 409, '{ \"error\": \"This order has already been added.\" }'
+                FROM idempotent_requests;
+            DROP TABLE idempotent_requests;
+            ALTER TABLE new_idempotent_requests RENAME TO idempotent_requests;"
+    },
+    Migration {
+        version: 6,
+        description: "Index orders by table, and order events and idempotency keys by age",
+        sql: "
+            CREATE INDEX orders_by_table ON orders(table_number, checked_out_at);
+            CREATE INDEX order_events_by_created_at ON order_events(created_at);
+            CREATE INDEX idempotent_requests_by_created_at ON idempotent_requests(created_at);"
+    },
+    Migration {
+        version: 7,
+        description: "Never reuse the ids of deleted orders",
+        sql: "
+            ALTER TABLE orders RENAME TO old_orders;
+            CREATE TABLE orders (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                menu_item_id INTEGER,
+                table_number INTEGER,
+                minutes_to_cook INTEGER,
+                price INTEGER NOT NULL DEFAULT 0,
+                created_at INTEGER,
+                status TEXT NOT NULL DEFAULT 'placed',
+                placed_at INTEGER,
+                cooking_at INTEGER,
+                ready_at INTEGER,
+                served_at INTEGER,
+                cancelled_at INTEGER,
+                checked_out_at INTEGER,
+                seat INTEGER,
+                modifiers TEXT NOT NULL DEFAULT '[]',
+                note TEXT,
+                FOREIGN KEY(menu_item_id) REFERENCES menu_items(id));
+            INSERT INTO orders (id, menu_item_id, table_number, minutes_to_cook, price, created_at, status,
+                    placed_at, cooking_at, ready_at, served_at, cancelled_at, checked_out_at, seat, modifiers, note)
+                SELECT id, menu_item_id, table_number, minutes_to_cook, price, created_at, status,
+                    placed_at, cooking_at, ready_at, served_at, cancelled_at, checked_out_at, seat, modifiers, note
+                FROM old_orders;
+            DROP TABLE old_orders;
+            CREATE INDEX orders_by_table ON orders(table_number, checked_out_at);
+            -- orders which were deleted before now are only known from the events which have not been purged yet
+            DELETE FROM sqlite_sequence WHERE name = 'orders';
+            INSERT INTO sqlite_sequence (name, seq)
+                SELECT 'orders', COALESCE(MAX(id), 0) FROM (SELECT id FROM orders UNION ALL SELECT order_id FROM order_events);"
+    }
+];
+
+// Migrations of Postgres databases, oldest first. These make the same changes as the SQLite migrations,
+// with the same version numbers.
+#[cfg(feature = "postgres")]
+pub const POSTGRES_MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "Create the tables",
+        sql: "
+            CREATE TABLE IF NOT EXISTS menu_items (
+                id BIGSERIAL PRIMARY KEY,
+                name TEXT NOT NULL UNIQUE);
+            CREATE TABLE IF NOT EXISTS orders (
+                id BIGSERIAL PRIMARY KEY,
+                menu_item_id BIGINT NOT NULL REFERENCES menu_items(id),
+                table_number BIGINT NOT NULL,
+                minutes_to_cook BIGINT NOT NULL);
+            CREATE TABLE IF NOT EXISTS idempotent_requests (
+                idempotency_key TEXT PRIMARY KEY);"
+    },
+    Migration {
+        version: 2,
+        description: "Add cook time ranges, prices, retirement and modifiers to menu items",
+        sql: "
+            ALTER TABLE menu_items ADD COLUMN min_minutes_to_cook BIGINT NOT NULL DEFAULT 5;
+            ALTER TABLE menu_items ADD COLUMN max_minutes_to_cook BIGINT NOT NULL DEFAULT 15;
+            ALTER TABLE menu_items ADD COLUMN price BIGINT NOT NULL DEFAULT 0;
+            ALTER TABLE menu_items ADD COLUMN retired BOOLEAN NOT NULL DEFAULT FALSE;
+            CREATE TABLE menu_item_modifiers (
+                id BIGSERIAL PRIMARY KEY,
+                menu_item_id BIGINT NOT NULL REFERENCES menu_items(id),
+                name TEXT NOT NULL,
+                price_delta BIGINT NOT NULL DEFAULT 0,
+                UNIQUE(menu_item_id, name));"
+    },
+    Migration {
+        version: 3,
+        description: "Add prices, statuses, status times, check-out times, seats, modifiers and notes to orders",
+        sql: "
+            ALTER TABLE orders ADD COLUMN price BIGINT NOT NULL DEFAULT 0;
+            ALTER TABLE orders ADD COLUMN created_at BIGINT;
+            ALTER TABLE orders ADD COLUMN status TEXT NOT NULL DEFAULT 'placed';
+            ALTER TABLE orders ADD COLUMN placed_at BIGINT;
+            ALTER TABLE orders ADD COLUMN cooking_at BIGINT;
+            ALTER TABLE orders ADD COLUMN ready_at BIGINT;
+            ALTER TABLE orders ADD COLUMN served_at BIGINT;
+            ALTER TABLE orders ADD COLUMN cancelled_at BIGINT;
+            ALTER TABLE orders ADD COLUMN checked_out_at BIGINT;
+            ALTER TABLE orders ADD COLUMN seat BIGINT;
+            ALTER TABLE orders ADD COLUMN modifiers TEXT NOT NULL DEFAULT '[]';
+            ALTER TABLE orders ADD COLUMN note TEXT;
+            -- when existing orders were placed was never recorded, so they count as placed now
+            UPDATE orders SET created_at = EXTRACT(EPOCH FROM NOW())::BIGINT, placed_at = EXTRACT(EPOCH FROM NOW())::BIGINT;
+            ALTER TABLE orders ALTER COLUMN created_at SET NOT NULL;"
+    },
+    Migration {
+        version: 4,
+        description: "Record tables and order events",
+        sql: "
+            CREATE TABLE tables (
+                number BIGINT PRIMARY KEY,
+                capacity BIGINT NOT NULL,
+                section TEXT,
+                open BOOLEAN NOT NULL DEFAULT TRUE);
+            CREATE TABLE order_events (
+                id BIGSERIAL PRIMARY KEY,
+                kind TEXT NOT NULL,
+                order_id BIGINT NOT NULL,
+                table_number BIGINT NOT NULL,
+                previous_table_number BIGINT,
+                created_at BIGINT NOT NULL,
+                data TEXT NOT NULL);"
+    },
+    Migration {
+        version: 5,
+        description: "Key idempotent requests by method and route, and record their requests and responses",
+        sql: "
+            CREATE TABLE new_idempotent_requests (
+                method TEXT NOT NULL,
+                route TEXT NOT NULL,
+                idempotency_key TEXT NOT NULL,
+                created_at BIGINT NOT NULL,
+                request TEXT,
+                status INTEGER,
+                response TEXT,
+                PRIMARY KEY(method, route, idempotency_key));
+            INSERT INTO new_idempotent_requests (method, route, idempotency_key, created_at, request, status, response)
+                SELECT 'POST', '/tables/<table_id>/orders', idempotency_key, EXTRACT(EPOCH FROM NOW())::BIGINT,
+                    NULL, 409, '{ \"error\": \"This order has already been added.\" }'
+                FROM idempotent_requests;
+            DROP TABLE idempotent_requests;
+            ALTER TABLE new_idempotent_requests RENAME TO idempotent_requests;
+            ALTER INDEX new_idempotent_requests_pkey RENAME TO idempotent_requests_pkey;"
+    },
+    Migration {
+        version: 6,
+        description: "Index orders by table, and order events and idempotency keys by age",
+        sql: "
+            CREATE INDEX orders_by_table ON orders(table_number, checked_out_at);
+            CREATE INDEX order_events_by_created_at ON order_events(created_at);
+            CREATE INDEX idempotent_requests_by_created_at ON idempotent_requests(created_at);"
+    },
+    Migration {
+        version: 7,
+        description: "Never reuse the ids of deleted orders",
+        // BIGSERIAL ids are never reused already
+        sql: ""
+    }
+];
+
+// The version a database is at once every migration has been applied
+pub fn latest_version(migrations: &[Migration]) -> u32 {
+    migrations.last().map(|m| m.version).unwrap_or(0)
+}
+
+// The migrations which a database at the given version has not applied yet, in the order they should be applied.
+// A database at a newer version than any migration was migrated by a newer server, which this one cannot safely use.
+pub fn pending(migrations: &'static [Migration], version: u32) -> Result<Vec<&'static Migration>, ServerError> {
+    let latest = latest_version(migrations);
+    if version > latest {
+        return Err(ServerError::StorageError(format!(
+            "The database is at schema version {version}, but this server only knows of versions up to {latest}")));
+    }
+    Result::Ok(migrations.iter().filter(|m| m.version > version).collect())
+}
@@ -0,0 +1,110 @@
+use std::str::FromStr;
+use std::time::Duration;
+use rocket::fairing::AdHoc;
+use rocket::http::{ Method, Status };
+use rocket::request::{ FromRequest, Outcome };
+use rocket::tokio::sync::Notify;
+use rocket::Request;
+
+// The name of the request header which an EventSource sends when it reconnects
+pub const LAST_EVENT_ID_HEADER: &str = "Last-Event-ID";
+
+// The most events read from the log at once; a client catching up gets the rest straight after
+pub const ORDER_EVENT_BATCH_SIZE: u32 = 100;
+
+// Streams are woken up after every change made through this server, but also check the log this often,
+// in case it was changed some other way (such as by another server sharing the database)
+pub const ORDER_EVENT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderEventKind {
+    Created,
+    Updated,
+    Deleted
+}
+
+impl OrderEventKind {
+    // The name of the event when it is streamed
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderEventKind::Created => "order_created",
+            OrderEventKind::Updated => "order_updated",
+            OrderEventKind::Deleted => "order_deleted"
+        }
+    }
+}
+
+impl FromStr for OrderEventKind {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "order_created" => Ok(OrderEventKind::Created),
+            "order_updated" => Ok(OrderEventKind::Updated),
+            "order_deleted" => Ok(OrderEventKind::Deleted),
+            x => Err(format!("Unknown order event: {x}"))
+        }
+    }
+}
+
+// A change to an order, as recorded in the event log. The data is the order as JSON, as of the change
+// (for a deleted order, as it was just before it was deleted).
+#[derive(Debug, Clone)]
+pub struct OrderEvent {
+    pub id: u64,
+    pub kind: OrderEventKind,
+    pub data: String
+}
+
+// Wakes up event streams when orders may have changed, so that they check the event log straight away
+// rather than waiting to poll it.
+#[derive(Default)]
+pub struct OrderEventNotifier {
+    notify: Notify
+}
+
+impl OrderEventNotifier {
+    pub fn notify(&self) {
+        self.notify.notify_waiters();
+    }
+
+    // Waits for the next notification. Notifications sent before this is called are missed, so the future
+    // should be created (and enabled) before the event log is checked.
+    pub fn notified(&self) -> rocket::tokio::sync::futures::Notified<'_> {
+        self.notify.notified()
+    }
+}
+
+// Manages the notifier, and notifies it after every request which could have changed orders
+pub fn fairing() -> AdHoc {
+    AdHoc::on_ignite("Order events", |rocket| async {
+        rocket
+            .manage(OrderEventNotifier::default())
+            .attach(AdHoc::on_response("Order event notifications", |request, _| Box::pin(async move {
+                if request.method() != Method::Get {
+                    if let Some(notifier) = request.rocket().state::<OrderEventNotifier>() {
+                        notifier.notify();
+                    }
+                }
+            })))
+    })
+}
+
+// A request guard reading the Last-Event-ID header, which is the id of the last event the client received.
+// The guard always succeeds when the header is missing; an id which is not a number is rejected with a 400.
+pub struct LastEventId(pub Option<u64>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for LastEventId {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match request.headers().get_one(LAST_EVENT_ID_HEADER) {
+            None => Outcome::Success(LastEventId(None)),
+            Some(id) => match id.trim().parse::<u64>() {
+                Result::Ok(id) => Outcome::Success(LastEventId(Some(id))),
+                Result::Err(_) => Outcome::Error((Status::BadRequest, ()))
+            }
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+use rocket::FromForm;
+
+use crate::errors::server_error::ServerError;
+use crate::order_status::OrderStatus;
+use crate::rest_responses;
+
+// The most orders which can be requested in a single page
+pub const MAX_PAGE_SIZE: u32 = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderSort {
+    Id,
+    MinutesToCook
+}
+
+// The position after the last order of a page. Pages are ordered by the sort column with the order id
+// breaking ties, so the cursor holds both; when sorting by id, the sort value is the id itself.
+// Cursors are sent to clients as "<id>" or "<minutes_to_cook>:<id>".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrderCursor {
+    pub sort_value: u32,
+    pub id: u32
+}
+
+// The query parameters of the order listing endpoints, e.g. ?status=placed&sort=minutes_to_cook&limit=20.
+// Numbers are taken as strings and parsed by OrderQuery, since Rocket would otherwise silently ignore a value which fails to parse.
+#[derive(Debug)]
+#[derive(FromForm)]
+pub struct OrderQueryParams {
+    // Comma separated lists, e.g. ?menu_item_id=1,2&status=placed,cooking
+    pub menu_item_id: Option<String>,
+    pub status: Option<String>,
+    pub created_after: Option<String>,
+    pub created_before: Option<String>,
+    pub sort: Option<String>,
+    pub limit: Option<String>,
+    pub cursor: Option<String>
+}
+
+// Filters, sorting and pagination for listing orders
+#[derive(Debug, Clone)]
+pub struct OrderQuery {
+    // Empty lists do not filter anything
+    pub menu_item_ids: Vec<u32>,
+    pub statuses: Vec<OrderStatus>,
+    // Only orders created strictly after created_after, and strictly before created_before, are included
+    pub created_after: Option<u64>,
+    pub created_before: Option<u64>,
+    pub sort: OrderSort,
+    // Without a limit, all matching orders are returned in one page
+    pub limit: Option<u32>,
+    pub cursor: Option<OrderCursor>
+}
+
+impl Default for OrderQuery {
+    fn default() -> Self {
+        OrderQuery {
+            menu_item_ids: Vec::new(),
+            statuses: Vec::new(),
+            created_after: None,
+            created_before: None,
+            sort: OrderSort::Id,
+            limit: None,
+            cursor: None
+        }
+    }
+}
+
+impl OrderQuery {
+    // Builds a query from the query parameters of the order listing endpoints
+    pub fn from_params(params: &OrderQueryParams) -> Result<OrderQuery, ServerError> {
+        let menu_item_ids = split_list(params.menu_item_id.as_deref())
+            .map(|id| parse_number("menu_item_id", id))
+            .collect::<Result<Vec<u32>, ServerError>>()?;
+        let statuses = split_list(params.status.as_deref())
+            .map(OrderStatus::from_str)
+            .collect::<Result<Vec<OrderStatus>, String>>()
+            .map_err(ServerError::InvalidOrderQuery)?;
+        let created_after = params.created_after.as_deref().map(|x| parse_number("created_after", x)).transpose()?;
+        let created_before = params.created_before.as_deref().map(|x| parse_number("created_before", x)).transpose()?;
+        let limit = params.limit.as_deref().map(|x| parse_number("limit", x)).transpose()?;
+
+        let sort = match params.sort.as_deref() {
+            None | Some("id") => OrderSort::Id,
+            Some("minutes_to_cook") => OrderSort::MinutesToCook,
+            Some(_) => return Err(ServerError::InvalidOrderQuery("sort must be one of id or minutes_to_cook.".to_string()))
+        };
+
+        if let Some(limit) = limit {
+            if limit == 0 || limit > MAX_PAGE_SIZE {
+                return Err(ServerError::InvalidOrderQuery(format!("limit must be between 1 and {MAX_PAGE_SIZE}.")));
+            }
+        }
+
+        let cursor = params.cursor.as_deref()
+            .map(|cursor| OrderCursor::parse(cursor, sort))
+            .transpose()?;
+
+        Result::Ok(
+            OrderQuery {
+                menu_item_ids,
+                statuses,
+                created_after,
+                created_before,
+                sort,
+                limit,
+                cursor
+            }
+        )
+    }
+}
+
+impl OrderQuery {
+    // The position of an order in the query's sort order
+    pub fn sort_key(&self, order: &rest_responses::Order) -> (u32, u32) {
+        match self.sort {
+            OrderSort::Id => (order.id, order.id),
+            OrderSort::MinutesToCook => (order.minutes_to_cook, order.id)
+        }
+    }
+
+    // Whether an order passes the query's filters and comes after its cursor, for repositories which filter orders themselves
+    pub fn matches(&self, order: &rest_responses::Order) -> bool {
+        (self.menu_item_ids.is_empty() || self.menu_item_ids.contains(&order.menu_item_id))
+            && (self.statuses.is_empty() || self.statuses.contains(&order.status))
+            && self.created_after.is_none_or(|created_after| order.created_at > created_after)
+            && self.created_before.is_none_or(|created_before| order.created_at < created_before)
+            && self.cursor.is_none_or(|cursor| self.sort_key(order) > (cursor.sort_value, cursor.id))
+    }
+
+    // Cuts matching orders, in sort order, down to the query's page. When there are more orders than the limit,
+    // the last order of the page is where the next page starts, so only one more than the limit needs to be given.
+    pub fn page(&self, mut items: Vec<rest_responses::Order>) -> rest_responses::Orders {
+        let next_cursor = match self.limit {
+            Some(limit) if items.len() > limit as usize => {
+                items.truncate(limit as usize);
+                items.last().map(|last| {
+                    let (sort_value, id) = self.sort_key(last);
+                    OrderCursor { sort_value, id }.encode(self.sort)
+                })
+            },
+            _ => None
+        };
+
+        rest_responses::Orders {
+            orders: items,
+            next_cursor
+        }
+    }
+}
+
+impl OrderCursor {
+    fn parse(cursor: &str, sort: OrderSort) -> Result<OrderCursor, ServerError> {
+        let invalid = || ServerError::InvalidOrderQuery("cursor is not valid for this sort.".to_string());
+        match sort {
+            OrderSort::Id => cursor.parse::<u32>()
+                .map(|id| OrderCursor { sort_value: id, id })
+                .map_err(|_| invalid()),
+            OrderSort::MinutesToCook => match cursor.split_once(':') {
+                Some((minutes, id)) => match (minutes.parse::<u32>(), id.parse::<u32>()) {
+                    (Result::Ok(sort_value), Result::Ok(id)) => Result::Ok(OrderCursor { sort_value, id }),
+                    _ => Err(invalid())
+                },
+                None => Err(invalid())
+            }
+        }
+    }
+
+    pub fn encode(&self, sort: OrderSort) -> String {
+        match sort {
+            OrderSort::Id => self.id.to_string(),
+            OrderSort::MinutesToCook => format!("{}:{}", self.sort_value, self.id)
+        }
+    }
+}
+
+// Tables to include when listing orders across tables, given as a comma separated list of
+// table numbers and inclusive ranges, e.g. ?tables=1,2,10-20. An empty filter includes every table.
+#[derive(Debug, Clone, Default)]
+pub struct TableFilter {
+    pub ranges: Vec<RangeInclusive<u32>>
+}
+
+impl TableFilter {
+    pub fn contains(&self, table_number: u32) -> bool {
+        self.ranges.is_empty() || self.ranges.iter().any(|range| range.contains(&table_number))
+    }
+
+    pub fn from_param(tables: Option<&str>) -> Result<TableFilter, ServerError> {
+        let ranges = split_list(tables)
+            .map(|table| match table.split_once('-') {
+                Some((from, to)) => {
+                    let from = parse_number("tables", from)?;
+                    let to = parse_number("tables", to)?;
+                    match from <= to {
+                        true => Result::Ok(from..=to),
+                        false => Err(ServerError::InvalidOrderQuery(format!("The table range {table} is backwards.")))
+                    }
+                },
+                None => parse_number("tables", table).map(|number| number..=number)
+            })
+            .collect::<Result<Vec<RangeInclusive<u32>>, ServerError>>()?;
+
+        Result::Ok(
+            TableFilter {
+                ranges
+            }
+        )
+    }
+}
+
+fn split_list(value: Option<&str>) -> impl Iterator<Item = &str> {
+    value
+        .into_iter()
+        .flat_map(|value| value.split(','))
+        .map(|item| item.trim())
+}
+
+fn parse_number<T: FromStr>(name: &str, value: &str) -> Result<T, ServerError> {
+    value
+        .parse::<T>()
+        .map_err(|_| ServerError::InvalidOrderQuery(format!("{name} must be a non-negative whole number.")))
+}
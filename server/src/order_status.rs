@@ -0,0 +1,69 @@
+use std::str::FromStr;
+use rocket::serde::{ Deserialize, Serialize };
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Deserialize)]
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+#[serde(rename_all = "lowercase")]
+pub enum OrderStatus {
+    Placed,
+    Cooking,
+    Ready,
+    Served,
+    Cancelled
+}
+
+impl OrderStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderStatus::Placed => "placed",
+            OrderStatus::Cooking => "cooking",
+            OrderStatus::Ready => "ready",
+            OrderStatus::Served => "served",
+            OrderStatus::Cancelled => "cancelled"
+        }
+    }
+
+    // The column in the orders table which records when an order entered this status
+    pub fn timestamp_column(&self) -> &'static str {
+        match self {
+            OrderStatus::Placed => "placed_at",
+            OrderStatus::Cooking => "cooking_at",
+            OrderStatus::Ready => "ready_at",
+            OrderStatus::Served => "served_at",
+            OrderStatus::Cancelled => "cancelled_at"
+        }
+    }
+
+    // Served and cancelled orders can no longer be changed
+    pub fn is_final(&self) -> bool {
+        matches!(self, OrderStatus::Served | OrderStatus::Cancelled)
+    }
+
+    // Orders move forward through placed -> cooking -> ready -> served, and can be
+    // cancelled at any point before they are served. Served and cancelled are final.
+    pub fn can_transition_to(&self, next: OrderStatus) -> bool {
+        matches!(
+            (self, next),
+            (OrderStatus::Placed, OrderStatus::Cooking)
+            | (OrderStatus::Cooking, OrderStatus::Ready)
+            | (OrderStatus::Ready, OrderStatus::Served)
+            | (OrderStatus::Placed | OrderStatus::Cooking | OrderStatus::Ready, OrderStatus::Cancelled))
+    }
+}
+
+impl FromStr for OrderStatus {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "placed" => Ok(OrderStatus::Placed),
+            "cooking" => Ok(OrderStatus::Cooking),
+            "ready" => Ok(OrderStatus::Ready),
+            "served" => Ok(OrderStatus::Served),
+            "cancelled" => Ok(OrderStatus::Cancelled),
+            x => Err(format!("Unknown order status: {x}"))
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+use rocket::fairing::AdHoc;
+use rocket::tokio;
+
+use crate::clock::Clock;
+use crate::repositories::Repository;
+use crate::settings::Settings;
+
+// Once the server has launched, periodically deletes idempotency keys which are older than idempotency_key_ttl_seconds.
+// The task is given its own repository and clock, since it outlives the borrow of Rocket's managed state.
+pub fn idempotency_keys(repository: Box<dyn Repository>, clock: Box<dyn Clock>) -> AdHoc {
+    AdHoc::on_liftoff("Idempotency key purge", |rocket| Box::pin(async move {
+        let settings = rocket.state::<Settings>().cloned().unwrap_or_default();
+        every(settings.idempotency_purge_interval_seconds, move || {
+            if let Err(e) = repository.purge_idempotency_keys(clock.as_ref(), settings.idempotency_key_ttl_seconds) {
+                eprintln!("Failed to purge expired idempotency keys: {e}");
+            }
+        });
+    }))
+}
+
+// Once the server has launched, periodically deletes order events which are older than order_event_ttl_seconds
+pub fn order_events(repository: Box<dyn Repository>, clock: Box<dyn Clock>) -> AdHoc {
+    AdHoc::on_liftoff("Order event purge", |rocket| Box::pin(async move {
+        let settings = rocket.state::<Settings>().cloned().unwrap_or_default();
+        every(settings.order_event_purge_interval_seconds, move || {
+            if let Err(e) = repository.purge_order_events(clock.as_ref(), settings.order_event_ttl_seconds) {
+                eprintln!("Failed to purge expired order events: {e}");
+            }
+        });
+    }))
+}
+
+// Runs the purge in the background every interval_seconds, starting straight away
+fn every(interval_seconds: u64, purge: impl Fn() + Send + 'static) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_seconds.max(1)));
+        loop {
+            interval.tick().await;
+            purge();
+        }
+    });
+}
@@ -0,0 +1,14 @@
+use rand::Rng;
+
+pub trait RandomNumberGenerator: Send + Sync {
+    // Returns a number between min and max, inclusive
+    fn gen_range(&self, min: u32, max: u32) -> u32;
+}
+
+pub struct DefaultRandomNumberGenerator {}
+
+impl RandomNumberGenerator for DefaultRandomNumberGenerator {
+    fn gen_range(&self, min: u32, max: u32) -> u32 {
+        rand::thread_rng().gen_range(min..=max)
+    }
+}
\ No newline at end of file
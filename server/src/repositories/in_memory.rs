@@ -0,0 +1,746 @@
+use std::collections::{ BTreeMap, HashMap };
+use std::sync::{ Arc, Mutex, PoisonError };
+use rocket::serde::json::to_string;
+
+use crate::errors::server_error::ServerError;
+use crate::clock::Clock;
+use crate::random_number_generator::RandomNumberGenerator;
+use crate::settings::Settings;
+use crate::bill_split::{ self, BillSplit };
+use crate::order_query::{ OrderQuery, TableFilter };
+use crate::order_events::{ OrderEvent, OrderEventKind };
+use crate::order_status::OrderStatus;
+use crate::repositories::{ IdempotencyRepository, MenuRepository, OrderRepository, TableRepository };
+use crate::rules::{ self, choose_modifiers, modifiers_from_body, normalize_section, price_with_modifiers, validate_menu_item, validate_note, validate_table };
+use crate::seed::Seed;
+use crate::{ rest_responses, rest_bodies };
+
+// Stores everything in memory, for tests and for running the server without a database file.
+// Nothing is kept once the repository is dropped. Clones share the same data.
+#[derive(Clone)]
+pub struct InMemoryRepository {
+    data: Arc<Mutex<Data>>
+}
+
+#[derive(Default)]
+struct Data {
+    menu_items: BTreeMap<u32, rest_responses::MenuItem>,
+    tables: BTreeMap<u32, TableRecord>,
+    // Checked out orders are kept, like in the database
+    orders: BTreeMap<u32, OrderRecord>,
+    // Keyed by method, route and idempotency key
+    idempotent_requests: HashMap<(String, String, String), IdempotentRequest>,
+    order_events: Vec<OrderEventRecord>,
+    // Order ids are never reused, even after orders are deleted
+    last_order_id: u32,
+    // Event ids are never reused, even after old events are purged
+    last_order_event_id: u64
+}
+
+#[derive(Clone)]
+struct TableRecord {
+    capacity: u32,
+    section: Option<String>,
+    open: bool
+}
+
+#[derive(Clone)]
+struct OrderRecord {
+    id: u32,
+    menu_item_id: u32,
+    table_number: u32,
+    minutes_to_cook: u32,
+    price: u32,
+    created_at: u64,
+    status: OrderStatus,
+    status_times: rest_responses::StatusTimes,
+    checked_out_at: Option<u64>,
+    seat: Option<u32>,
+    modifiers: Vec<rest_responses::Modifier>,
+    note: Option<String>
+}
+
+#[derive(Clone)]
+struct IdempotentRequest {
+    created_at: u64,
+    request: String,
+    response: Option<(u16, String)>
+}
+
+#[derive(Clone)]
+struct OrderEventRecord {
+    event: OrderEvent,
+    table_number: u32,
+    previous_table_number: Option<u32>,
+    created_at: u64
+}
+
+impl Default for InMemoryRepository {
+    fn default() -> Self {
+        InMemoryRepository::new()
+    }
+}
+
+impl InMemoryRepository {
+    // Starts with the default menu, like a newly set up database
+    pub fn new() -> InMemoryRepository {
+        InMemoryRepository::with_seed(&Seed::default())
+    }
+
+    // Starts with the seed's menu instead, which is expected to have been validated
+    pub fn with_seed(seed: &Seed) -> InMemoryRepository {
+        let mut data = Data::default();
+        for (id, (item, modifiers)) in (1..).zip(seed.items()) {
+            data.menu_items.insert(id, rest_responses::MenuItem {
+                id,
+                name: item.name.clone(),
+                min_minutes_to_cook: item.min_minutes_to_cook,
+                max_minutes_to_cook: item.max_minutes_to_cook,
+                price: item.price,
+                retired: false,
+                modifiers
+            });
+        }
+
+        InMemoryRepository {
+            data: Arc::new(Mutex::new(data))
+        }
+    }
+
+    fn read<T>(&self, f: impl FnOnce(&Data) -> Result<T, ServerError>) -> Result<T, ServerError> {
+        let data = self.data.lock().unwrap_or_else(PoisonError::into_inner);
+        f(&data)
+    }
+
+    // Makes changes in place while holding the lock. So that a failed write leaves the data as it was, every change
+    // makes all of its checks, and works out everything which can fail, before it changes anything.
+    // A panic can then only happen before the data has been changed, so a poisoned lock is safe to use.
+    fn write<T>(&self, f: impl FnOnce(&mut Data) -> Result<T, ServerError>) -> Result<T, ServerError> {
+        let mut data = self.data.lock().unwrap_or_else(PoisonError::into_inner);
+        f(&mut data)
+    }
+}
+
+impl Data {
+    // Like INTEGER PRIMARY KEYs, new ids are one more than the largest id so far.
+    // Menu items are never deleted, so their ids are never reused.
+    fn next_menu_item_id(&self) -> u32 {
+        self.menu_items.keys().next_back().map_or(1, |id| id + 1)
+    }
+
+    // Returns DataNotFound if the table has not been registered
+    fn table(&self, table_number: u32) -> Result<rest_responses::Table, ServerError> {
+        let table = self.tables.get(&table_number).ok_or(ServerError::DataNotFound)?;
+        Result::Ok(rest_responses::Table {
+            number: table_number,
+            capacity: table.capacity,
+            section: table.section.clone(),
+            open: table.open,
+            order_count: self.open_orders(table_number)
+                .filter(|o| o.status != OrderStatus::Cancelled)
+                .count() as u32
+        })
+    }
+
+    // Unless registered tables are required, orders can be added to any table number.
+    // Otherwise returns TableNotFound if the table has not been registered, or TableClosed if it is closed.
+    fn check_table_accepts_orders(&self, settings: &Settings, table_number: u32) -> Result<(), ServerError> {
+        if !settings.require_registered_tables {
+            return Result::Ok(());
+        }
+
+        match self.tables.get(&table_number) {
+            None => Err(ServerError::TableNotFound),
+            Some(table) if !table.open => Err(ServerError::TableClosed),
+            Some(_) => Result::Ok(())
+        }
+    }
+
+    // Returns MenuItemNotFound if no menu item with the given id can be ordered
+    fn orderable_menu_item(&self, menu_item_id: u32) -> Result<&rest_responses::MenuItem, ServerError> {
+        self.menu_items.get(&menu_item_id)
+            .filter(|m| !m.retired)
+            .ok_or(ServerError::MenuItemNotFound)
+    }
+
+    // The open orders of a table, by id
+    fn open_orders(&self, table_number: u32) -> impl Iterator<Item = &OrderRecord> {
+        self.orders.values().filter(move |o| o.table_number == table_number && o.checked_out_at.is_none())
+    }
+
+    // Returns DataNotFound if the table has no open order with the given id
+    fn open_order(&self, table_number: u32, order_id: u32) -> Result<&OrderRecord, ServerError> {
+        self.orders.get(&order_id)
+            .filter(|o| o.table_number == table_number && o.checked_out_at.is_none())
+            .ok_or(ServerError::DataNotFound)
+    }
+
+    // Converts a stored order into a response, calculating how long is left to cook as of `now`
+    fn order(&self, order: &OrderRecord, now: u64) -> Result<rest_responses::Order, ServerError> {
+        let menu_item = self.menu_items.get(&order.menu_item_id)
+            .ok_or_else(|| ServerError::StorageError(format!("Order {} has an unknown menu item.", order.id)))?;
+        let (ready_at, minutes_remaining) = rules::order_timing(order.status, order.created_at, order.minutes_to_cook, now);
+
+        Result::Ok(rest_responses::Order {
+            id: order.id,
+            table_number: order.table_number,
+            menu_item_id: order.menu_item_id,
+            menu_item_name: menu_item.name.clone(),
+            minutes_to_cook: order.minutes_to_cook,
+            status: order.status,
+            status_times: order.status_times.clone(),
+            created_at: order.created_at,
+            minutes_remaining,
+            ready_at,
+            price: order.price,
+            seat: order.seat,
+            modifiers: order.modifiers.clone(),
+            note: order.note.clone()
+        })
+    }
+
+    // Lists the open orders which the filter keeps and which match the query, in the query's order
+    fn query_orders(&self, now: u64, filter: impl Fn(&OrderRecord) -> bool, query: &OrderQuery) -> Result<rest_responses::Orders, ServerError> {
+        let mut items = Vec::new();
+        for order in self.orders.values().filter(|o| o.checked_out_at.is_none() && filter(o)) {
+            let item = self.order(order, now)?;
+            if query.matches(&item) {
+                items.push(item);
+            }
+        }
+        items.sort_by_key(|item| query.sort_key(item));
+        // one extra order shows whether there is another page
+        if let Some(limit) = query.limit {
+            items.truncate(limit as usize + 1);
+        }
+        Result::Ok(query.page(items))
+    }
+
+    // Totals the open orders of a table. Cancelled orders are not charged.
+    fn bill(&self, table_number: u32) -> Result<rest_responses::Bill, ServerError> {
+        let mut line_items = Vec::new();
+        for order in self.open_orders(table_number).filter(|o| o.status != OrderStatus::Cancelled) {
+            line_items.push(rest_responses::BillLineItem {
+                order_id: order.id,
+                menu_item_id: order.menu_item_id,
+                menu_item_name: self.order(order, 0)?.menu_item_name,
+                price: order.price,
+                seat: order.seat
+            });
+        }
+        Result::Ok(rules::bill(table_number, line_items))
+    }
+
+    // Adds events to the event log, giving each the next id
+    fn record_order_events(&mut self, events: impl IntoIterator<Item = OrderEventRecord>) {
+        for mut event in events {
+            self.last_order_event_id += 1;
+            event.event.id = self.last_order_event_id;
+            self.order_events.push(event);
+        }
+    }
+}
+
+// An event about a change to an order, ready to be recorded. previous_table_number is set when the order moved tables.
+fn order_event(now: u64, kind: OrderEventKind, order: &rest_responses::Order, previous_table_number: Option<u32>) -> Result<OrderEventRecord, ServerError> {
+    let data = to_string(order).map_err(|e| ServerError::StorageError(e.to_string()))?;
+    Result::Ok(OrderEventRecord {
+        // given by record_order_events
+        event: OrderEvent {
+            id: 0,
+            kind,
+            data
+        },
+        table_number: order.table_number,
+        previous_table_number,
+        created_at: now
+    })
+}
+
+impl MenuRepository for InMemoryRepository {
+    fn get_menu_items(&self, include_retired: bool) -> Result<rest_responses::MenuItems, ServerError> {
+        self.read(|data| Result::Ok(rest_responses::MenuItems {
+            menu_items: data.menu_items.values()
+                .filter(|m| include_retired || !m.retired)
+                .cloned()
+                .collect()
+        }))
+    }
+
+    fn add_menu_item(&self, menu_item: rest_bodies::MenuItem) -> Result<rest_responses::MenuItem, ServerError> {
+        let modifiers = menu_item.modifiers.map(modifiers_from_body).unwrap_or_default();
+        validate_menu_item(&menu_item.name, menu_item.min_minutes_to_cook, menu_item.max_minutes_to_cook, menu_item.price, &modifiers)?;
+
+        self.write(|data| {
+            if data.menu_items.values().any(|m| m.name == menu_item.name) {
+                return Err(ServerError::DuplicateMenuItem);
+            }
+
+            let added = rest_responses::MenuItem {
+                id: data.next_menu_item_id(),
+                name: menu_item.name,
+                min_minutes_to_cook: menu_item.min_minutes_to_cook,
+                max_minutes_to_cook: menu_item.max_minutes_to_cook,
+                price: menu_item.price,
+                retired: false,
+                modifiers
+            };
+            data.menu_items.insert(added.id, added.clone());
+            Result::Ok(added)
+        })
+    }
+
+    fn update_menu_item(&self, menu_item_id: u32, update: rest_bodies::MenuItemUpdate) -> Result<rest_responses::MenuItem, ServerError> {
+        self.write(|data| {
+            // fields which are not provided keep their current values, which the new ones are validated against
+            let current = data.menu_items.get(&menu_item_id).ok_or(ServerError::DataNotFound)?;
+            let updated = rest_responses::MenuItem {
+                id: menu_item_id,
+                name: update.name.unwrap_or_else(|| current.name.clone()),
+                min_minutes_to_cook: update.min_minutes_to_cook.unwrap_or(current.min_minutes_to_cook),
+                max_minutes_to_cook: update.max_minutes_to_cook.unwrap_or(current.max_minutes_to_cook),
+                price: update.price.unwrap_or(current.price),
+                retired: current.retired,
+                // orders which have already been made keep the modifiers they were made with
+                modifiers: update.modifiers.map(modifiers_from_body).unwrap_or_else(|| current.modifiers.clone())
+            };
+            validate_menu_item(&updated.name, updated.min_minutes_to_cook, updated.max_minutes_to_cook, updated.price, &updated.modifiers)?;
+
+            if data.menu_items.values().any(|m| m.name == updated.name && m.id != menu_item_id) {
+                return Err(ServerError::DuplicateMenuItem);
+            }
+
+            data.menu_items.insert(menu_item_id, updated.clone());
+            Result::Ok(updated)
+        })
+    }
+
+    fn retire_menu_item(&self, menu_item_id: u32) -> Result<(), ServerError> {
+        self.write(|data| {
+            let menu_item = data.menu_items.get_mut(&menu_item_id).ok_or(ServerError::DataNotFound)?;
+            menu_item.retired = true;
+            Result::Ok(())
+        })
+    }
+}
+
+impl TableRepository for InMemoryRepository {
+    fn get_tables(&self) -> Result<rest_responses::Tables, ServerError> {
+        self.read(|data| Result::Ok(rest_responses::Tables {
+            tables: data.tables.keys()
+                .map(|number| data.table(*number))
+                .collect::<Result<Vec<_>, ServerError>>()?
+        }))
+    }
+
+    fn get_table(&self, table_number: u32) -> Result<rest_responses::Table, ServerError> {
+        self.read(|data| data.table(table_number))
+    }
+
+    fn add_table(&self, table: rest_bodies::Table) -> Result<rest_responses::Table, ServerError> {
+        validate_table(table.capacity)?;
+
+        self.write(|data| {
+            if data.tables.contains_key(&table.number) {
+                return Err(ServerError::DuplicateTable);
+            }
+
+            data.tables.insert(table.number, TableRecord {
+                capacity: table.capacity,
+                section: normalize_section(table.section.as_deref()),
+                open: table.open.unwrap_or(true)
+            });
+            data.table(table.number)
+        })
+    }
+
+    fn update_table(&self, table_number: u32, update: rest_bodies::TableUpdate) -> Result<rest_responses::Table, ServerError> {
+        self.write(|data| {
+            // fields which are not provided keep their current values
+            let table = data.tables.get_mut(&table_number).ok_or(ServerError::DataNotFound)?;
+            let capacity = update.capacity.unwrap_or(table.capacity);
+            validate_table(capacity)?;
+
+            table.capacity = capacity;
+            if let Some(section) = update.section {
+                table.section = normalize_section(Some(&section));
+            }
+            table.open = update.open.unwrap_or(table.open);
+            data.table(table_number)
+        })
+    }
+
+    fn delete_table(&self, table_number: u32) -> Result<(), ServerError> {
+        self.write(|data| {
+            if !data.tables.contains_key(&table_number) {
+                return Err(ServerError::DataNotFound);
+            }
+            if data.open_orders(table_number).next().is_some() {
+                return Err(ServerError::TableHasOpenOrders);
+            }
+
+            data.tables.remove(&table_number);
+            Result::Ok(())
+        })
+    }
+}
+
+impl OrderRepository for InMemoryRepository {
+    fn add_orders(&self, clock: &dyn Clock, rng: &dyn RandomNumberGenerator, settings: &Settings, table_number: u32, orders: rest_bodies::Orders) -> Result<rest_responses::Orders, ServerError> {
+        self.write(|data| {
+            data.check_table_accepts_orders(settings, table_number)?;
+
+            let mut records = Vec::new();
+            let mut items = Vec::new();
+            let mut events = Vec::new();
+            let now = clock.now();
+
+            for order in orders.orders {
+                // the price and modifiers are copied onto the order so that later changes to the menu do not affect open bills
+                let menu_item = data.orderable_menu_item(order.menu_item_id)?;
+                let modifiers = choose_modifiers(menu_item, order.modifiers.as_deref().unwrap_or_default())?;
+                let note = validate_note(order.note.as_deref())?;
+                let record = OrderRecord {
+                    id: data.last_order_id + records.len() as u32 + 1,
+                    menu_item_id: order.menu_item_id,
+                    table_number,
+                    minutes_to_cook: rng.gen_range(menu_item.min_minutes_to_cook, menu_item.max_minutes_to_cook),
+                    price: price_with_modifiers(menu_item.price, &modifiers)?,
+                    created_at: now,
+                    status: OrderStatus::Placed,
+                    status_times: rest_responses::StatusTimes {
+                        placed_at: Some(now),
+                        cooking_at: None,
+                        ready_at: None,
+                        served_at: None,
+                        cancelled_at: None
+                    },
+                    checked_out_at: None,
+                    seat: order.seat,
+                    modifiers,
+                    note
+                };
+
+                let item = data.order(&record, now)?;
+                events.push(order_event(now, OrderEventKind::Created, &item, None)?);
+                records.push(record);
+                items.push(item);
+            }
+
+            data.last_order_id += records.len() as u32;
+            data.orders.extend(records.into_iter().map(|record| (record.id, record)));
+            data.record_order_events(events);
+
+            Result::Ok(rest_responses::Orders {
+                orders: items,
+                next_cursor: None
+            })
+        })
+    }
+
+    fn get_orders(&self, clock: &dyn Clock, table_number: u32, query: &OrderQuery) -> Result<rest_responses::Orders, ServerError> {
+        self.read(|data| data.query_orders(clock.now(), |o| o.table_number == table_number, query))
+    }
+
+    fn get_all_orders(&self, clock: &dyn Clock, tables: &TableFilter, query: &OrderQuery) -> Result<rest_responses::Orders, ServerError> {
+        self.read(|data| data.query_orders(clock.now(), |o| tables.contains(o.table_number), query))
+    }
+
+    fn get_order(&self, clock: &dyn Clock, table_number: u32, order_id: u32) -> Result<rest_responses::Order, ServerError> {
+        self.read(|data| data.order(data.open_order(table_number, order_id)?, clock.now()))
+    }
+
+    fn delete_order(&self, clock: &dyn Clock, table_number: u32, order_id: u32) -> Result<(), ServerError> {
+        self.write(|data| {
+            let now = clock.now();
+            let item = data.order(data.open_order(table_number, order_id)?, now)?;
+            let event = order_event(now, OrderEventKind::Deleted, &item, None)?;
+            data.orders.remove(&order_id);
+            data.record_order_events([event]);
+            Result::Ok(())
+        })
+    }
+
+    fn delete_orders(&self, clock: &dyn Clock, table_number: u32, ids: Option<&[u32]>) -> Result<rest_responses::Orders, ServerError> {
+        self.write(|data| {
+            let now = clock.now();
+            let items = data.open_orders(table_number)
+                .filter(|o| ids.is_none_or(|ids| ids.contains(&o.id)))
+                .map(|o| data.order(o, now))
+                .collect::<Result<Vec<_>, ServerError>>()?;
+
+            if let Some(ids) = ids {
+                if ids.iter().any(|id| !items.iter().any(|i| i.id == *id)) {
+                    return Err(ServerError::DataNotFound);
+                }
+            }
+
+            let events = items.iter()
+                .map(|item| order_event(now, OrderEventKind::Deleted, item, None))
+                .collect::<Result<Vec<_>, ServerError>>()?;
+            for item in &items {
+                data.orders.remove(&item.id);
+            }
+            data.record_order_events(events);
+
+            Result::Ok(rest_responses::Orders {
+                orders: items,
+                next_cursor: None
+            })
+        })
+    }
+
+    fn update_order(&self, clock: &dyn Clock, rng: &dyn RandomNumberGenerator, settings: &Settings, table_number: u32, order_id: u32, update: rest_bodies::OrderUpdate) -> Result<rest_responses::Order, ServerError> {
+        self.write(|data| {
+            let now = clock.now();
+            if let Some(new_table_number) = update.table_number {
+                data.check_table_accepts_orders(settings, new_table_number)?;
+            }
+
+            let current = data.order(data.open_order(table_number, order_id)?, now)?;
+            if current.status.is_final() {
+                return Err(ServerError::OrderFinal(current.status));
+            }
+
+            // a different menu item will take a different amount of time to cook, unless the kitchen has provided its own estimate,
+            // and is charged at the new menu item's current price
+            let mut minutes_to_cook = update.minutes_to_cook;
+            let mut price = None;
+            let mut modifiers = None;
+            if update.menu_item_id.is_some() || update.modifiers.is_some() {
+                let menu_item = data.orderable_menu_item(update.menu_item_id.unwrap_or(current.menu_item_id))?;
+
+                // modifiers which are not being replaced must also be offered by the new menu item
+                let modifier_names = update.modifiers
+                    .unwrap_or_else(|| current.modifiers.iter().map(|m| m.name.clone()).collect());
+                let chosen_modifiers = choose_modifiers(menu_item, &modifier_names)?;
+
+                // when only the modifiers change, the order keeps the price its menu item was ordered at
+                let base_price = match update.menu_item_id {
+                    Some(_) => {
+                        minutes_to_cook = minutes_to_cook.or_else(|| Some(rng.gen_range(menu_item.min_minutes_to_cook, menu_item.max_minutes_to_cook)));
+                        menu_item.price
+                    },
+                    None => rules::base_price(&current)?
+                };
+                price = Some(price_with_modifiers(base_price, &chosen_modifiers)?);
+                modifiers = Some(chosen_modifiers);
+            }
+            let note = match update.note {
+                Some(note) => Some(validate_note(Some(&note))?),
+                None => None
+            };
+
+            // only the provided fields are changed, and with nothing to change the order is returned as is
+            let changed = update.menu_item_id.is_some() || update.table_number.is_some() || minutes_to_cook.is_some()
+                || price.is_some() || update.seat.is_some() || modifiers.is_some() || note.is_some();
+            if !changed {
+                return Result::Ok(current);
+            }
+            let mut order = data.open_order(table_number, order_id)?.clone();
+
+            if let Some(menu_item_id) = update.menu_item_id {
+                order.menu_item_id = menu_item_id;
+            }
+            if let Some(new_table_number) = update.table_number {
+                order.table_number = new_table_number;
+            }
+            if let Some(minutes_to_cook) = minutes_to_cook {
+                order.minutes_to_cook = minutes_to_cook;
+            }
+            if let Some(price) = price {
+                order.price = price;
+            }
+            if let Some(seat) = update.seat {
+                order.seat = Some(seat);
+            }
+            if let Some(modifiers) = modifiers {
+                order.modifiers = modifiers;
+            }
+            if let Some(note) = note {
+                order.note = note;
+            }
+
+            let item = data.order(&order, now)?;
+            let previous_table_number = update.table_number.filter(|n| *n != table_number).map(|_| table_number);
+            let event = order_event(now, OrderEventKind::Updated, &item, previous_table_number)?;
+            data.orders.insert(order_id, order);
+            data.record_order_events([event]);
+            Result::Ok(item)
+        })
+    }
+
+    fn transfer_orders(&self, clock: &dyn Clock, settings: &Settings, table_number: u32, transfer: rest_bodies::Transfer) -> Result<rest_responses::Orders, ServerError> {
+        if transfer.table_number == table_number {
+            return Err(ServerError::InvalidTable("Orders cannot be transferred to the table they are already on.".to_string()));
+        }
+
+        self.write(|data| {
+            data.check_table_accepts_orders(settings, transfer.table_number)?;
+
+            let source_ids = data.open_orders(table_number).map(|o| o.id).collect::<Vec<_>>();
+            let ids = match transfer.order_ids {
+                Some(ids) if ids.iter().any(|id| !source_ids.contains(id)) => return Err(ServerError::DataNotFound),
+                Some(ids) => ids,
+                None => source_ids
+            };
+
+            let now = clock.now();
+            let mut moved = Vec::new();
+            let mut events = Vec::new();
+            for id in &ids {
+                let mut order = data.open_order(table_number, *id)?.clone();
+                order.table_number = transfer.table_number;
+                events.push(order_event(now, OrderEventKind::Updated, &data.order(&order, now)?, Some(table_number))?);
+                moved.push(order);
+            }
+
+            // the new table's orders as they will be once the transferred orders have moved to it
+            let mut transferred = data.query_orders(now, |o| o.table_number == transfer.table_number || ids.contains(&o.id), &OrderQuery::default())?;
+            for item in &mut transferred.orders {
+                item.table_number = transfer.table_number;
+            }
+
+            data.orders.extend(moved.into_iter().map(|order| (order.id, order)));
+            data.record_order_events(events);
+            Result::Ok(transferred)
+        })
+    }
+
+    fn update_order_status(&self, clock: &dyn Clock, table_number: u32, order_id: u32, status: OrderStatus) -> Result<rest_responses::Order, ServerError> {
+        self.write(|data| {
+            let now = clock.now();
+            let mut order = data.open_order(table_number, order_id)?.clone();
+            if !order.status.can_transition_to(status) {
+                return Err(ServerError::InvalidStatusTransition(order.status, status));
+            }
+
+            order.status = status;
+            let status_time = match status {
+                OrderStatus::Placed => &mut order.status_times.placed_at,
+                OrderStatus::Cooking => &mut order.status_times.cooking_at,
+                OrderStatus::Ready => &mut order.status_times.ready_at,
+                OrderStatus::Served => &mut order.status_times.served_at,
+                OrderStatus::Cancelled => &mut order.status_times.cancelled_at
+            };
+            *status_time = Some(now);
+
+            let item = data.order(&order, now)?;
+            let event = order_event(now, OrderEventKind::Updated, &item, None)?;
+            data.orders.insert(order_id, order);
+            data.record_order_events([event]);
+            Result::Ok(item)
+        })
+    }
+
+    fn get_bill(&self, table_number: u32) -> Result<rest_responses::Bill, ServerError> {
+        self.read(|data| data.bill(table_number))
+    }
+
+    fn get_split_bill(&self, table_number: u32, split: BillSplit) -> Result<rest_responses::SplitBill, ServerError> {
+        let bill = self.read(|data| data.bill(table_number))?;
+        bill_split::split_bill(bill, split)
+    }
+
+    fn checkout(&self, clock: &dyn Clock, table_number: u32) -> Result<rest_responses::Bill, ServerError> {
+        self.write(|data| {
+            let bill = data.bill(table_number)?;
+
+            // closed orders are no longer listed, so to anyone following the event log they have been deleted
+            let now = clock.now();
+            let closed = data.query_orders(now, |o| o.table_number == table_number, &OrderQuery::default())?;
+            if closed.orders.is_empty() {
+                return Err(ServerError::DataNotFound);
+            }
+            let events = closed.orders.iter()
+                .map(|item| order_event(now, OrderEventKind::Deleted, item, None))
+                .collect::<Result<Vec<_>, ServerError>>()?;
+            for item in &closed.orders {
+                if let Some(order) = data.orders.get_mut(&item.id) {
+                    order.checked_out_at = Some(now);
+                }
+            }
+            data.record_order_events(events);
+
+            Result::Ok(bill)
+        })
+    }
+
+    fn get_order_events(&self, tables: &TableFilter, after_id: u64, limit: u32) -> Result<Vec<OrderEvent>, ServerError> {
+        self.read(|data| Result::Ok(data.order_events.iter()
+            .filter(|e| e.event.id > after_id)
+            .filter(|e| tables.contains(e.table_number) || e.previous_table_number.is_some_and(|n| tables.contains(n)))
+            .take(limit as usize)
+            .map(|e| e.event.clone())
+            .collect()))
+    }
+
+    fn get_last_order_event_id(&self) -> Result<u64, ServerError> {
+        self.read(|data| Result::Ok(data.order_events.last().map_or(0, |e| e.event.id)))
+    }
+
+    fn purge_order_events(&self, clock: &dyn Clock, ttl_seconds: u64) -> Result<u64, ServerError> {
+        let expired_at = clock.now().saturating_sub(ttl_seconds);
+        self.write(|data| {
+            let count = data.order_events.len();
+            data.order_events.retain(|e| e.created_at > expired_at);
+            Result::Ok((count - data.order_events.len()) as u64)
+        })
+    }
+}
+
+impl IdempotencyRepository for InMemoryRepository {
+    fn begin_idempotent_request(&self, clock: &dyn Clock, method: &str, route: &str, key: &str, request: &str) -> Result<Option<(u16, String)>, ServerError> {
+        self.write(|data| {
+            let id = (method.to_string(), route.to_string(), key.to_string());
+            match data.idempotent_requests.get(&id) {
+                Some(stored) if stored.request != request => Err(ServerError::IdempotencyKeyReused),
+                Some(stored) => match &stored.response {
+                    Some(response) => Result::Ok(Some(response.clone())),
+                    // the first request has not finished yet
+                    None => Err(ServerError::Idempotency)
+                },
+                None => {
+                    data.idempotent_requests.insert(id, IdempotentRequest {
+                        created_at: clock.now(),
+                        request: request.to_string(),
+                        response: None
+                    });
+                    Result::Ok(None)
+                }
+            }
+        })
+    }
+
+    fn complete_idempotent_request(&self, method: &str, route: &str, key: &str, status: u16, response: &str) -> Result<(), ServerError> {
+        self.write(|data| {
+            if let Some(stored) = data.idempotent_requests.get_mut(&(method.to_string(), route.to_string(), key.to_string())) {
+                stored.response = Some((status, response.to_string()));
+            }
+            Result::Ok(())
+        })
+    }
+
+    fn release_idempotent_request(&self, method: &str, route: &str, key: &str) -> Result<(), ServerError> {
+        self.write(|data| {
+            data.idempotent_requests.remove(&(method.to_string(), route.to_string(), key.to_string()));
+            Result::Ok(())
+        })
+    }
+
+    fn get_idempotency_keys(&self) -> Result<rest_responses::IdempotencyKeys, ServerError> {
+        self.read(|data| Result::Ok(rest_responses::IdempotencyKeys {
+            count: data.idempotent_requests.len() as u64,
+            oldest_created_at: data.idempotent_requests.values().map(|r| r.created_at).min()
+        }))
+    }
+
+    fn purge_idempotency_keys(&self, clock: &dyn Clock, ttl_seconds: u64) -> Result<u64, ServerError> {
+        let expired_at = clock.now().saturating_sub(ttl_seconds);
+        self.write(|data| {
+            let count = data.idempotent_requests.len();
+            data.idempotent_requests.retain(|_, r| r.created_at > expired_at);
+            Result::Ok((count - data.idempotent_requests.len()) as u64)
+        })
+    }
+}
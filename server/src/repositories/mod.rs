@@ -0,0 +1,99 @@
+use std::sync::Arc;
+use rocket::{ Build, Rocket };
+
+use crate::errors::server_error::ServerError;
+use crate::clock::Clock;
+use crate::random_number_generator::RandomNumberGenerator;
+use crate::settings::Settings;
+use crate::bill_split::BillSplit;
+use crate::order_query::{ OrderQuery, TableFilter };
+use crate::order_events::OrderEvent;
+use crate::order_status::OrderStatus;
+use crate::migrations::Migration;
+use crate::seed::Seed;
+use crate::{ rest_responses, rest_bodies };
+
+pub mod sqlite;
+pub mod in_memory;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+
+// The storage behind the endpoints. Each trait is managed by Rocket as an Arc<dyn _>, so endpoints only depend on
+// the data they use and never on how it is stored, and can hand it to a blocking task. Every implementation must behave the same way, down to the
+// errors it returns; the integration tests can be run against each of them.
+
+pub trait MenuRepository: Send + Sync {
+    fn get_menu_items(&self, include_retired: bool) -> Result<rest_responses::MenuItems, ServerError>;
+    fn add_menu_item(&self, menu_item: rest_bodies::MenuItem) -> Result<rest_responses::MenuItem, ServerError>;
+    fn update_menu_item(&self, menu_item_id: u32, update: rest_bodies::MenuItemUpdate) -> Result<rest_responses::MenuItem, ServerError>;
+    // Menu items are never removed, as existing orders still reference them.
+    // Retired items are hidden from the menu and can no longer be ordered.
+    fn retire_menu_item(&self, menu_item_id: u32) -> Result<(), ServerError>;
+}
+
+pub trait TableRepository: Send + Sync {
+    fn get_tables(&self) -> Result<rest_responses::Tables, ServerError>;
+    // Returns DataNotFound if the table has not been registered
+    fn get_table(&self, table_number: u32) -> Result<rest_responses::Table, ServerError>;
+    fn add_table(&self, table: rest_bodies::Table) -> Result<rest_responses::Table, ServerError>;
+    fn update_table(&self, table_number: u32, update: rest_bodies::TableUpdate) -> Result<rest_responses::Table, ServerError>;
+    // Tables can only be removed once all of their orders have been checked out or deleted
+    fn delete_table(&self, table_number: u32) -> Result<(), ServerError>;
+}
+
+// Orders, along with the bills they add up to and the event log of their changes
+pub trait OrderRepository: Send + Sync {
+    fn add_orders(&self, clock: &dyn Clock, rng: &dyn RandomNumberGenerator, settings: &Settings, table_number: u32, orders: rest_bodies::Orders) -> Result<rest_responses::Orders, ServerError>;
+    fn get_orders(&self, clock: &dyn Clock, table_number: u32, query: &OrderQuery) -> Result<rest_responses::Orders, ServerError>;
+    // Lists open orders across all of the tables matched by the filter
+    fn get_all_orders(&self, clock: &dyn Clock, tables: &TableFilter, query: &OrderQuery) -> Result<rest_responses::Orders, ServerError>;
+    // Returns DataNotFound if the table has no open order with the given id
+    fn get_order(&self, clock: &dyn Clock, table_number: u32, order_id: u32) -> Result<rest_responses::Order, ServerError>;
+    fn delete_order(&self, clock: &dyn Clock, table_number: u32, order_id: u32) -> Result<(), ServerError>;
+    // When ids are given, only those orders are deleted, and DataNotFound is returned (with nothing deleted)
+    // if any of them is not an open order of the table
+    fn delete_orders(&self, clock: &dyn Clock, table_number: u32, ids: Option<&[u32]>) -> Result<rest_responses::Orders, ServerError>;
+    fn update_order(&self, clock: &dyn Clock, rng: &dyn RandomNumberGenerator, settings: &Settings, table_number: u32, order_id: u32, update: rest_bodies::OrderUpdate) -> Result<rest_responses::Order, ServerError>;
+    fn transfer_orders(&self, clock: &dyn Clock, settings: &Settings, table_number: u32, transfer: rest_bodies::Transfer) -> Result<rest_responses::Orders, ServerError>;
+    fn update_order_status(&self, clock: &dyn Clock, table_number: u32, order_id: u32, status: OrderStatus) -> Result<rest_responses::Order, ServerError>;
+    fn get_bill(&self, table_number: u32) -> Result<rest_responses::Bill, ServerError>;
+    fn get_split_bill(&self, table_number: u32, split: BillSplit) -> Result<rest_responses::SplitBill, ServerError>;
+    // Returns DataNotFound if the table has no open orders
+    fn checkout(&self, clock: &dyn Clock, table_number: u32) -> Result<rest_responses::Bill, ServerError>;
+    fn get_order_events(&self, tables: &TableFilter, after_id: u64, limit: u32) -> Result<Vec<OrderEvent>, ServerError>;
+    // The id of the most recent event, or 0 if nothing has happened yet
+    fn get_last_order_event_id(&self) -> Result<u64, ServerError>;
+    fn purge_order_events(&self, clock: &dyn Clock, ttl_seconds: u64) -> Result<u64, ServerError>;
+}
+
+// Responses stored for requests made with an idempotency key, as described in server_functions
+pub trait IdempotencyRepository: Send + Sync {
+    fn begin_idempotent_request(&self, clock: &dyn Clock, method: &str, route: &str, key: &str, request: &str) -> Result<Option<(u16, String)>, ServerError>;
+    fn complete_idempotent_request(&self, method: &str, route: &str, key: &str, status: u16, response: &str) -> Result<(), ServerError>;
+    fn release_idempotent_request(&self, method: &str, route: &str, key: &str) -> Result<(), ServerError>;
+    fn get_idempotency_keys(&self) -> Result<rest_responses::IdempotencyKeys, ServerError>;
+    fn purge_idempotency_keys(&self, clock: &dyn Clock, ttl_seconds: u64) -> Result<u64, ServerError>;
+}
+
+// Storage which implements every repository, such as a database
+pub trait Repository: MenuRepository + TableRepository + OrderRepository + IdempotencyRepository {}
+
+impl<T> Repository for T where T: MenuRepository + TableRepository + OrderRepository + IdempotencyRepository {}
+
+// Storage in a database, whose schema is kept up to date with migrations
+pub trait DatabaseRepository: Repository {
+    // Applies the migrations which the database has not applied yet, returning them.
+    // In a dry run nothing is changed, but the migrations are still checked against the database.
+    fn migrate(&self, dry_run: bool) -> Result<Vec<&'static Migration>, ServerError>;
+    // Migrates the database, and adds the seed's menu if there are no menu items yet
+    fn setup(&self, seed: &Seed) -> Result<(), ServerError>;
+}
+
+// Manages each of the repositories as state. Clones of the repository must share the same storage.
+pub fn manage<R: Repository + Clone + 'static>(rocket: Rocket<Build>, repository: R) -> Rocket<Build> {
+    rocket
+        .manage(Arc::new(repository.clone()) as Arc<dyn MenuRepository>)
+        .manage(Arc::new(repository.clone()) as Arc<dyn TableRepository>)
+        .manage(Arc::new(repository.clone()) as Arc<dyn OrderRepository>)
+        .manage(Arc::new(repository) as Arc<dyn IdempotencyRepository>)
+}
@@ -0,0 +1,163 @@
+use crate::errors::server_error::ServerError;
+use crate::server_functions;
+use crate::database_pool::DatabasePool;
+use crate::clock::Clock;
+use crate::random_number_generator::RandomNumberGenerator;
+use crate::settings::Settings;
+use crate::bill_split::BillSplit;
+use crate::order_query::{ OrderQuery, TableFilter };
+use crate::order_events::OrderEvent;
+use crate::order_status::OrderStatus;
+use crate::migrations::Migration;
+use crate::seed::Seed;
+use crate::repositories::{ DatabaseRepository, IdempotencyRepository, MenuRepository, OrderRepository, TableRepository };
+use crate::{ rest_responses, rest_bodies };
+
+// Stores everything in a SQLite database, using the queries in server_functions.
+// Clones share the pool, and so the database.
+#[derive(Clone)]
+pub struct SqliteRepository {
+    pool: DatabasePool
+}
+
+impl SqliteRepository {
+    pub fn new(pool: DatabasePool) -> SqliteRepository {
+        SqliteRepository {
+            pool
+        }
+    }
+}
+
+impl DatabaseRepository for SqliteRepository {
+    fn migrate(&self, dry_run: bool) -> Result<Vec<&'static Migration>, ServerError> {
+        server_functions::migrate(&self.pool, dry_run)
+    }
+
+    fn setup(&self, seed: &Seed) -> Result<(), ServerError> {
+        server_functions::setup_database(&self.pool, seed)
+    }
+}
+
+impl MenuRepository for SqliteRepository {
+    fn get_menu_items(&self, include_retired: bool) -> Result<rest_responses::MenuItems, ServerError> {
+        server_functions::get_menu_items(&self.pool, include_retired)
+    }
+
+    fn add_menu_item(&self, menu_item: rest_bodies::MenuItem) -> Result<rest_responses::MenuItem, ServerError> {
+        server_functions::add_menu_item(&self.pool, menu_item)
+    }
+
+    fn update_menu_item(&self, menu_item_id: u32, update: rest_bodies::MenuItemUpdate) -> Result<rest_responses::MenuItem, ServerError> {
+        server_functions::update_menu_item(&self.pool, menu_item_id, update)
+    }
+
+    fn retire_menu_item(&self, menu_item_id: u32) -> Result<(), ServerError> {
+        server_functions::retire_menu_item(&self.pool, menu_item_id)
+    }
+}
+
+impl TableRepository for SqliteRepository {
+    fn get_tables(&self) -> Result<rest_responses::Tables, ServerError> {
+        server_functions::get_tables(&self.pool)
+    }
+
+    fn get_table(&self, table_number: u32) -> Result<rest_responses::Table, ServerError> {
+        server_functions::get_table(&self.pool, table_number)
+    }
+
+    fn add_table(&self, table: rest_bodies::Table) -> Result<rest_responses::Table, ServerError> {
+        server_functions::add_table(&self.pool, table)
+    }
+
+    fn update_table(&self, table_number: u32, update: rest_bodies::TableUpdate) -> Result<rest_responses::Table, ServerError> {
+        server_functions::update_table(&self.pool, table_number, update)
+    }
+
+    fn delete_table(&self, table_number: u32) -> Result<(), ServerError> {
+        server_functions::delete_table(&self.pool, table_number)
+    }
+}
+
+impl OrderRepository for SqliteRepository {
+    fn add_orders(&self, clock: &dyn Clock, rng: &dyn RandomNumberGenerator, settings: &Settings, table_number: u32, orders: rest_bodies::Orders) -> Result<rest_responses::Orders, ServerError> {
+        server_functions::add_orders(&self.pool, clock, rng, settings, table_number, orders)
+    }
+
+    fn get_orders(&self, clock: &dyn Clock, table_number: u32, query: &OrderQuery) -> Result<rest_responses::Orders, ServerError> {
+        server_functions::get_orders(&self.pool, clock, table_number, query)
+    }
+
+    fn get_all_orders(&self, clock: &dyn Clock, tables: &TableFilter, query: &OrderQuery) -> Result<rest_responses::Orders, ServerError> {
+        server_functions::get_all_orders(&self.pool, clock, tables, query)
+    }
+
+    fn get_order(&self, clock: &dyn Clock, table_number: u32, order_id: u32) -> Result<rest_responses::Order, ServerError> {
+        server_functions::get_order(&self.pool, clock, table_number, order_id)
+    }
+
+    fn delete_order(&self, clock: &dyn Clock, table_number: u32, order_id: u32) -> Result<(), ServerError> {
+        server_functions::delete_order(&self.pool, clock, table_number, order_id)
+    }
+
+    fn delete_orders(&self, clock: &dyn Clock, table_number: u32, ids: Option<&[u32]>) -> Result<rest_responses::Orders, ServerError> {
+        server_functions::delete_orders(&self.pool, clock, table_number, ids)
+    }
+
+    fn update_order(&self, clock: &dyn Clock, rng: &dyn RandomNumberGenerator, settings: &Settings, table_number: u32, order_id: u32, update: rest_bodies::OrderUpdate) -> Result<rest_responses::Order, ServerError> {
+        server_functions::update_order(&self.pool, clock, rng, settings, table_number, order_id, update)
+    }
+
+    fn transfer_orders(&self, clock: &dyn Clock, settings: &Settings, table_number: u32, transfer: rest_bodies::Transfer) -> Result<rest_responses::Orders, ServerError> {
+        server_functions::transfer_orders(&self.pool, clock, settings, table_number, transfer)
+    }
+
+    fn update_order_status(&self, clock: &dyn Clock, table_number: u32, order_id: u32, status: OrderStatus) -> Result<rest_responses::Order, ServerError> {
+        server_functions::update_order_status(&self.pool, clock, table_number, order_id, status)
+    }
+
+    fn get_bill(&self, table_number: u32) -> Result<rest_responses::Bill, ServerError> {
+        server_functions::get_bill(&self.pool, table_number)
+    }
+
+    fn get_split_bill(&self, table_number: u32, split: BillSplit) -> Result<rest_responses::SplitBill, ServerError> {
+        server_functions::get_split_bill(&self.pool, table_number, split)
+    }
+
+    fn checkout(&self, clock: &dyn Clock, table_number: u32) -> Result<rest_responses::Bill, ServerError> {
+        server_functions::checkout(&self.pool, clock, table_number)
+    }
+
+    fn get_order_events(&self, tables: &TableFilter, after_id: u64, limit: u32) -> Result<Vec<OrderEvent>, ServerError> {
+        server_functions::get_order_events(&self.pool, tables, after_id, limit)
+    }
+
+    fn get_last_order_event_id(&self) -> Result<u64, ServerError> {
+        server_functions::get_last_order_event_id(&self.pool)
+    }
+
+    fn purge_order_events(&self, clock: &dyn Clock, ttl_seconds: u64) -> Result<u64, ServerError> {
+        server_functions::purge_order_events(&self.pool, clock, ttl_seconds)
+    }
+}
+
+impl IdempotencyRepository for SqliteRepository {
+    fn begin_idempotent_request(&self, clock: &dyn Clock, method: &str, route: &str, key: &str, request: &str) -> Result<Option<(u16, String)>, ServerError> {
+        server_functions::begin_idempotent_request(&self.pool, clock, method, route, key, request)
+    }
+
+    fn complete_idempotent_request(&self, method: &str, route: &str, key: &str, status: u16, response: &str) -> Result<(), ServerError> {
+        server_functions::complete_idempotent_request(&self.pool, method, route, key, status, response)
+    }
+
+    fn release_idempotent_request(&self, method: &str, route: &str, key: &str) -> Result<(), ServerError> {
+        server_functions::release_idempotent_request(&self.pool, method, route, key)
+    }
+
+    fn get_idempotency_keys(&self) -> Result<rest_responses::IdempotencyKeys, ServerError> {
+        server_functions::get_idempotency_keys(&self.pool)
+    }
+
+    fn purge_idempotency_keys(&self, clock: &dyn Clock, ttl_seconds: u64) -> Result<u64, ServerError> {
+        server_functions::purge_idempotency_keys(&self.pool, clock, ttl_seconds)
+    }
+}
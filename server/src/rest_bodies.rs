@@ -1,5 +1,7 @@
 use rocket::serde::{ Serialize, Deserialize };
 
+use crate::order_status;
+
 #[derive(Debug)]
 #[derive(Serialize)]
 #[derive(Deserialize)]
@@ -14,5 +16,106 @@ pub struct Orders {
 #[derive(Deserialize)]
 #[serde(crate = "rocket::serde")]
 pub struct Order {
-    pub menu_item_id: u32
+    pub menu_item_id: u32,
+    pub seat: Option<u32>,
+    // Names of modifiers offered by the menu item, e.g. "no onions"
+    pub modifiers: Option<Vec<String>>,
+    // Free text instructions for the kitchen
+    pub note: Option<String>
+}
+
+#[derive(Debug)]
+#[derive(Serialize)]
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct OrderStatus {
+    pub status: order_status::OrderStatus
+}
+
+// Fields which are left out are not changed
+#[derive(Debug)]
+#[derive(Serialize)]
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct OrderUpdate {
+    pub menu_item_id: Option<u32>,
+    pub table_number: Option<u32>,
+    pub minutes_to_cook: Option<u32>,
+    pub seat: Option<u32>,
+    // Replaces all of the order's modifiers
+    pub modifiers: Option<Vec<String>>,
+    // An empty note removes the existing one
+    pub note: Option<String>
+}
+
+// Moves orders from one table to another. Without order_ids, all of the table's open orders are moved.
+#[derive(Debug)]
+#[derive(Serialize)]
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Transfer {
+    pub table_number: u32,
+    pub order_ids: Option<Vec<u32>>
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Serialize)]
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct MenuItem {
+    pub name: String,
+    pub min_minutes_to_cook: u32,
+    pub max_minutes_to_cook: u32,
+    pub price: u32,
+    pub modifiers: Option<Vec<MenuItemModifier>>
+}
+
+// A change which can be requested when ordering a menu item. The price delta is added to the
+// menu item's price, and defaults to 0.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Serialize)]
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct MenuItemModifier {
+    pub name: String,
+    pub price_delta: Option<u32>
+}
+
+// Tables are open unless specified otherwise
+#[derive(Debug)]
+#[derive(Serialize)]
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Table {
+    pub number: u32,
+    pub capacity: u32,
+    pub section: Option<String>,
+    pub open: Option<bool>
+}
+
+// Fields which are left out are not changed. An empty section removes the existing one.
+#[derive(Debug)]
+#[derive(Serialize)]
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct TableUpdate {
+    pub capacity: Option<u32>,
+    pub section: Option<String>,
+    pub open: Option<bool>
+}
+
+// Fields which are left out are not changed
+#[derive(Debug)]
+#[derive(Serialize)]
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct MenuItemUpdate {
+    pub name: Option<String>,
+    pub min_minutes_to_cook: Option<u32>,
+    pub max_minutes_to_cook: Option<u32>,
+    pub price: Option<u32>,
+    // Replaces all of the menu item's modifiers
+    pub modifiers: Option<Vec<MenuItemModifier>>
 }
\ No newline at end of file
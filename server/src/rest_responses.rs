@@ -1,12 +1,32 @@
 use rocket::serde::{ Deserialize, Serialize };
 
+use crate::order_status::OrderStatus;
+
 #[derive(Debug)]
+#[derive(Clone)]
 #[derive(Deserialize)]
 #[derive(Serialize)]
 #[serde(crate = "rocket::serde")]
 pub struct MenuItem {
     pub id: u32,
-    pub name: String
+    pub name: String,
+    pub min_minutes_to_cook: u32,
+    pub max_minutes_to_cook: u32,
+    pub price: u32,
+    pub retired: bool,
+    pub modifiers: Vec<Modifier>
+}
+
+// On a menu item, a modifier which can be ordered. On an order, a modifier which was ordered,
+// with the price delta it was ordered at.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Deserialize)]
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Modifier {
+    pub name: String,
+    pub price_delta: u32
 }
 
 #[derive(Debug)]
@@ -17,12 +37,35 @@ pub struct MenuItems {
     pub menu_items: Vec<MenuItem>
 }
 
+#[derive(Debug)]
+#[derive(Deserialize)]
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Tables {
+    pub tables: Vec<Table>
+}
+
+// order_count is the number of orders which have not been cancelled or checked out
+#[derive(Debug)]
+#[derive(Deserialize)]
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Table {
+    pub number: u32,
+    pub capacity: u32,
+    pub section: Option<String>,
+    pub open: bool,
+    pub order_count: u32
+}
+
 #[derive(Debug)]
 #[derive(Deserialize)]
 #[derive(Serialize)]
 #[serde(crate = "rocket::serde")]
 pub struct Orders {
-    pub orders: Vec<Order>
+    pub orders: Vec<Order>,
+    // Set when a limit was given and there are more orders; passed as the cursor to get the next page
+    pub next_cursor: Option<String>
 }
 
 #[derive(Debug)]
@@ -31,7 +74,95 @@ pub struct Orders {
 #[serde(crate = "rocket::serde")]
 pub struct Order {
     pub id: u32,
+    pub table_number: u32,
     pub menu_item_id: u32,
     pub menu_item_name: String,
-    pub minutes_to_cook: u32
+    pub minutes_to_cook: u32,
+    pub status: OrderStatus,
+    pub status_times: StatusTimes,
+    pub created_at: u64,
+    pub minutes_remaining: u32,
+    pub ready_at: u64,
+    pub price: u32,
+    pub seat: Option<u32>,
+    pub modifiers: Vec<Modifier>,
+    pub note: Option<String>
+}
+
+// Unix timestamps (in seconds) of when an order entered each status.
+// Statuses which the order has not (yet) passed through are null.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Deserialize)]
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct StatusTimes {
+    pub placed_at: Option<u64>,
+    pub cooking_at: Option<u64>,
+    pub ready_at: Option<u64>,
+    pub served_at: Option<u64>,
+    pub cancelled_at: Option<u64>
+}
+
+#[derive(Debug)]
+#[derive(Deserialize)]
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct IdempotencyKeys {
+    pub count: u64,
+    // Unix timestamp (in seconds) of when the oldest key was stored, or null if there are none
+    pub oldest_created_at: Option<u64>
+}
+
+// All amounts are in minor units of the currency
+#[derive(Debug)]
+#[derive(Deserialize)]
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Bill {
+    pub table_number: u32,
+    pub line_items: Vec<BillLineItem>,
+    pub subtotal: u64,
+    pub tax: u64,
+    pub total: u64
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Deserialize)]
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct BillLineItem {
+    pub order_id: u32,
+    pub menu_item_id: u32,
+    pub menu_item_name: String,
+    pub price: u32,
+    pub seat: Option<u32>
+}
+
+// A bill divided into sub-bills which add up exactly to the bill's subtotal, tax and total.
+// All amounts are in minor units of the currency.
+#[derive(Debug)]
+#[derive(Deserialize)]
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct SplitBill {
+    pub table_number: u32,
+    pub bills: Vec<SubBill>,
+    pub subtotal: u64,
+    pub tax: u64,
+    pub total: u64
+}
+
+// Sub-bills of an even split have no line items; seat is only set when splitting by seat
+#[derive(Debug)]
+#[derive(Deserialize)]
+#[derive(Serialize)]
+#[serde(crate = "rocket::serde")]
+pub struct SubBill {
+    pub seat: Option<u32>,
+    pub line_items: Vec<BillLineItem>,
+    pub subtotal: u64,
+    pub tax: u64,
+    pub total: u64
 }
\ No newline at end of file
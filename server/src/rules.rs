@@ -0,0 +1,132 @@
+use crate::errors::server_error::ServerError;
+use crate::order_status::OrderStatus;
+use crate::settings::CookTimeRange;
+use crate::{ rest_responses, rest_bodies };
+
+// The rules which every repository applies in the same way, whatever it stores its data in
+
+// Tax added on top of the subtotal of a bill
+pub const TAX_RATE_PERCENT: u64 = 10;
+
+// The longest note, in characters, which can be left on an order
+pub const MAX_NOTE_LENGTH: usize = 200;
+
+pub fn validate_table(capacity: u32) -> Result<(), ServerError> {
+    if capacity == 0 {
+        return Err(ServerError::InvalidTable("Tables must have a capacity of at least 1.".to_string()));
+    }
+    Result::Ok(())
+}
+
+// Sections are trimmed, and empty sections are treated as no section at all
+pub fn normalize_section(section: Option<&str>) -> Option<String> {
+    section.map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+pub fn validate_menu_item(name: &str, min_minutes_to_cook: u32, max_minutes_to_cook: u32, price: u32, modifiers: &[rest_responses::Modifier]) -> Result<(), ServerError> {
+    if name.trim().is_empty() {
+        return Err(ServerError::InvalidMenuItem("Menu item names cannot be empty.".to_string()));
+    }
+    if min_minutes_to_cook > max_minutes_to_cook {
+        return Err(ServerError::InvalidMenuItem("min_minutes_to_cook cannot be greater than max_minutes_to_cook.".to_string()));
+    }
+    if modifiers.iter().any(|m| m.name.trim().is_empty()) {
+        return Err(ServerError::InvalidMenuItem("Modifier names cannot be empty.".to_string()));
+    }
+    if modifiers.iter().enumerate().any(|(i, m)| modifiers[..i].iter().any(|other| other.name == m.name)) {
+        return Err(ServerError::InvalidMenuItem("A menu item cannot have two modifiers with the same name.".to_string()));
+    }
+    // so that the item can be ordered with any of its modifiers
+    if price_with_modifiers(price, modifiers).is_err() {
+        return Err(ServerError::InvalidMenuItem(format!("The price with every modifier cannot be more than {}.", u32::MAX)));
+    }
+    Result::Ok(())
+}
+
+// Returns InvalidMenuItem if a cooking time falls outside of the configured range
+pub fn validate_cook_time(range: &CookTimeRange, minutes_to_cook: u32) -> Result<(), ServerError> {
+    if minutes_to_cook < range.min_minutes || minutes_to_cook > range.max_minutes {
+        return Err(ServerError::InvalidMenuItem(format!(
+            "Cooking times must be between {} and {} minutes.", range.min_minutes, range.max_minutes)));
+    }
+    Result::Ok(())
+}
+
+// Modifiers which are left without a price delta are free
+pub fn modifiers_from_body(modifiers: Vec<rest_bodies::MenuItemModifier>) -> Vec<rest_responses::Modifier> {
+    modifiers.into_iter()
+        .map(|m| rest_responses::Modifier {
+            name: m.name,
+            price_delta: m.price_delta.unwrap_or(0)
+        })
+        .collect()
+}
+
+// Looks up each of the named modifiers on the menu item. Returns InvalidOrder if the menu item
+// does not offer one of them, or if one is given more than once.
+pub fn choose_modifiers(menu_item: &rest_responses::MenuItem, names: &[String]) -> Result<Vec<rest_responses::Modifier>, ServerError> {
+    let mut chosen: Vec<rest_responses::Modifier> = Vec::new();
+    for name in names {
+        if chosen.iter().any(|m| m.name == *name) {
+            return Err(ServerError::InvalidOrder(format!("The modifier '{name}' was given more than once.")));
+        }
+        match menu_item.modifiers.iter().find(|m| m.name == *name) {
+            Some(modifier) => chosen.push(modifier.clone()),
+            None => return Err(ServerError::InvalidOrder(format!("'{name}' is not a modifier of {}.", menu_item.name)))
+        }
+    }
+    Result::Ok(chosen)
+}
+
+// Prices and price deltas are chosen by clients, so their sum is checked rather than allowed to overflow
+pub fn price_with_modifiers(price: u32, modifiers: &[rest_responses::Modifier]) -> Result<u32, ServerError> {
+    modifiers.iter()
+        .try_fold(price, |total, m| total.checked_add(m.price_delta))
+        .ok_or_else(|| ServerError::InvalidOrder(format!("The price with these modifiers cannot be more than {}.", u32::MAX)))
+}
+
+// The price an order's menu item was ordered at, without its modifiers
+pub fn base_price(order: &rest_responses::Order) -> Result<u32, ServerError> {
+    order.modifiers.iter()
+        .try_fold(order.price, |total, m| total.checked_sub(m.price_delta))
+        .ok_or_else(|| ServerError::InvalidOrder("The order's price is less than the price of its modifiers.".to_string()))
+}
+
+// Notes are trimmed, and empty notes are treated as no note at all
+pub fn validate_note(note: Option<&str>) -> Result<Option<String>, ServerError> {
+    match note.map(str::trim) {
+        Some(note) if note.chars().count() > MAX_NOTE_LENGTH =>
+            Err(ServerError::InvalidOrder(format!("Notes cannot be longer than {MAX_NOTE_LENGTH} characters."))),
+        Some(note) if !note.is_empty() => Result::Ok(Some(note.to_string())),
+        _ => Result::Ok(None)
+    }
+}
+
+// When an order will be ready, and how many minutes are left until then as of `now`
+pub fn order_timing(status: OrderStatus, created_at: u64, minutes_to_cook: u32, now: u64) -> (u64, u32) {
+    let ready_at = created_at + u64::from(minutes_to_cook) * 60;
+
+    // once the kitchen has finished with an order there is nothing left to count down
+    let minutes_remaining = match status {
+        OrderStatus::Placed | OrderStatus::Cooking => ready_at.saturating_sub(now).div_ceil(60) as u32,
+        _ => 0
+    };
+    (ready_at, minutes_remaining)
+}
+
+// Totals the line items of a bill, which should not include cancelled orders
+pub fn bill(table_number: u32, line_items: Vec<rest_responses::BillLineItem>) -> rest_responses::Bill {
+    let subtotal = line_items.iter().map(|i| u64::from(i.price)).sum::<u64>();
+    // tax is rounded to the nearest minor unit, with halves rounded up
+    let tax = (subtotal * TAX_RATE_PERCENT + 50) / 100;
+
+    rest_responses::Bill {
+        table_number,
+        line_items,
+        subtotal,
+        tax,
+        total: subtotal + tax
+    }
+}
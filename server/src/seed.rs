@@ -0,0 +1,90 @@
+use std::fs;
+use std::path::Path;
+use rocket::serde::Deserialize;
+use rocket::serde::json::from_str;
+
+use crate::errors::server_error::ServerError;
+use crate::rules::{ modifiers_from_body, validate_cook_time, validate_menu_item };
+use crate::settings::CookTimeRange;
+use crate::{ rest_responses, rest_bodies };
+
+// A menu item which a new database is set up with
+pub struct SeedMenuItem {
+    pub name: &'static str,
+    pub min_minutes_to_cook: u32,
+    pub max_minutes_to_cook: u32,
+    pub price: u32,
+    // Names and price deltas
+    pub modifiers: &'static [(&'static str, u32)]
+}
+
+// The menu every repository starts with when it has no menu items yet, unless a seed file is configured
+pub const DEFAULT_MENU: &[SeedMenuItem] = &[
+    SeedMenuItem { name: "Hamburger", min_minutes_to_cook: 10, max_minutes_to_cook: 15, price: 900, modifiers: &[("no onions", 0), ("extra cheese", 100)] },
+    SeedMenuItem { name: "Salad", min_minutes_to_cook: 5, max_minutes_to_cook: 8, price: 650, modifiers: &[("dressing on the side", 0)] },
+    SeedMenuItem { name: "Sushi", min_minutes_to_cook: 8, max_minutes_to_cook: 12, price: 1200, modifiers: &[] },
+    SeedMenuItem { name: "Ice Cream", min_minutes_to_cook: 2, max_minutes_to_cook: 4, price: 400, modifiers: &[("extra scoop", 150)] },
+    SeedMenuItem { name: "Soda", min_minutes_to_cook: 1, max_minutes_to_cook: 2, price: 250, modifiers: &[("no ice", 0)] }
+];
+
+// The menu items a repository is set up with when it has no menu items yet.
+// A seed file is JSON with the menu items in the same form as the body of POST /menu-items, i.e.
+// { "menu_items": [{ "name": "Soda", "min_minutes_to_cook": 1, "max_minutes_to_cook": 2, "price": 250 }] }
+#[derive(Debug)]
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct Seed {
+    pub menu_items: Vec<rest_bodies::MenuItem>
+}
+
+impl Default for Seed {
+    fn default() -> Self {
+        Seed {
+            menu_items: DEFAULT_MENU.iter()
+                .map(|item| rest_bodies::MenuItem {
+                    name: item.name.to_string(),
+                    min_minutes_to_cook: item.min_minutes_to_cook,
+                    max_minutes_to_cook: item.max_minutes_to_cook,
+                    price: item.price,
+                    modifiers: Some(item.modifiers.iter()
+                        .map(|(name, price_delta)| rest_bodies::MenuItemModifier { name: name.to_string(), price_delta: Some(*price_delta) })
+                        .collect())
+                })
+                .collect()
+        }
+    }
+}
+
+impl Seed {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Seed, String> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read the seed file {}: {e}", path.display()))?;
+        from_str(&contents)
+            .map_err(|e| format!("Failed to parse the seed file {}: {e}", path.display()))
+    }
+
+    // Checks every menu item as if it was being added with POST /menu-items, so that a repository is never set up
+    // with a menu which could not have been created through the API
+    pub fn validate(&self, cook_time_range: &CookTimeRange) -> Result<(), String> {
+        for (i, (item, modifiers)) in self.items().enumerate() {
+            validate_menu_item(&item.name, item.min_minutes_to_cook, item.max_minutes_to_cook, item.price, &modifiers)
+                .and_then(|_| validate_cook_time(cook_time_range, item.min_minutes_to_cook))
+                .and_then(|_| validate_cook_time(cook_time_range, item.max_minutes_to_cook))
+                .map_err(|e| match e {
+                    ServerError::InvalidMenuItem(message) => format!("Invalid seed menu item '{}': {message}", item.name),
+                    e => e.to_string()
+                })?;
+            if self.menu_items[..i].iter().any(|other| other.name == item.name) {
+                return Err(format!("The seed menu has more than one item named '{}'.", item.name));
+            }
+        }
+        Result::Ok(())
+    }
+
+    // Each menu item along with its modifiers, in the order they are added
+    pub fn items(&self) -> impl Iterator<Item = (&rest_bodies::MenuItem, Vec<rest_responses::Modifier>)> {
+        self.menu_items.iter()
+            .map(|item| (item, modifiers_from_body(item.modifiers.clone().unwrap_or_default())))
+    }
+}
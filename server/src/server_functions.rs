@@ -1,28 +1,58 @@
-use rusqlite::{ params_from_iter, Error, ErrorCode, Result };
-use rand::Rng;
+use rocket::serde::json::{ from_str, to_string };
+use rusqlite::{ Connection, Error, ErrorCode, OptionalExtension, Result, Row, ToSql, Transaction, TransactionBehavior };
+use rusqlite::types::{ FromSql, FromSqlError, FromSqlResult, Type, ValueRef };
 
 use crate::errors::server_error::ServerError;
-use crate::database_connector::DatabaseConnector;
+use crate::database_pool::DatabasePool;
+use crate::clock::Clock;
+use crate::random_number_generator::RandomNumberGenerator;
+use crate::order_status::OrderStatus;
+use crate::settings::Settings;
+use crate::bill_split::{ self, BillSplit };
+use crate::order_query::{ OrderQuery, OrderSort, TableFilter };
+use crate::order_events::{ OrderEvent, OrderEventKind };
+use crate::rules::{ self, choose_modifiers, modifiers_from_body, normalize_section, price_with_modifiers, validate_menu_item, validate_note, validate_table };
+use crate::seed::Seed;
+use crate::migrations::{ self, Migration, SQLITE_MIGRATIONS };
 use crate::{ rest_responses, rest_bodies };
 
-pub fn setup_database(connector: &dyn DatabaseConnector) -> Result<(), ServerError> {
-    let connection = connector.open().sql_err()?;
-
-    // Table setup
-    connection.execute("
-        CREATE TABLE IF NOT EXISTS menu_items (
-            id INTEGER PRIMARY KEY,
-            name TEXT UNIQUE);", ()).sql_err()?;
-    connection.execute("
-        CREATE TABLE IF NOT EXISTS orders (
-            id INTEGER PRIMARY KEY,
-            menu_item_id INTEGER,
-            table_number INTEGER,
-            minutes_to_cook INTEGER,
-            FOREIGN KEY(menu_item_id) REFERENCES menu_items(id));", ()).sql_err()?;
-    connection.execute("
-        CREATE TABLE IF NOT EXISTS idempotent_requests (
-            idempotency_key TEXT PRIMARY KEY);", ()).sql_err()?;
+// Records the migrations which have been applied to the database, one row per migration
+const SCHEMA_VERSION_TABLE: &str = "
+    CREATE TABLE IF NOT EXISTS schema_version (
+        version INTEGER PRIMARY KEY,
+        description TEXT NOT NULL,
+        applied_at INTEGER NOT NULL);";
+
+// Columns selected by every query that returns menu items. Rows are converted with menu_item_from_row.
+// The menu item's modifiers are selected as a JSON array, in the order they were added.
+const MENU_ITEM_SELECT: &str = "SELECT id, name, min_minutes_to_cook, max_minutes_to_cook, price, retired,
+        (SELECT json_group_array(json_object('name', mm.name, 'price_delta', mm.price_delta) ORDER BY mm.rowid)
+            FROM menu_item_modifiers AS mm
+            WHERE mm.menu_item_id = menu_items.id)
+    FROM menu_items";
+
+// Columns selected by every query that returns tables. Rows are converted with table_from_row.
+const TABLE_SELECT: &str = "SELECT t.number, t.capacity, t.section, t.open,
+        (SELECT COUNT(*) FROM orders AS o
+            WHERE o.table_number = t.number
+            AND o.checked_out_at IS NULL
+            AND o.status != 'cancelled')
+    FROM tables AS t";
+
+// Columns selected by every query that returns orders. Rows are converted with order_from_row.
+// Orders which have been checked out are closed, so only open orders are selected;
+// further conditions should be added with AND.
+const ORDER_SELECT: &str = "SELECT o.id, o.minutes_to_cook, m.id, m.name,
+        o.status, o.placed_at, o.cooking_at, o.ready_at, o.served_at, o.cancelled_at,
+        o.created_at, o.price, o.seat, o.modifiers, o.note, o.table_number
+    FROM orders AS o
+    INNER JOIN menu_items AS m ON m.id = o.menu_item_id
+    WHERE o.checked_out_at IS NULL";
+
+// Migrates the database to the latest schema, and adds the seed's menu if there are no menu items yet
+pub fn setup_database(pool: &DatabasePool, seed: &Seed) -> Result<(), ServerError> {
+    migrate(pool, false)?;
+    let connection = pool.open()?;
 
     // Data setup
     let menu_items_exist_query = "SELECT * FROM menu_items;";
@@ -30,26 +60,77 @@ pub fn setup_database(connector: &dyn DatabaseConnector) -> Result<(), ServerErr
     let menu_items_exist = stmt.exists([]).sql_err()?;
 
     if !menu_items_exist {
-        let data_sql = "
-            INSERT INTO menu_items (name) VALUES
-                ('Hamburger'), ('Salad'), ('Sushi'), ('Ice Cream'), ('Soda');";
-
-        connection.execute(data_sql, ()).sql_err()?;
+        for (item, modifiers) in seed.items() {
+            connection.execute(
+                "INSERT INTO menu_items (name, min_minutes_to_cook, max_minutes_to_cook, price)
+                    VALUES (:name, :min_minutes_to_cook, :max_minutes_to_cook, :price)",
+                &[
+                    (":name", &item.name as &dyn ToSql),
+                    (":min_minutes_to_cook", &item.min_minutes_to_cook),
+                    (":max_minutes_to_cook", &item.max_minutes_to_cook),
+                    (":price", &item.price)]).sql_err()?;
+            set_menu_item_modifiers(&connection, connection.last_insert_rowid(), &modifiers)?;
+        }
     }
 
     Result::Ok(())
 }
 
-pub fn get_menu_items(connector: &dyn DatabaseConnector) -> Result<rest_responses::MenuItems, ServerError> {
-    let connection = connector.open().sql_err()?;
-    let query = "SELECT id, name FROM menu_items";
-    let mut stmt = connection.prepare(query).sql_err()?;
+// Applies the migrations which the database has not applied yet, in a single transaction, returning them.
+// In a dry run the transaction is rolled back rather than committed, so the migrations are checked against
+// the database without changing it.
+pub fn migrate(pool: &DatabasePool, dry_run: bool) -> Result<Vec<&'static Migration>, ServerError> {
+    let mut connection = pool.open()?;
+    // the write lock is taken before the version is read, so that servers starting at the same time apply each migration once
+    let transaction = write_transaction(&mut connection)?;
+    transaction.execute_batch(SCHEMA_VERSION_TABLE).sql_err()?;
+    let pending = migrations::pending(SQLITE_MIGRATIONS, query_schema_version(&transaction)?)?;
+
+    for migration in &pending {
+        transaction.execute_batch(migration.sql).sql_err()?;
+        transaction.execute(
+            "INSERT INTO schema_version (version, description, applied_at)
+                VALUES (:version, :description, strftime('%s', 'now'))",
+            &[
+                (":version", &migration.version as &dyn ToSql),
+                (":description", &migration.description)]).sql_err()?;
+    }
+
+    match dry_run {
+        true => transaction.rollback().sql_err()?,
+        false => transaction.commit().sql_err()?
+    };
+    Result::Ok(pending)
+}
+
+// The version of the latest migration which has been applied, or 0 if there are none
+pub fn get_schema_version(pool: &DatabasePool) -> Result<u32, ServerError> {
+    let connection = pool.open()?;
+    query_schema_version(&connection)
+}
+
+// Databases created before migrations were introduced have no schema_version table, and are at version 0
+fn query_schema_version(connection: &Connection) -> Result<u32, ServerError> {
+    let mut stmt = connection.prepare("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'schema_version'").sql_err()?;
+    if !stmt.exists([]).sql_err()? {
+        return Result::Ok(0);
+    }
+    connection.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_version",
+        [],
+        |row| row.get(0)).sql_err()
+}
+
+pub fn get_menu_items(pool: &DatabasePool, include_retired: bool) -> Result<rest_responses::MenuItems, ServerError> {
+    let connection = pool.open()?;
+    let query = match include_retired {
+        true => MENU_ITEM_SELECT.to_string(),
+        false => format!("{MENU_ITEM_SELECT} WHERE retired = 0")
+    };
+    let mut stmt = connection.prepare(&query).sql_err()?;
     let query_result = stmt.query_map(
         [],
-        |row| Result::Ok(rest_responses::MenuItem {
-            id: row.get(0)?,
-            name: row.get(1)?
-        })).sql_err()?;
+        menu_item_from_row).sql_err()?;
 
     let mut items = Vec::new();
     for item in query_result {
@@ -63,147 +144,1031 @@ pub fn get_menu_items(connector: &dyn DatabaseConnector) -> Result<rest_response
     )
 }
 
-pub fn add_orders(connector: &dyn DatabaseConnector, table_number: u32, orders: rest_bodies::Orders) -> Result<rest_responses::Orders, ServerError> {
-    let mut connection = connector.open().sql_err()?;
-    let transaction = connection.transaction().sql_err()?;
+pub fn add_menu_item(pool: &DatabasePool, menu_item: rest_bodies::MenuItem) -> Result<rest_responses::MenuItem, ServerError> {
+    let modifiers = menu_item.modifiers.map(modifiers_from_body).unwrap_or_default();
+    validate_menu_item(&menu_item.name, menu_item.min_minutes_to_cook, menu_item.max_minutes_to_cook, menu_item.price, &modifiers)?;
 
-    // if an idempotency key was required, check that first. On conflict, an Err will be returned.
-    if let Some(key) = orders.idempotency_key {
-        transaction.execute(
-            "INSERT INTO idempotent_requests (idempotency_key) VALUES (:key)",
-            &[(":key", &key)])
-            .map_err(|e| match e {
-                Error::SqliteFailure(err, _) if err.code == ErrorCode::ConstraintViolation =>
-                    ServerError::Idempotency,
-                x => ServerError::SqlError(x)
-            })?;
+    let mut connection = pool.open()?;
+    let transaction = write_transaction(&mut connection)?;
+    transaction.execute(
+        "INSERT INTO menu_items (name, min_minutes_to_cook, max_minutes_to_cook, price)
+            VALUES (:name, :min_minutes_to_cook, :max_minutes_to_cook, :price)",
+        &[
+            (":name", &menu_item.name),
+            (":min_minutes_to_cook", &menu_item.min_minutes_to_cook.to_string()),
+            (":max_minutes_to_cook", &menu_item.max_minutes_to_cook.to_string()),
+            (":price", &menu_item.price.to_string())])
+        .map_err(menu_item_name_err)?;
+
+    let menu_item_id = transaction.last_insert_rowid();
+    set_menu_item_modifiers(&transaction, menu_item_id, &modifiers)?;
+
+    let added = get_menu_item(&transaction, menu_item_id)?;
+    transaction.commit().sql_err()?;
+    Result::Ok(added)
+}
+
+pub fn update_menu_item(pool: &DatabasePool, menu_item_id: u32, update: rest_bodies::MenuItemUpdate) -> Result<rest_responses::MenuItem, ServerError> {
+    let mut connection = pool.open()?;
+    let transaction = write_transaction(&mut connection)?;
+
+    // fields which are not provided keep their current values, which the new ones are validated against
+    let current = get_menu_item(&transaction, i64::from(menu_item_id))?;
+    let name = update.name.unwrap_or(current.name);
+    let min_minutes_to_cook = update.min_minutes_to_cook.unwrap_or(current.min_minutes_to_cook);
+    let max_minutes_to_cook = update.max_minutes_to_cook.unwrap_or(current.max_minutes_to_cook);
+    let price = update.price.unwrap_or(current.price);
+    let modifiers = update.modifiers.map(modifiers_from_body);
+    validate_menu_item(&name, min_minutes_to_cook, max_minutes_to_cook, price, modifiers.as_ref().unwrap_or(&current.modifiers))?;
+
+    transaction.execute(
+        "UPDATE menu_items
+            SET name = :name,
+                min_minutes_to_cook = :min_minutes_to_cook,
+                max_minutes_to_cook = :max_minutes_to_cook,
+                price = :price
+            WHERE id = :menu_item_id",
+        &[
+            (":name", &name),
+            (":min_minutes_to_cook", &min_minutes_to_cook.to_string()),
+            (":max_minutes_to_cook", &max_minutes_to_cook.to_string()),
+            (":price", &price.to_string()),
+            (":menu_item_id", &menu_item_id.to_string())])
+        .map_err(menu_item_name_err)?;
+
+    // orders which have already been made keep the modifiers they were made with
+    if let Some(modifiers) = modifiers {
+        set_menu_item_modifiers(&transaction, i64::from(menu_item_id), &modifiers)?;
+    }
+
+    let updated = get_menu_item(&transaction, i64::from(menu_item_id))?;
+    transaction.commit().sql_err()?;
+    Result::Ok(updated)
+}
+
+// Menu items are never removed from the database, as existing orders still reference them.
+// Retired items are hidden from the menu and can no longer be ordered.
+pub fn retire_menu_item(pool: &DatabasePool, menu_item_id: u32) -> Result<(), ServerError> {
+    let connection = pool.open()?;
+    let rows = connection.execute(
+        "UPDATE menu_items SET retired = 1 WHERE id = :menu_item_id",
+        &[(":menu_item_id", &menu_item_id.to_string())]).sql_err()?;
+
+    match rows {
+        0 => Err(ServerError::DataNotFound),
+        _ => Result::Ok(())
     }
+}
+
+pub fn get_tables(pool: &DatabasePool) -> Result<rest_responses::Tables, ServerError> {
+    let connection = pool.open()?;
+    let mut stmt = connection.prepare(&format!("{TABLE_SELECT} ORDER BY t.number")).sql_err()?;
+    let query_result = stmt.query_map(
+        [],
+        table_from_row).sql_err()?;
+
+    let mut tables = Vec::new();
+    for table in query_result {
+        tables.push(table.sql_err()?);
+    }
+
+    Result::Ok(
+        rest_responses::Tables {
+            tables
+        }
+    )
+}
 
-    let mut ids = Vec::new();
+pub fn get_table(pool: &DatabasePool, table_number: u32) -> Result<rest_responses::Table, ServerError> {
+    let connection = pool.open()?;
+    query_table(&connection, table_number)
+}
+
+pub fn add_table(pool: &DatabasePool, table: rest_bodies::Table) -> Result<rest_responses::Table, ServerError> {
+    validate_table(table.capacity)?;
+
+    let connection = pool.open()?;
+    connection.execute(
+        "INSERT INTO tables (number, capacity, section, open)
+            VALUES (:number, :capacity, :section, :open)",
+        &[
+            (":number", &table.number.to_string() as &dyn ToSql),
+            (":capacity", &table.capacity.to_string()),
+            (":section", &normalize_section(table.section.as_deref())),
+            (":open", &table.open.unwrap_or(true))])
+        .map_err(|e| match e {
+            Error::SqliteFailure(err, _) if err.code == ErrorCode::ConstraintViolation =>
+                ServerError::DuplicateTable,
+            x => ServerError::SqlError(x)
+        })?;
+
+    query_table(&connection, table.number)
+}
+
+pub fn update_table(pool: &DatabasePool, table_number: u32, update: rest_bodies::TableUpdate) -> Result<rest_responses::Table, ServerError> {
+    let mut connection = pool.open()?;
+    let transaction = write_transaction(&mut connection)?;
+
+    // fields which are not provided keep their current values
+    let current = query_table(&transaction, table_number)?;
+    let capacity = update.capacity.unwrap_or(current.capacity);
+    let section = match update.section {
+        Some(section) => normalize_section(Some(&section)),
+        None => current.section
+    };
+    let open = update.open.unwrap_or(current.open);
+    validate_table(capacity)?;
+
+    transaction.execute(
+        "UPDATE tables
+            SET capacity = :capacity,
+                section = :section,
+                open = :open
+            WHERE number = :number",
+        &[
+            (":capacity", &capacity.to_string() as &dyn ToSql),
+            (":section", &section),
+            (":open", &open),
+            (":number", &table_number.to_string())]).sql_err()?;
+
+    let updated = query_table(&transaction, table_number)?;
+    transaction.commit().sql_err()?;
+    Result::Ok(updated)
+}
+
+// Tables can only be removed once all of their orders have been checked out or deleted.
+// Returns DataNotFound if the table has not been registered.
+pub fn delete_table(pool: &DatabasePool, table_number: u32) -> Result<(), ServerError> {
+    let mut connection = pool.open()?;
+    let transaction = write_transaction(&mut connection)?;
+
+    let table = query_table(&transaction, table_number)?;
+    let mut stmt = transaction.prepare(
+        "SELECT 1 FROM orders
+            WHERE table_number = :table_number
+            AND checked_out_at IS NULL").sql_err()?;
+    if stmt.exists(&[(":table_number", &table.number.to_string())]).sql_err()? {
+        return Err(ServerError::TableHasOpenOrders);
+    }
+    drop(stmt);
+
+    transaction.execute(
+        "DELETE FROM tables WHERE number = :number",
+        &[(":number", &table.number.to_string())]).sql_err()?;
+
+    transaction.commit().sql_err()?;
+    Result::Ok(())
+}
+
+pub fn add_orders(pool: &DatabasePool, clock: &dyn Clock, rng: &dyn RandomNumberGenerator, settings: &Settings, table_number: u32, orders: rest_bodies::Orders) -> Result<rest_responses::Orders, ServerError> {
+    let mut connection = pool.open()?;
+    let transaction = write_transaction(&mut connection)?;
+
+    check_table_accepts_orders(&transaction, settings, table_number)?;
+
+    let mut items = Vec::new();
+    let now = clock.now();
 
     // add orders one at a time
     for order in &orders.orders {
-        let cook_time = rand::thread_rng().gen_range(5..15);
-        let rows = transaction.execute(
-            "INSERT INTO orders (menu_item_id, table_number, minutes_to_cook)
-            SELECT
-                m.id,
-                :table_number,
-                :cook_time
-            FROM menu_items AS m WHERE m.id = :menu_item_id",
+        // the price and modifiers are copied onto the order so that later changes to the menu do not affect open bills
+        let menu_item = get_orderable_menu_item(&transaction, order.menu_item_id)?;
+        let modifiers = choose_modifiers(&menu_item, order.modifiers.as_deref().unwrap_or_default())?;
+        let note = validate_note(order.note.as_deref())?;
+        let cook_time = rng.gen_range(menu_item.min_minutes_to_cook, menu_item.max_minutes_to_cook);
+        transaction.execute(
+            "INSERT INTO orders (menu_item_id, table_number, minutes_to_cook, price, created_at, status, placed_at, seat, modifiers, note)
+            VALUES (:menu_item_id, :table_number, :cook_time, :price, :created_at, :status, :created_at, :seat, :modifiers, :note)",
             &[
+                (":menu_item_id", &order.menu_item_id.to_string() as &dyn ToSql),
                 (":table_number", &table_number.to_string()),
                 (":cook_time", &cook_time.to_string()),
-                (":menu_item_id", &order.menu_item_id.to_string())])
+                (":price", &price_with_modifiers(menu_item.price, &modifiers)?.to_string()),
+                (":created_at", &now.to_string()),
+                (":status", &OrderStatus::Placed.as_str().to_string()),
+                // seats and notes are optional, so these are bound as nullable values rather than strings
+                (":seat", &order.seat),
+                (":modifiers", &modifiers_to_sql(&modifiers)?),
+                (":note", &note)])
             .sql_err()?;
-        
-        // if the requested menu item does not exist, no data will be added; return an error.
-        if rows == 0 {
-            return Err(ServerError::DataNotFound);
-        }
 
-        // keep track of all added ids so they can be queried after the transaction
-        ids.push(transaction.last_insert_rowid());
+        // the added order is queried in the transaction, both for the event log and for the response
+        let item = query_order(&transaction, now, table_number, transaction.last_insert_rowid() as u32)?;
+        record_order_event(&transaction, now, OrderEventKind::Created, &item, None)?;
+        items.push(item);
     };
 
     transaction.commit().sql_err()?;
 
-    // query the added items for the response
-    let query = format!("SELECT o.id, o.minutes_to_cook, m.id, m.name
-        FROM orders AS o
-        INNER JOIN menu_items AS m ON m.id = o.menu_item_id
-        WHERE o.id IN ({})",
-        (1..ids.len() + 1).map(|x| format!("?{x}")).collect::<Vec<_>>().join(","));
+    Result::Ok(
+        rest_responses::Orders {
+            orders: items,
+            next_cursor: None
+        }
+    )
+}
 
-    let mut stmt = connection.prepare(&query).sql_err()?;
-    let query_result = stmt.query_map(
-        params_from_iter(ids.iter().map(|i| i.to_string())),
-        |row| Result::Ok(rest_responses::Order {
-            id: row.get(0)?,
-            minutes_to_cook: row.get(1)?,
-            menu_item_id: row.get(2)?,
-            menu_item_name: row.get(3)?
-        })).sql_err()?;
+pub fn get_orders(pool: &DatabasePool, clock: &dyn Clock, table_number: u32, query: &OrderQuery) -> Result<rest_responses::Orders, ServerError> {
+    let connection = pool.open()?;
+    query_orders(
+        &connection,
+        clock.now(),
+        vec!["o.table_number = :table_number".to_string()],
+        vec![(":table_number".to_string(), table_number.to_string())],
+        query)
+}
+
+// Lists open orders across all of the tables matched by the filter
+pub fn get_all_orders(pool: &DatabasePool, clock: &dyn Clock, tables: &TableFilter, query: &OrderQuery) -> Result<rest_responses::Orders, ServerError> {
+    let connection = pool.open()?;
+    let mut conditions = Vec::new();
+    let mut values = Vec::new();
+    if !tables.ranges.is_empty() {
+        conditions.push(format!("({})", tables.ranges.iter().enumerate()
+            .map(|(i, _)| format!("o.table_number BETWEEN :table_from_{i} AND :table_to_{i}"))
+            .collect::<Vec<_>>()
+            .join(" OR ")));
+        for (i, range) in tables.ranges.iter().enumerate() {
+            values.push((format!(":table_from_{i}"), range.start().to_string()));
+            values.push((format!(":table_to_{i}"), range.end().to_string()));
+        }
+    }
+    query_orders(&connection, clock.now(), conditions, values, query)
+}
 
+// Lists the open orders which match the query, along with any conditions given by the caller (such as the table).
+// When the query has a limit, one extra order is selected to find out whether there is another page.
+fn query_orders(connection: &Connection, now: u64, mut conditions: Vec<String>, mut values: Vec<(String, String)>, query: &OrderQuery) -> Result<rest_responses::Orders, ServerError> {
+    in_condition(&mut conditions, &mut values, "o.menu_item_id", "menu_item_id", query.menu_item_ids.iter().map(|id| id.to_string()));
+    in_condition(&mut conditions, &mut values, "o.status", "status", query.statuses.iter().map(|status| status.as_str().to_string()));
+    if let Some(created_after) = query.created_after {
+        conditions.push("o.created_at > :created_after".to_string());
+        values.push((":created_after".to_string(), created_after.to_string()));
+    }
+    if let Some(created_before) = query.created_before {
+        conditions.push("o.created_at < :created_before".to_string());
+        values.push((":created_before".to_string(), created_before.to_string()));
+    }
+
+    // the order id breaks ties, so that every order has a unique position to resume from
+    let sort_column = match query.sort {
+        OrderSort::Id => "o.id",
+        OrderSort::MinutesToCook => "o.minutes_to_cook"
+    };
+    if let Some(cursor) = query.cursor {
+        conditions.push(match query.sort {
+            OrderSort::Id => "o.id > :cursor_id".to_string(),
+            OrderSort::MinutesToCook => "(o.minutes_to_cook, o.id) > (:cursor_sort_value, :cursor_id)".to_string()
+        });
+        if query.sort != OrderSort::Id {
+            values.push((":cursor_sort_value".to_string(), cursor.sort_value.to_string()));
+        }
+        values.push((":cursor_id".to_string(), cursor.id.to_string()));
+    }
+    let limit = match query.limit {
+        Some(limit) => {
+            values.push((":limit".to_string(), (limit + 1).to_string()));
+            "LIMIT :limit"
+        },
+        None => ""
+    };
+    let mut stmt = connection.prepare(&format!(
+        "{ORDER_SELECT}
+        {}
+        ORDER BY {sort_column}, o.id
+        {limit}",
+        conditions.iter().map(|c| format!("AND {c}")).collect::<Vec<_>>().join("\n"))).sql_err()?;
+    let params = values.iter()
+        .map(|(name, value)| (name.as_str(), value as &dyn ToSql))
+        .collect::<Vec<_>>();
+    let query_result = stmt.query_map(
+        params.as_slice(),
+        |row| order_from_row(row, now)).sql_err()?;
 
     let mut items = Vec::new();
     for item in query_result {
         items.push(item.sql_err()?);
     }
 
+    Result::Ok(query.page(items))
+}
+
+// Adds a condition that the column is one of the listed values, which are bound as :<name>_0, :<name>_1 and so on.
+// An empty list adds no condition.
+fn in_condition(conditions: &mut Vec<String>, values: &mut Vec<(String, String)>, column: &str, name: &str, items: impl Iterator<Item = String>) {
+    let mut names = Vec::new();
+    for (i, item) in items.enumerate() {
+        names.push(format!(":{name}_{i}"));
+        values.push((format!(":{name}_{i}"), item));
+    }
+    if !names.is_empty() {
+        conditions.push(format!("{column} IN ({})", names.join(", ")));
+    }
+}
+
+pub fn get_order(pool: &DatabasePool, clock: &dyn Clock, table_number: u32, order_id: u32) -> Result<rest_responses::Order, ServerError> {
+    let connection = pool.open()?;
+    query_order(&connection, clock.now(), table_number, order_id)
+}
+
+// Returns DataNotFound if the table has no open order with the given id
+fn query_order(connection: &Connection, now: u64, table_number: u32, order_id: u32) -> Result<rest_responses::Order, ServerError> {
+    let mut stmt = connection.prepare(&format!(
+        "{ORDER_SELECT}
+        AND o.id = :order_id
+        AND o.table_number = :table_number")).sql_err()?;
+    let query_result = stmt.query_row(
+        &[
+            (":order_id", &order_id.to_string()),
+            (":table_number", &table_number.to_string())],
+        |row| order_from_row(row, now))
+        .map_err(|e| match e {
+            Error::QueryReturnedNoRows => ServerError::DataNotFound,
+            x => ServerError::SqlError(x)
+        })?;
+
+    Result::Ok(query_result)
+}
+
+// Returns DataNotFound if the order does not exist, belongs to another table or has been checked out
+pub fn delete_order(pool: &DatabasePool, clock: &dyn Clock, table_number: u32, order_id: u32) -> Result<(), ServerError> {
+    let mut connection = pool.open()?;
+    let transaction = write_transaction(&mut connection)?;
+    let now = clock.now();
+
+    let item = query_order(&transaction, now, table_number, order_id)?;
+    record_order_event(&transaction, now, OrderEventKind::Deleted, &item, None)?;
+    transaction.execute(
+        "DELETE FROM orders WHERE id = :order_id",
+        &[(":order_id", &order_id.to_string())]).sql_err()?;
+
+    transaction.commit().sql_err()?;
+    Result::Ok(())
+}
+
+// Deletes the open orders of a table in a single transaction, returning the deleted orders.
+// When ids are given, only those orders are deleted, and DataNotFound is returned (with nothing deleted)
+// if any of them is not an open order of the table.
+pub fn delete_orders(pool: &DatabasePool, clock: &dyn Clock, table_number: u32, ids: Option<&[u32]>) -> Result<rest_responses::Orders, ServerError> {
+    let mut connection = pool.open()?;
+    let transaction = write_transaction(&mut connection)?;
+    let now = clock.now();
+
+    let mut items = Vec::new();
+    {
+        let mut stmt = transaction.prepare(&format!(
+            "{ORDER_SELECT}
+            AND o.table_number = :table_number
+            ORDER BY o.id")).sql_err()?;
+        let query_result = stmt.query_map(
+            &[(":table_number", &table_number.to_string())],
+            |row| order_from_row(row, now)).sql_err()?;
+
+        for item in query_result {
+            let item = item.sql_err()?;
+            if ids.is_none_or(|ids| ids.contains(&item.id)) {
+                items.push(item);
+            }
+        }
+    }
+
+    if let Some(ids) = ids {
+        if ids.iter().any(|id| !items.iter().any(|i| i.id == *id)) {
+            return Err(ServerError::DataNotFound);
+        }
+    }
+
+    for item in &items {
+        record_order_event(&transaction, now, OrderEventKind::Deleted, item, None)?;
+        transaction.execute(
+            "DELETE FROM orders WHERE id = :order_id",
+            &[(":order_id", &item.id.to_string())]).sql_err()?;
+    }
+
+    transaction.commit().sql_err()?;
+
     Result::Ok(
         rest_responses::Orders {
-            orders: items
+            orders: items,
+            next_cursor: None
         }
     )
 }
 
-pub fn get_orders(connector: &dyn DatabaseConnector, table_number: u32) -> Result<rest_responses::Orders, ServerError> {
-    let connection = connector.open().sql_err()?;
-    let mut stmt = connection.prepare(
-        "SELECT o.id, o.minutes_to_cook, m.id, m.name
-        FROM orders AS o
-        INNER JOIN menu_items AS m ON m.id = o.menu_item_id
-        WHERE o.table_number = :table_number").sql_err()?;
+pub fn update_order(pool: &DatabasePool, clock: &dyn Clock, rng: &dyn RandomNumberGenerator, settings: &Settings, table_number: u32, order_id: u32, update: rest_bodies::OrderUpdate) -> Result<rest_responses::Order, ServerError> {
+    let mut connection = pool.open()?;
+    let transaction = write_transaction(&mut connection)?;
+
+    if let Some(new_table_number) = update.table_number {
+        check_table_accepts_orders(&transaction, settings, new_table_number)?;
+    }
+
+    let current = query_order(&transaction, clock.now(), table_number, order_id)?;
+    if current.status.is_final() {
+        return Err(ServerError::OrderFinal(current.status));
+    }
+
+    // a different menu item will take a different amount of time to cook, unless the kitchen has provided its own estimate,
+    // and is charged at the new menu item's current price
+    let mut minutes_to_cook = update.minutes_to_cook;
+    let mut price = None;
+    let mut modifiers = None;
+    if update.menu_item_id.is_some() || update.modifiers.is_some() {
+        let menu_item = get_orderable_menu_item(&transaction, update.menu_item_id.unwrap_or(current.menu_item_id))?;
+
+        // modifiers which are not being replaced must also be offered by the new menu item
+        let modifier_names = update.modifiers
+            .unwrap_or_else(|| current.modifiers.iter().map(|m| m.name.clone()).collect());
+        let chosen_modifiers = choose_modifiers(&menu_item, &modifier_names)?;
+
+        // when only the modifiers change, the order keeps the price its menu item was ordered at
+        let base_price = match update.menu_item_id {
+            Some(_) => {
+                minutes_to_cook = minutes_to_cook.or_else(|| Some(rng.gen_range(menu_item.min_minutes_to_cook, menu_item.max_minutes_to_cook)));
+                menu_item.price
+            },
+            None => rules::base_price(&current)?
+        };
+        price = Some(price_with_modifiers(base_price, &chosen_modifiers)?);
+        modifiers = Some(chosen_modifiers);
+    }
+    let note = match update.note {
+        Some(note) => Some(validate_note(Some(&note))?),
+        None => None
+    };
+
+    // only the provided fields are changed
+    let mut assignments = Vec::new();
+    let mut values = vec![
+        (":order_id", order_id.to_string()),
+        (":table_number", table_number.to_string())];
+    if let Some(menu_item_id) = update.menu_item_id {
+        assignments.push("menu_item_id = :menu_item_id");
+        values.push((":menu_item_id", menu_item_id.to_string()));
+    }
+    if let Some(new_table_number) = update.table_number {
+        assignments.push("table_number = :new_table_number");
+        values.push((":new_table_number", new_table_number.to_string()));
+    }
+    if let Some(minutes_to_cook) = minutes_to_cook {
+        assignments.push("minutes_to_cook = :minutes_to_cook");
+        values.push((":minutes_to_cook", minutes_to_cook.to_string()));
+    }
+    if let Some(price) = price {
+        assignments.push("price = :price");
+        values.push((":price", price.to_string()));
+    }
+    if let Some(seat) = update.seat {
+        assignments.push("seat = :seat");
+        values.push((":seat", seat.to_string()));
+    }
+    if let Some(modifiers) = modifiers {
+        assignments.push("modifiers = :modifiers");
+        values.push((":modifiers", modifiers_to_sql(&modifiers)?));
+    }
+    // an empty note is stored as null, so it is set directly rather than bound as a string
+    match note {
+        Some(Some(note)) => {
+            assignments.push("note = :note");
+            values.push((":note", note));
+        },
+        Some(None) => assignments.push("note = NULL"),
+        None => {}
+    }
+
+    // with nothing to change, the order is returned as is
+    if assignments.is_empty() {
+        return Result::Ok(current);
+    }
+
+    let params = values.iter()
+        .map(|(name, value)| (*name, value as &dyn ToSql))
+        .collect::<Vec<_>>();
+    let rows = transaction.execute(
+        &format!("UPDATE orders
+            SET {}
+            WHERE id = :order_id
+            AND table_number = :table_number
+            AND checked_out_at IS NULL", assignments.join(", ")),
+        params.as_slice()).sql_err()?;
+
+    if rows == 0 {
+        return Err(ServerError::DataNotFound);
+    }
+
+    let now = clock.now();
+    let item = query_order(&transaction, now, update.table_number.unwrap_or(table_number), order_id)?;
+    let previous_table_number = update.table_number.filter(|n| *n != table_number).map(|_| table_number);
+    record_order_event(&transaction, now, OrderEventKind::Updated, &item, previous_table_number)?;
+
+    transaction.commit().sql_err()?;
+
+    Result::Ok(item)
+}
+
+// Moves all or selected open orders of a table to another table in a single transaction, returning the destination table's orders.
+// If any of the selected orders is not an open order of the source table, DataNotFound is returned and nothing is moved.
+pub fn transfer_orders(pool: &DatabasePool, clock: &dyn Clock, settings: &Settings, table_number: u32, transfer: rest_bodies::Transfer) -> Result<rest_responses::Orders, ServerError> {
+    if transfer.table_number == table_number {
+        return Err(ServerError::InvalidTable("Orders cannot be transferred to the table they are already on.".to_string()));
+    }
+
+    let mut connection = pool.open()?;
+    let transaction = write_transaction(&mut connection)?;
+
+    check_table_accepts_orders(&transaction, settings, transfer.table_number)?;
+
+    let mut source_ids = Vec::new();
+    {
+        let mut stmt = transaction.prepare(
+            "SELECT id FROM orders
+            WHERE table_number = :table_number
+            AND checked_out_at IS NULL").sql_err()?;
+        let query_result = stmt.query_map(
+            &[(":table_number", &table_number.to_string())],
+            |row| row.get::<_, u32>(0)).sql_err()?;
+        for id in query_result {
+            source_ids.push(id.sql_err()?);
+        }
+    }
+
+    let ids = match transfer.order_ids {
+        Some(ids) if ids.iter().any(|id| !source_ids.contains(id)) => return Err(ServerError::DataNotFound),
+        Some(ids) => ids,
+        None => source_ids
+    };
+
+    let now = clock.now();
+    for id in ids {
+        transaction.execute(
+            "UPDATE orders SET table_number = :new_table_number WHERE id = :order_id",
+            &[
+                (":new_table_number", &transfer.table_number.to_string()),
+                (":order_id", &id.to_string())]).sql_err()?;
+        let item = query_order(&transaction, now, transfer.table_number, id)?;
+        record_order_event(&transaction, now, OrderEventKind::Updated, &item, Some(table_number))?;
+    }
+
+    transaction.commit().sql_err()?;
+
+    get_orders(pool, clock, transfer.table_number, &OrderQuery::default())
+}
+
+pub fn update_order_status(pool: &DatabasePool, clock: &dyn Clock, table_number: u32, order_id: u32, status: OrderStatus) -> Result<rest_responses::Order, ServerError> {
+    let mut connection = pool.open()?;
+    let transaction = write_transaction(&mut connection)?;
+
+    let current_status = transaction.query_row(
+        "SELECT status FROM orders
+            WHERE id = :order_id
+            AND table_number = :table_number
+            AND checked_out_at IS NULL",
+        &[
+            (":order_id", &order_id.to_string()),
+            (":table_number", &table_number.to_string())],
+        |row| row.get::<_, OrderStatus>(0))
+        .map_err(|e| match e {
+            Error::QueryReturnedNoRows => ServerError::DataNotFound,
+            x => ServerError::SqlError(x)
+        })?;
+
+    if !current_status.can_transition_to(status) {
+        return Err(ServerError::InvalidStatusTransition(current_status, status));
+    }
+
+    // the timestamp column is chosen from a fixed set of names, so it is safe to format into the query
+    transaction.execute(
+        &format!("UPDATE orders
+            SET status = :status, {} = :changed_at
+            WHERE id = :order_id", status.timestamp_column()),
+        &[
+            (":status", &status.as_str().to_string()),
+            (":changed_at", &clock.now().to_string()),
+            (":order_id", &order_id.to_string())]).sql_err()?;
+
+    let now = clock.now();
+    let item = query_order(&transaction, now, table_number, order_id)?;
+    record_order_event(&transaction, now, OrderEventKind::Updated, &item, None)?;
+
+    transaction.commit().sql_err()?;
+
+    Result::Ok(item)
+}
+
+// Starts a request which was made with an idempotency key. Requests are identified by their method, route and key,
+// and the request itself is stored so that the key cannot be reused for a different request.
+// If the request has already been completed, its stored status and response are returned. Otherwise the key is reserved,
+// and the request should be finished with complete_idempotent_request or release_idempotent_request.
+pub fn begin_idempotent_request(pool: &DatabasePool, clock: &dyn Clock, method: &str, route: &str, key: &str, request: &str) -> Result<Option<(u16, String)>, ServerError> {
+    let connection = pool.open()?;
+    let inserted = connection.execute(
+        "INSERT INTO idempotent_requests (method, route, idempotency_key, created_at, request)
+        VALUES (:method, :route, :key, :created_at, :request)",
+        &[
+            (":method", method),
+            (":route", route),
+            (":key", key),
+            (":created_at", &clock.now().to_string()),
+            (":request", request)]);
+
+    match inserted {
+        Result::Ok(_) => Result::Ok(None),
+        Err(Error::SqliteFailure(err, _)) if err.code == ErrorCode::ConstraintViolation => {
+            let stored = connection.query_row(
+                "SELECT request, status, response FROM idempotent_requests
+                WHERE method = :method AND route = :route AND idempotency_key = :key",
+                &[(":method", method), (":route", route), (":key", key)],
+                |row| Result::Ok((row.get::<_, Option<String>>(0)?, row.get::<_, Option<u16>>(1)?, row.get::<_, Option<String>>(2)?)))
+                .optional().sql_err()?;
+
+            match stored {
+                // keys which were migrated from before requests were stored have no request, and match any
+                Some((Some(stored_request), _, _)) if stored_request != request => Err(ServerError::IdempotencyKeyReused),
+                Some((_, Some(status), Some(response))) => Result::Ok(Some((status, response))),
+                // the first request has not finished yet, or was released between the insert and the select
+                _ => Err(ServerError::Idempotency)
+            }
+        },
+        Err(e) => Err(ServerError::SqlError(e))
+    }
+}
+
+// Stores the status and response of a request started with begin_idempotent_request, so that they can be replayed
+pub fn complete_idempotent_request(pool: &DatabasePool, method: &str, route: &str, key: &str, status: u16, response: &str) -> Result<(), ServerError> {
+    let connection = pool.open()?;
+    connection.execute(
+        "UPDATE idempotent_requests SET status = :status, response = :response
+        WHERE method = :method AND route = :route AND idempotency_key = :key",
+        &[
+            (":status", status.to_string().as_str()),
+            (":response", response),
+            (":method", method),
+            (":route", route),
+            (":key", key)]).sql_err()?;
+    Result::Ok(())
+}
+
+// Releases the key of a request started with begin_idempotent_request without storing a response, so that it can be retried
+pub fn release_idempotent_request(pool: &DatabasePool, method: &str, route: &str, key: &str) -> Result<(), ServerError> {
+    let connection = pool.open()?;
+    connection.execute(
+        "DELETE FROM idempotent_requests
+        WHERE method = :method AND route = :route AND idempotency_key = :key",
+        &[(":method", method), (":route", route), (":key", key)]).sql_err()?;
+    Result::Ok(())
+}
+
+pub fn get_idempotency_keys(pool: &DatabasePool) -> Result<rest_responses::IdempotencyKeys, ServerError> {
+    let connection = pool.open()?;
+    connection.query_row(
+        "SELECT COUNT(*), MIN(created_at) FROM idempotent_requests",
+        [],
+        |row| Result::Ok(rest_responses::IdempotencyKeys {
+            count: row.get(0)?,
+            oldest_created_at: row.get(1)?
+        })).sql_err()
+}
+
+// Deletes idempotency keys which are at least ttl_seconds old, returning how many were deleted.
+// Once a key has been purged, a request with the same key will be processed again.
+pub fn purge_idempotency_keys(pool: &DatabasePool, clock: &dyn Clock, ttl_seconds: u64) -> Result<u64, ServerError> {
+    let connection = pool.open()?;
+    let deleted = connection.execute(
+        "DELETE FROM idempotent_requests WHERE created_at <= :expired_at",
+        &[(":expired_at", &clock.now().saturating_sub(ttl_seconds).to_string())]).sql_err()?;
+    Result::Ok(deleted as u64)
+}
+
+// Returns up to `limit` events from the event log which come after the given event id, oldest first.
+// When tables are given, only events for orders on (or moved off) those tables are returned.
+pub fn get_order_events(pool: &DatabasePool, tables: &TableFilter, after_id: u64, limit: u32) -> Result<Vec<OrderEvent>, ServerError> {
+    let connection = pool.open()?;
+    let mut conditions = vec!["id > :after_id".to_string()];
+    let mut values = vec![
+        (":after_id".to_string(), after_id.to_string()),
+        (":limit".to_string(), limit.to_string())];
+    if !tables.ranges.is_empty() {
+        conditions.push(format!("({})", tables.ranges.iter().enumerate()
+            .map(|(i, _)| format!("table_number BETWEEN :table_from_{i} AND :table_to_{i}
+                OR previous_table_number BETWEEN :table_from_{i} AND :table_to_{i}"))
+            .collect::<Vec<_>>()
+            .join(" OR ")));
+        for (i, range) in tables.ranges.iter().enumerate() {
+            values.push((format!(":table_from_{i}"), range.start().to_string()));
+            values.push((format!(":table_to_{i}"), range.end().to_string()));
+        }
+    }
+
+    let mut stmt = connection.prepare(&format!(
+        "SELECT id, kind, data FROM order_events
+        WHERE {}
+        ORDER BY id
+        LIMIT :limit",
+        conditions.join(" AND "))).sql_err()?;
+    let params = values.iter()
+        .map(|(name, value)| (name.as_str(), value as &dyn ToSql))
+        .collect::<Vec<_>>();
     let query_result = stmt.query_map(
-        &[(":table_number", &table_number.to_string())],
-        |row| Result::Ok(rest_responses::Order {
+        params.as_slice(),
+        |row| Result::Ok(OrderEvent {
             id: row.get(0)?,
-            minutes_to_cook: row.get(1)?,
-            menu_item_id: row.get(2)?,
-            menu_item_name: row.get(3)?
+            kind: row.get(1)?,
+            data: row.get(2)?
         })).sql_err()?;
 
     let mut items = Vec::new();
     for item in query_result {
         items.push(item.sql_err()?);
     }
+    Result::Ok(items)
+}
 
-    Result::Ok(
-        rest_responses::Orders {
-            orders: items
-        }
-    )
+// The id of the most recent event, or 0 if nothing has happened yet
+pub fn get_last_order_event_id(pool: &DatabasePool) -> Result<u64, ServerError> {
+    let connection = pool.open()?;
+    connection.query_row(
+        "SELECT COALESCE(MAX(id), 0) FROM order_events",
+        [],
+        |row| row.get(0)).sql_err()
+}
+
+// Deletes events which are at least ttl_seconds old, returning how many were deleted.
+// A client which resumes from a purged event only receives the events which are left.
+pub fn purge_order_events(pool: &DatabasePool, clock: &dyn Clock, ttl_seconds: u64) -> Result<u64, ServerError> {
+    let connection = pool.open()?;
+    let deleted = connection.execute(
+        "DELETE FROM order_events WHERE created_at <= :expired_at",
+        &[(":expired_at", &clock.now().saturating_sub(ttl_seconds).to_string())]).sql_err()?;
+    Result::Ok(deleted as u64)
+}
+
+pub fn get_bill(pool: &DatabasePool, table_number: u32) -> Result<rest_responses::Bill, ServerError> {
+    let connection = pool.open()?;
+    calculate_bill(&connection, table_number)
+}
+
+pub fn get_split_bill(pool: &DatabasePool, table_number: u32, split: BillSplit) -> Result<rest_responses::SplitBill, ServerError> {
+    let connection = pool.open()?;
+    let bill = calculate_bill(&connection, table_number)?;
+    bill_split::split_bill(bill, split)
+}
+
+// Closes out every open order for the table, returning the final bill.
+// Returns DataNotFound if the table has no open orders.
+pub fn checkout(pool: &DatabasePool, clock: &dyn Clock, table_number: u32) -> Result<rest_responses::Bill, ServerError> {
+    let mut connection = pool.open()?;
+    let transaction = write_transaction(&mut connection)?;
+
+    // the bill is calculated in the same transaction so that it covers exactly the orders which are closed
+    let bill = calculate_bill(&transaction, table_number)?;
+
+    // closed orders are no longer listed, so to anyone following the event log they have been deleted
+    let now = clock.now();
+    let closed = query_orders(
+        &transaction,
+        now,
+        vec!["o.table_number = :table_number".to_string()],
+        vec![(":table_number".to_string(), table_number.to_string())],
+        &OrderQuery::default())?;
+    for item in &closed.orders {
+        record_order_event(&transaction, now, OrderEventKind::Deleted, item, None)?;
+    }
+    let rows = transaction.execute(
+        "UPDATE orders
+            SET checked_out_at = :checked_out_at
+            WHERE table_number = :table_number
+            AND checked_out_at IS NULL",
+        &[
+            (":checked_out_at", &now.to_string()),
+            (":table_number", &table_number.to_string())]).sql_err()?;
+
+    if rows == 0 {
+        return Err(ServerError::DataNotFound);
+    }
+
+    transaction.commit().sql_err()?;
+    Result::Ok(bill)
 }
 
-pub fn get_order(connector: &dyn DatabaseConnector, table_number: u32, order_id: u32) -> Result<rest_responses::Order, ServerError> {
-    let connection = connector.open().sql_err()?;
+// Totals the open orders of a table. Cancelled orders are not charged.
+fn calculate_bill(connection: &Connection, table_number: u32) -> Result<rest_responses::Bill, ServerError> {
     let mut stmt = connection.prepare(
-        "SELECT o.id, o.minutes_to_cook, m.id, m.name
+        "SELECT o.id, m.id, m.name, o.price, o.seat
         FROM orders AS o
         INNER JOIN menu_items AS m ON m.id = o.menu_item_id
-        WHERE o.id = :order_id
-        AND o.table_number = :table_number").sql_err()?;
-    let query_result = stmt.query_row(
+        WHERE o.table_number = :table_number
+        AND o.checked_out_at IS NULL
+        AND o.status != :cancelled
+        ORDER BY o.id").sql_err()?;
+    let query_result = stmt.query_map(
         &[
-            (":order_id", &order_id.to_string()),
-            (":table_number", &table_number.to_string())],
-        |row| Result::Ok(rest_responses::Order {
-            id: row.get(0)?,
-            minutes_to_cook: row.get(1)?,
-            menu_item_id: row.get(2)?,
-            menu_item_name: row.get(3)?
-        }))
+            (":table_number", &table_number.to_string()),
+            (":cancelled", &OrderStatus::Cancelled.as_str().to_string())],
+        |row| Result::Ok(rest_responses::BillLineItem {
+            order_id: row.get(0)?,
+            menu_item_id: row.get(1)?,
+            menu_item_name: row.get(2)?,
+            price: row.get(3)?,
+            seat: row.get(4)?
+        })).sql_err()?;
+
+    let mut line_items = Vec::new();
+    for item in query_result {
+        line_items.push(item.sql_err()?);
+    }
+
+    Result::Ok(rules::bill(table_number, line_items))
+}
+
+// Starts a transaction which takes the database's write lock straight away. A deferred transaction which reads
+// before it writes cannot wait for another connection's write to finish, and would fail with SQLITE_BUSY instead.
+fn write_transaction(connection: &mut Connection) -> Result<Transaction<'_>, ServerError> {
+    connection.transaction_with_behavior(TransactionBehavior::Immediate).sql_err()
+}
+
+// Returns DataNotFound if the table has not been registered
+fn query_table(connection: &Connection, table_number: u32) -> Result<rest_responses::Table, ServerError> {
+    connection.query_row(
+        &format!("{TABLE_SELECT} WHERE t.number = :number"),
+        &[(":number", &table_number.to_string())],
+        table_from_row)
         .map_err(|e| match e {
             Error::QueryReturnedNoRows => ServerError::DataNotFound,
             x => ServerError::SqlError(x)
+        })
+}
+
+// Unless registered tables are required, orders can be added to any table number.
+// Otherwise returns TableNotFound if the table has not been registered, or TableClosed if it is closed.
+fn check_table_accepts_orders(connection: &Connection, settings: &Settings, table_number: u32) -> Result<(), ServerError> {
+    if !settings.require_registered_tables {
+        return Result::Ok(());
+    }
+
+    let open = connection.query_row(
+        "SELECT open FROM tables WHERE number = :number",
+        &[(":number", &table_number.to_string())],
+        |row| row.get::<_, bool>(0))
+        .map_err(|e| match e {
+            Error::QueryReturnedNoRows => ServerError::TableNotFound,
+            x => ServerError::SqlError(x)
         })?;
 
-    Result::Ok(query_result)
+    match open {
+        true => Result::Ok(()),
+        false => Err(ServerError::TableClosed)
+    }
+}
+
+// Returns MenuItemNotFound if no menu item with the given id can be ordered
+fn get_orderable_menu_item(connection: &Connection, menu_item_id: u32) -> Result<rest_responses::MenuItem, ServerError> {
+    connection.query_row(
+        &format!("{MENU_ITEM_SELECT}
+            WHERE id = :menu_item_id
+            AND retired = 0"),
+        &[(":menu_item_id", &menu_item_id.to_string())],
+        menu_item_from_row)
+        .map_err(|e| match e {
+            Error::QueryReturnedNoRows => ServerError::MenuItemNotFound,
+            x => ServerError::SqlError(x)
+        })
+}
+
+fn get_menu_item(connection: &Connection, menu_item_id: i64) -> Result<rest_responses::MenuItem, ServerError> {
+    connection.query_row(
+        &format!("{MENU_ITEM_SELECT} WHERE id = :menu_item_id"),
+        &[(":menu_item_id", &menu_item_id.to_string())],
+        menu_item_from_row)
+        .map_err(|e| match e {
+            Error::QueryReturnedNoRows => ServerError::DataNotFound,
+            x => ServerError::SqlError(x)
+        })
 }
 
-pub fn delete_order(connector: &dyn DatabaseConnector, table_number: u32, order_id: u32) -> Result<(), ServerError> {
-    let connection = connector.open().sql_err()?;
+// Replaces all of a menu item's modifiers
+fn set_menu_item_modifiers(connection: &Connection, menu_item_id: i64, modifiers: &[rest_responses::Modifier]) -> Result<(), ServerError> {
     connection.execute(
-        "DELETE FROM orders
-            WHERE id = :order_id
-            AND table_number = :table_number",
+        "DELETE FROM menu_item_modifiers WHERE menu_item_id = :menu_item_id",
+        &[(":menu_item_id", &menu_item_id.to_string())]).sql_err()?;
+    for modifier in modifiers {
+        connection.execute(
+            "INSERT INTO menu_item_modifiers (menu_item_id, name, price_delta)
+                VALUES (:menu_item_id, :name, :price_delta)",
+            &[
+                (":menu_item_id", &menu_item_id.to_string()),
+                (":name", &modifier.name),
+                (":price_delta", &modifier.price_delta.to_string())]).sql_err()?;
+    }
+    Result::Ok(())
+}
+
+// Modifiers are copied onto orders as a JSON array
+fn modifiers_to_sql(modifiers: &[rest_responses::Modifier]) -> Result<String, ServerError> {
+    to_string(&modifiers).map_err(|e| ServerError::SqlError(Error::ToSqlConversionFailure(Box::new(e))))
+}
+
+// Reads a column holding a JSON array of modifiers
+fn modifiers_from_row(row: &Row, index: usize) -> Result<Vec<rest_responses::Modifier>> {
+    let modifiers: String = row.get(index)?;
+    from_str(&modifiers).map_err(|e| Error::FromSqlConversionFailure(index, Type::Text, Box::new(e)))
+}
+
+// Menu item names are unique, so a constraint violation when writing one means the name is taken
+fn menu_item_name_err(e: Error) -> ServerError {
+    match e {
+        Error::SqliteFailure(err, _) if err.code == ErrorCode::ConstraintViolation =>
+            ServerError::DuplicateMenuItem,
+        x => ServerError::SqlError(x)
+    }
+}
+
+fn table_from_row(row: &Row) -> Result<rest_responses::Table> {
+    Result::Ok(rest_responses::Table {
+        number: row.get(0)?,
+        capacity: row.get(1)?,
+        section: row.get(2)?,
+        open: row.get(3)?,
+        order_count: row.get(4)?
+    })
+}
+
+fn menu_item_from_row(row: &Row) -> Result<rest_responses::MenuItem> {
+    Result::Ok(rest_responses::MenuItem {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        min_minutes_to_cook: row.get(2)?,
+        max_minutes_to_cook: row.get(3)?,
+        price: row.get(4)?,
+        retired: row.get(5)?,
+        modifiers: modifiers_from_row(row, 6)?
+    })
+}
+
+// Adds a change to an order to the event log. This should be called in the same transaction as the change,
+// so that the log never misses (or invents) a change. previous_table_number is set when the order moved tables.
+fn record_order_event(connection: &Connection, now: u64, kind: OrderEventKind, order: &rest_responses::Order, previous_table_number: Option<u32>) -> Result<(), ServerError> {
+    let data = to_string(order).map_err(|e| ServerError::SqlError(Error::ToSqlConversionFailure(Box::new(e))))?;
+    connection.execute(
+        "INSERT INTO order_events (kind, order_id, table_number, previous_table_number, created_at, data)
+        VALUES (:kind, :order_id, :table_number, :previous_table_number, :created_at, :data)",
         &[
-            (":order_id", &order_id.to_string()),
-            (":table_number", &table_number.to_string())]).sql_err()?;
+            (":kind", &kind.as_str() as &dyn ToSql),
+            (":order_id", &order.id.to_string()),
+            (":table_number", &order.table_number.to_string()),
+            (":previous_table_number", &previous_table_number),
+            (":created_at", &now.to_string()),
+            (":data", &data)]).sql_err()?;
     Result::Ok(())
 }
 
+// Converts a row selected with ORDER_SELECT, calculating how long is left to cook as of `now`
+fn order_from_row(row: &Row, now: u64) -> Result<rest_responses::Order> {
+    let minutes_to_cook: u32 = row.get(1)?;
+    let status: OrderStatus = row.get(4)?;
+    let created_at: u64 = row.get(10)?;
+    let (ready_at, minutes_remaining) = rules::order_timing(status, created_at, minutes_to_cook, now);
+
+    Result::Ok(rest_responses::Order {
+        id: row.get(0)?,
+        table_number: row.get(15)?,
+        minutes_to_cook,
+        menu_item_id: row.get(2)?,
+        menu_item_name: row.get(3)?,
+        status,
+        status_times: rest_responses::StatusTimes {
+            placed_at: row.get(5)?,
+            cooking_at: row.get(6)?,
+            ready_at: row.get(7)?,
+            served_at: row.get(8)?,
+            cancelled_at: row.get(9)?
+        },
+        created_at,
+        minutes_remaining,
+        ready_at,
+        price: row.get(11)?,
+        seat: row.get(12)?,
+        modifiers: modifiers_from_row(row, 13)?,
+        note: row.get(14)?
+    })
+}
+
+// Order statuses and event kinds are stored as TEXT columns, in the same form as in the API
+impl FromSql for OrderStatus {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value.as_str()?
+            .parse()
+            .map_err(|e: String| FromSqlError::Other(e.into()))
+    }
+}
+
+impl FromSql for OrderEventKind {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value.as_str()?
+            .parse()
+            .map_err(|e: String| FromSqlError::Other(e.into()))
+    }
+}
+
 pub trait DisplayResultMethods<T> {
     fn sql_err(self) -> Result<T, ServerError>;
 }
@@ -214,6 +1179,6 @@ impl<T> DisplayResultMethods<T> for Result<T, rusqlite::Error>
     // that converts the error to a ServerError as defined by this application
     fn sql_err(self) -> Result<T, ServerError>
     {
-        self.map_err(|e| ServerError::SqlError(e))
+        self.map_err(ServerError::SqlError)
     }
 }
\ No newline at end of file
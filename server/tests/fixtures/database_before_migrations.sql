@@ -0,0 +1,25 @@
+-- A database as created by setup_database before migrations were introduced, with some data in every table.
+-- The tables are created exactly as they were then; there is no schema_version table.
+
+CREATE TABLE IF NOT EXISTS menu_items (
+    id INTEGER PRIMARY KEY,
+    name TEXT UNIQUE);
+CREATE TABLE IF NOT EXISTS orders (
+    id INTEGER PRIMARY KEY,
+    menu_item_id INTEGER,
+    table_number INTEGER,
+    minutes_to_cook INTEGER,
+    FOREIGN KEY(menu_item_id) REFERENCES menu_items(id));
+CREATE TABLE IF NOT EXISTS idempotent_requests (
+    idempotency_key TEXT PRIMARY KEY);
+
+INSERT INTO menu_items (name) VALUES
+    ('Hamburger'), ('Salad'), ('Sushi'), ('Ice Cream'), ('Soda');
+
+INSERT INTO orders (id, menu_item_id, table_number, minutes_to_cook) VALUES
+    (1, 1, 1, 12),
+    (2, 2, 1, 5),
+    (3, 3, 2, 8);
+
+INSERT INTO idempotent_requests (idempotency_key) VALUES
+    ('tablet-1-request-1');
@@ -1,18 +1,39 @@
 #[cfg(test)]
-
-mod mock_database_connector;
+mod temp_database;
+mod mock_clock;
+mod mock_random_number_generator;
+mod test_repository;
 
 mod tests {
-    use rocket::http::{ContentType, Status};
+    use std::io::Read;
+    use std::sync::{ Arc, Barrier };
+    use rocket::http::{ContentType, Header, Status};
     use rocket::serde::Deserialize;
-    use server::database_connector::DatabaseConnector;
+    use server::clock::Clock;
+    use server::random_number_generator::RandomNumberGenerator;
     use rocket::local::blocking::{Client, LocalResponse};
-    use server::server_functions::setup_database;
+    use server::server_functions::{ get_schema_version, migrate, setup_database };
+    use server::migrations::{ latest_version, SQLITE_MIGRATIONS };
+    use server::database_pool::DatabasePool;
+    use server::repositories::sqlite::SqliteRepository;
     use rocket::serde::json::{ to_string, from_str };
     use server::{rest_bodies, rest_responses};
+    use server::order_status::OrderStatus;
+    use server::settings::{ CookTimeRange, Settings, Storage };
+    use server::seed::Seed;
+    use rocket::figment::Figment;
+    use rocket::figment::providers::{ Format, Toml };
+    use server::order_events;
+    use server::kitchen::KitchenMessage;
+    use rocket::{Build, Rocket, Shutdown};
+    use rocket::fairing::AdHoc;
+    use rocket::futures::{SinkExt, StreamExt};
     use rocket::{catchers, routes};
 
-    use crate::mock_database_connector;
+    use crate::temp_database;
+    use crate::test_repository::{self, TestRepository};
+    use crate::mock_clock::{self, MockClock};
+    use crate::mock_random_number_generator;
     use server::endpoints::*;
 
     #[derive(Deserialize)]
@@ -22,47 +43,547 @@ mod tests {
     }
 
     fn assert_response_contains_error(response: LocalResponse) -> Result<ErrorResponse, String> {
-        from_str::<ErrorResponse>(&response.into_string().unwrap())
-            .map_err(|e| e.to_string())
+        let error_response = from_str::<ErrorResponse>(&response.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert!(!error_response.error.is_empty());
+        Ok(error_response)
     }
 
     fn create_client_without_setup() -> Result<Client, String> {
-        let database_connector = mock_database_connector::new()?;
-        let rocket = rocket::build()
-            .mount("/", routes![get_table_orders])
-            .mount("/", routes![post_table_order])
-            .mount("/", routes![get_table_order])
-            .mount("/", routes![delete_table_order])
-            .mount("/", routes![get_menu_items])
-            .register("/", catchers![internal_error, not_found, default, unprocessable_entity])
-            .manage(Box::new(database_connector) as Box<dyn DatabaseConnector>);
-        Ok(Client::tracked(rocket).unwrap())
+        create_client_with(test_repository::without_setup()?, mock_clock::new(), Settings::default())
     }
 
     fn create_client() -> Result<Client, String> {
-        let database_connector = mock_database_connector::new()?;
-        setup_database(&database_connector).map_err(|e| e.to_string())?;
-        let rocket = rocket::build()
+        create_client_with_clock(mock_clock::new())
+    }
+
+    fn create_client_with_clock(clock: MockClock) -> Result<Client, String> {
+        create_client_with(test_repository::new()?, clock, Settings::default())
+    }
+
+    fn create_client_with_settings(settings: Settings) -> Result<Client, String> {
+        create_client_with(test_repository::new()?, mock_clock::new(), settings)
+    }
+
+    fn create_client_with(repository: TestRepository, clock: MockClock, settings: Settings) -> Result<Client, String> {
+        Ok(Client::tracked(build_rocket(repository, clock, settings)).unwrap())
+    }
+
+    fn build_rocket(repository: TestRepository, clock: MockClock, settings: Settings) -> Rocket<Build> {
+        repository.manage(rocket::build())
             .mount("/", routes![get_table_orders])
+            .mount("/", routes![get_orders])
+            .mount("/", routes![get_events])
+            .mount("/", routes![kitchen_channel])
             .mount("/", routes![post_table_order])
             .mount("/", routes![get_table_order])
             .mount("/", routes![delete_table_order])
+            .mount("/", routes![delete_table_orders])
+            .mount("/", routes![patch_table_order])
+            .mount("/", routes![put_table_order_status])
+            .mount("/", routes![get_table_bill])
+            .mount("/", routes![get_table_split_bill])
+            .mount("/", routes![post_table_checkout])
+            .mount("/", routes![post_table_transfer])
+            .mount("/", routes![get_tables])
+            .mount("/", routes![post_table])
+            .mount("/", routes![get_table])
+            .mount("/", routes![patch_table])
+            .mount("/", routes![delete_table])
             .mount("/", routes![get_menu_items])
+            .mount("/", routes![post_menu_item])
+            .mount("/", routes![patch_menu_item])
+            .mount("/", routes![delete_menu_item])
+            .mount("/", routes![get_idempotency_keys])
             .register("/", catchers![internal_error, not_found, default, unprocessable_entity])
-            .manage(Box::new(database_connector) as Box<dyn DatabaseConnector>);
-        Ok(Client::tracked(rocket).unwrap())
+            .manage(Arc::new(clock) as Arc<dyn Clock>)
+            .manage(Box::new(mock_random_number_generator::new()) as Box<dyn RandomNumberGenerator>)
+            .manage(settings)
+            .attach(order_events::fairing())
+    }
+
+    // Launches a server on a free port, for tests which need a real connection (such as WebSockets), returning its port.
+    // The server is shut down with the returned handle.
+    async fn launch_server(repository: TestRepository) -> Result<(u16, Shutdown), String> {
+        let (port_sender, port_receiver) = rocket::tokio::sync::oneshot::channel();
+        let rocket = build_rocket(repository, mock_clock::new(), Settings::default())
+            .configure(rocket::Config {
+                port: 0,
+                log_level: rocket::config::LogLevel::Off,
+                ..rocket::Config::debug_default()
+            })
+            .attach(AdHoc::on_liftoff("Port", |rocket| Box::pin(async move {
+                let _ = port_sender.send(rocket.config().port);
+            })))
+            .ignite().await
+            .map_err(|e| e.to_string())?;
+        let shutdown = rocket.shutdown();
+        rocket::tokio::spawn(rocket.launch());
+        let port = port_receiver.await.map_err(|e| e.to_string())?;
+        Ok((port, shutdown))
+    }
+
+    type KitchenSocket = tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<rocket::tokio::net::TcpStream>>;
+
+    async fn send_kitchen_request(socket: &mut KitchenSocket, request: &str) -> Result<(), String> {
+        socket.send(tokio_tungstenite::tungstenite::Message::Text(request.to_string())).await.map_err(|e| e.to_string())
+    }
+
+    async fn next_kitchen_message(socket: &mut KitchenSocket) -> Result<KitchenMessage, String> {
+        loop {
+            match socket.next().await {
+                Some(Ok(tokio_tungstenite::tungstenite::Message::Text(text))) => return from_str::<KitchenMessage>(&text).map_err(|e| e.to_string()),
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => return Err(e.to_string()),
+                None => return Err("The channel was closed".to_string())
+            }
+        }
+    }
+
+    fn post_orders(client: &Client, table_number: u32, menu_item_ids: Vec<u32>) -> Result<Vec<rest_responses::Order>, String> {
+        post_seated_orders(client, table_number, menu_item_ids.iter().map(|i| (*i, None)).collect())
+    }
+
+    // Each order is a (menu_item_id, seat) pair
+    fn post_seated_orders(client: &Client, table_number: u32, seated_orders: Vec<(u32, Option<u32>)>) -> Result<Vec<rest_responses::Order>, String> {
+        let orders = rest_bodies::Orders {
+            idempotency_key: Option::None,
+            orders: seated_orders.iter().map(|(menu_item_id, seat)| rest_bodies::Order {
+                menu_item_id: *menu_item_id,
+                seat: *seat,
+                modifiers: None,
+                note: None
+            }).collect()
+        };
+        let post_response = client.post(format!("/tables/{table_number}/orders"))
+            .header(ContentType::JSON)
+            .body(to_string(&orders).map_err(|e| e.to_string())?)
+            .dispatch();
+        Ok(from_str::<rest_responses::Orders>(&post_response.into_string().unwrap())
+            .map_err(|e| e.to_string())
+            ?.orders)
+    }
+
+    fn post_order(client: &Client, table_number: u32, order: rest_bodies::Order) -> Result<LocalResponse<'_>, String> {
+        let orders = rest_bodies::Orders {
+            idempotency_key: Option::None,
+            orders: vec![order]
+        };
+        Ok(client.post(format!("/tables/{table_number}/orders"))
+            .header(ContentType::JSON)
+            .body(to_string(&orders).map_err(|e| e.to_string())?)
+            .dispatch())
+    }
+
+    fn post_order_with_key<'a>(client: &'a Client, table_number: u32, idempotency_key: &str) -> Result<LocalResponse<'a>, String> {
+        let orders = rest_bodies::Orders {
+            idempotency_key: Option::Some(idempotency_key.to_string()),
+            orders: vec![rest_bodies::Order {
+                menu_item_id: 1,
+                seat: None,
+                modifiers: None,
+                note: None
+            }]
+        };
+        Ok(client.post(format!("/tables/{table_number}/orders"))
+            .header(ContentType::JSON)
+            .body(to_string(&orders).map_err(|e| e.to_string())?)
+            .dispatch())
+    }
+
+    fn put_status(client: &Client, table_number: u32, order_id: u32, status: OrderStatus) -> Result<LocalResponse<'_>, String> {
+        let body = rest_bodies::OrderStatus {
+            status
+        };
+        Ok(client.put(format!("/tables/{table_number}/orders/{order_id}/status"))
+            .header(ContentType::JSON)
+            .body(to_string(&body).map_err(|e| e.to_string())?)
+            .dispatch())
+    }
+
+    fn patch_order(client: &Client, table_number: u32, order_id: u32, update: rest_bodies::OrderUpdate) -> Result<LocalResponse<'_>, String> {
+        Ok(client.patch(format!("/tables/{table_number}/orders/{order_id}"))
+            .header(ContentType::JSON)
+            .body(to_string(&update).map_err(|e| e.to_string())?)
+            .dispatch())
+    }
+
+    fn create_menu_item<'a>(client: &'a Client, name: &str, min_minutes_to_cook: u32, max_minutes_to_cook: u32) -> Result<LocalResponse<'a>, String> {
+        let menu_item = rest_bodies::MenuItem {
+            name: name.to_string(),
+            min_minutes_to_cook,
+            max_minutes_to_cook,
+            price: 1000,
+            modifiers: None
+        };
+        Ok(client.post("/menu-items")
+            .header(ContentType::JSON)
+            .body(to_string(&menu_item).map_err(|e| e.to_string())?)
+            .dispatch())
+    }
+
+    fn update_menu_item(client: &Client, menu_item_id: u32, update: rest_bodies::MenuItemUpdate) -> Result<LocalResponse<'_>, String> {
+        Ok(client.patch(format!("/menu-items/{menu_item_id}"))
+            .header(ContentType::JSON)
+            .body(to_string(&update).map_err(|e| e.to_string())?)
+            .dispatch())
+    }
+
+    fn create_table<'a>(client: &'a Client, number: u32, capacity: u32, section: Option<&str>) -> Result<LocalResponse<'a>, String> {
+        let table = rest_bodies::Table {
+            number,
+            capacity,
+            section: section.map(|s| s.to_string()),
+            open: None
+        };
+        Ok(client.post("/tables")
+            .header(ContentType::JSON)
+            .body(to_string(&table).map_err(|e| e.to_string())?)
+            .dispatch())
+    }
+
+    fn update_table(client: &Client, table_number: u32, update: rest_bodies::TableUpdate) -> Result<LocalResponse<'_>, String> {
+        Ok(client.patch(format!("/tables/{table_number}"))
+            .header(ContentType::JSON)
+            .body(to_string(&update).map_err(|e| e.to_string())?)
+            .dispatch())
+    }
+
+    fn get_menu_item_list(client: &Client, uri: &str) -> Result<Vec<rest_responses::MenuItem>, String> {
+        let response = client.get(uri.to_string()).dispatch();
+        Ok(from_str::<rest_responses::MenuItems>(&response.into_string().unwrap())
+            .map_err(|e| e.to_string())
+            ?.menu_items)
+    }
+
+    fn get_bill(client: &Client, table_number: u32) -> Result<rest_responses::Bill, String> {
+        let response = client.get(format!("/tables/{table_number}/bill")).dispatch();
+        from_str::<rest_responses::Bill>(&response.into_string().unwrap())
+            .map_err(|e| e.to_string())
+    }
+
+    fn get_split_bill(client: &Client, uri: String) -> Result<rest_responses::SplitBill, String> {
+        let response = client.get(uri).dispatch();
+        from_str::<rest_responses::SplitBill>(&response.into_string().unwrap())
+            .map_err(|e| e.to_string())
+    }
+
+    fn get_orders_page(client: &Client, uri: String) -> Result<rest_responses::Orders, String> {
+        let response = client.get(uri).dispatch();
+        from_str::<rest_responses::Orders>(&response.into_string().unwrap())
+            .map_err(|e| e.to_string())
+    }
+
+    // Reads events from an event stream until `count` have been received, returning each event's id, name and order.
+    // Events are only sent once they have happened, so this blocks until then.
+    fn read_events(response: &mut LocalResponse, count: usize) -> Result<Vec<(u64, String, rest_responses::Order)>, String> {
+        let mut text = String::new();
+        let mut buffer = [0; 1024];
+        // events end with a blank line, so anything after the last one is still being received
+        let complete_events = |text: &str| text[..text.rfind("\n\n").unwrap_or(0)]
+            .split("\n\n")
+            .filter(|event| event.contains("data:"))
+            .count();
+        while complete_events(&text) < count {
+            let read = response.read(&mut buffer).map_err(|e| e.to_string())?;
+            if read == 0 {
+                return Err("The event stream ended".to_string());
+            }
+            text.push_str(&String::from_utf8_lossy(&buffer[..read]));
+        }
+
+        text.split("\n\n")
+            .filter(|event| event.contains("data:"))
+            .take(count)
+            .map(|event| {
+                let field = |name: &str| event.lines()
+                    .find_map(|line| line.strip_prefix(name))
+                    .map(|value| value.trim().to_string())
+                    .unwrap_or_default();
+                Ok((
+                    field("id:").parse::<u64>().map_err(|e| e.to_string())?,
+                    field("event:"),
+                    from_str::<rest_responses::Order>(&field("data:")).map_err(|e| e.to_string())?))
+            })
+            .collect()
+    }
+
+    fn transfer_orders(client: &Client, table_number: u32, transfer: rest_bodies::Transfer) -> Result<LocalResponse<'_>, String> {
+        Ok(client.post(format!("/tables/{table_number}/transfer"))
+            .header(ContentType::JSON)
+            .body(to_string(&transfer).map_err(|e| e.to_string())?)
+            .dispatch())
+    }
+
+    // Adds a menu item with the given price, returning its id
+    fn create_priced_menu_item(client: &Client, name: &str, price: u32) -> Result<u32, String> {
+        let menu_item = from_str::<rest_responses::MenuItem>(&create_menu_item(client, name, 1, 1)?.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        update_menu_item(client, menu_item.id, rest_bodies::MenuItemUpdate {
+            name: None,
+            min_minutes_to_cook: None,
+            max_minutes_to_cook: None,
+            price: Some(price),
+            modifiers: None
+        })?;
+        Ok(menu_item.id)
     }
 
     #[test]
     fn setup_database_no_error() -> Result<(), String> {
         // setup
-        let database_connector = mock_database_connector::new()?;
+        let pool = temp_database::new()?;
+
+        // execution
+        let result = setup_database(&pool, &Seed::default());
+
+        // assertion
+        assert!(result.is_ok(), "Error setting up database: {}", result.unwrap_err());
+        Ok(())
+    }
+
+    #[test]
+    fn setup_database_with_seed_file() -> Result<(), String> {
+        // setup
+        let seed_file = tempfile::NamedTempFile::new().map_err(|e| e.to_string())?;
+        std::fs::write(seed_file.path(), r#"{ "menu_items": [
+            { "name": "Ramen", "min_minutes_to_cook": 6, "max_minutes_to_cook": 9, "price": 1100, "modifiers": [{ "name": "extra egg", "price_delta": 150 }] },
+            { "name": "Tea", "min_minutes_to_cook": 1, "max_minutes_to_cook": 2, "price": 200 }
+        ] }"#).map_err(|e| e.to_string())?;
+        let settings = Settings {
+            seed_file: Some(seed_file.path().display().to_string()),
+            ..Settings::default()
+        };
+
+        // execution
+        let seed = settings.seed()?;
+        let client = create_client_with(test_repository::with_seed(&seed)?, mock_clock::new(), settings)?;
+
+        // assertion
+        let menu_items = get_menu_item_list(&client, "/menu-items")?;
+        assert_eq!(menu_items.len(), 2);
+        assert_eq!(menu_items[0].name, "Ramen");
+        assert_eq!(menu_items[0].modifiers.len(), 1);
+        assert_eq!(menu_items[0].modifiers[0].price_delta, 150);
+        assert_eq!(menu_items[1].name, "Tea");
+        assert!(menu_items[1].modifiers.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn seed_outside_cook_time_range_error() -> Result<(), String> {
+        // setup
+        let settings = Settings {
+            cook_time_range: CookTimeRange { min_minutes: 3, max_minutes: 60 }, // Ice Cream takes 2 to 4 minutes
+            ..Settings::default()
+        };
+
+        // execution
+        let result = settings.seed();
+
+        // assertion
+        assert!(result.unwrap_err().contains("Ice Cream"));
+        Ok(())
+    }
+
+    #[test]
+    fn seed_file_missing_error() -> Result<(), String> {
+        // setup
+        let settings = Settings {
+            seed_file: Some("does/not/exist.json".to_string()),
+            ..Settings::default()
+        };
+
+        // execution
+        let result = settings.seed();
+
+        // assertion
+        assert!(result.unwrap_err().contains("does/not/exist.json"));
+        Ok(())
+    }
+
+    #[test]
+    fn settings_extracted_from_configuration() -> Result<(), String> {
+        // setup
+        let figment = Figment::new().merge(Toml::string("
+            storage = \"memory\"
+            database_pool_size = 2
+            seed_file = \"menu.json\"
+            kitchen_channel_enabled = false
+
+            [cook_time_range]
+            max_minutes = 90
+        "));
+
+        // execution
+        let settings = figment.extract::<Settings>().map_err(|e| e.to_string())?;
+
+        // assertion
+        assert_eq!(settings.storage, Storage::Memory);
+        assert_eq!(settings.database_pool_size, 2);
+        assert_eq!(settings.seed_file.as_deref(), Some("menu.json"));
+        assert!(!settings.kitchen_channel_enabled);
+        assert!(settings.event_stream_enabled);
+        assert_eq!(settings.cook_time_range, CookTimeRange { min_minutes: 0, max_minutes: 90 });
+        assert_eq!(settings.idempotency_key_ttl_seconds, Settings::default().idempotency_key_ttl_seconds);
+        assert!(settings.validate().is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn settings_validate_reports_every_problem() -> Result<(), String> {
+        // setup
+        let settings = Settings {
+            database_url: " ".to_string(),
+            database_pool_size: 0,
+            idempotency_purge_interval_seconds: 0,
+            order_event_purge_interval_seconds: 0,
+            cook_time_range: CookTimeRange { min_minutes: 30, max_minutes: 10 },
+            ..Settings::default()
+        };
+
+        // execution
+        let problems = settings.validate().unwrap_err();
+
+        // assertion
+        assert_eq!(problems.len(), 5);
+        assert!(problems.iter().any(|p| p.contains("database_url")));
+        assert!(problems.iter().any(|p| p.contains("database_pool_size")));
+        assert!(problems.iter().any(|p| p.contains("idempotency_purge_interval_seconds")));
+        assert!(problems.iter().any(|p| p.contains("order_event_purge_interval_seconds")));
+        assert!(problems.iter().any(|p| p.contains("cook_time_range")));
+        Ok(())
+    }
+
+    // A database with data in every table, as created before migrations were introduced
+    fn create_fixture_database() -> Result<DatabasePool, String> {
+        let pool = temp_database::new()?;
+        pool.open().map_err(|e| e.to_string())?
+            .execute_batch(include_str!("fixtures/database_before_migrations.sql"))
+            .map_err(|e| e.to_string())?;
+        Ok(pool)
+    }
+
+    #[test]
+    fn migrate_fixture_database_keeps_data() -> Result<(), String> {
+        // setup
+        let pool = create_fixture_database()?;
+
+        // execution
+        let applied = migrate(&pool, false).map_err(|e| e.to_string())?;
+
+        // assertion
+        assert_eq!(applied, SQLITE_MIGRATIONS.iter().collect::<Vec<_>>());
+        assert_eq!(get_schema_version(&pool).map_err(|e| e.to_string())?, latest_version(SQLITE_MIGRATIONS));
+
+        setup_database(&pool, &Seed::default()).map_err(|e| e.to_string())?;
+        let repository = TestRepository::Sqlite(SqliteRepository::new(pool));
+        assert_eq!(repository.orders().get_last_order_event_id().map_err(|e| e.to_string())?, 0);
+        let client = create_client_with(repository, mock_clock::new(), Settings::default())?;
+
+        // the existing menu is kept rather than replaced by the seed, with the new columns' defaults
+        let menu_items = get_menu_item_list(&client, "/menu-items?include_retired=true")?;
+        assert_eq!(menu_items.iter().map(|m| m.name.as_str()).collect::<Vec<_>>(), vec!["Hamburger", "Salad", "Sushi", "Ice Cream", "Soda"]);
+        assert!(menu_items.iter().all(|m| m.min_minutes_to_cook == 5 && m.max_minutes_to_cook == 15));
+        assert!(menu_items.iter().all(|m| m.price == 0 && !m.retired && m.modifiers.is_empty()));
+
+        let tables = client.get("/tables").dispatch();
+        let tables = from_str::<rest_responses::Tables>(&tables.into_string().unwrap()).map_err(|e| e.to_string())?.tables;
+        assert!(tables.is_empty());
+
+        let orders = get_orders_page(&client, "/orders".to_string())?.orders;
+        assert_eq!(orders.iter().map(|o| (o.id, o.table_number, o.menu_item_id, o.minutes_to_cook)).collect::<Vec<_>>(),
+            vec![(1, 1, 1, 12), (2, 1, 2, 5), (3, 2, 3, 8)]);
+        for order in &orders {
+            assert_eq!(order.status, OrderStatus::Placed);
+            assert!(order.created_at > 0);
+            assert_eq!(order.status_times.placed_at, Some(order.created_at));
+            assert_eq!(order.status_times.cooking_at, None);
+            assert_eq!(order.price, 0);
+            assert_eq!(order.seat, None);
+            assert!(order.modifiers.is_empty());
+            assert_eq!(order.note, None);
+        }
+
+        let bill = get_bill(&client, 1)?;
+        assert_eq!(bill.subtotal, 0);
+
+        // keys recorded before their responses were stored still stop the orders being added again
+        let keys = client.get("/admin/idempotency-keys").dispatch();
+        let keys = from_str::<rest_responses::IdempotencyKeys>(&keys.into_string().unwrap()).map_err(|e| e.to_string())?;
+        assert_eq!(keys.count, 1);
+
+        let retry_body = rest_bodies::Orders {
+            idempotency_key: None,
+            orders: vec![rest_bodies::Order { menu_item_id: 1, seat: None, modifiers: None, note: None }]
+        };
+        let retry_response = client.post("/tables/1/orders")
+            .header(ContentType::JSON)
+            .header(Header::new("Idempotency-Key", "tablet-1-request-1"))
+            .body(to_string(&retry_body).map_err(|e| e.to_string())?)
+            .dispatch();
+        assert_eq!(retry_response.status(), Status::Conflict);
+        assert_eq!(retry_response.headers().get_one("Idempotent-Replayed"), Some("true"));
+        assert_response_contains_error(retry_response)?;
+        assert_eq!(get_orders_page(&client, "/orders".to_string())?.orders.len(), 3);
+
+        // ids carry on from the existing data
+        let added = post_orders(&client, 1, vec![3])?;
+        assert_eq!(added[0].id, 4);
+        Ok(())
+    }
+
+    #[test]
+    fn migrate_dry_run_changes_nothing() -> Result<(), String> {
+        // setup
+        let pool = create_fixture_database()?;
+
+        // execution
+        let dry_run = migrate(&pool, true).map_err(|e| e.to_string())?;
+        let version_after_dry_run = get_schema_version(&pool).map_err(|e| e.to_string())?;
+        let applied = migrate(&pool, false).map_err(|e| e.to_string())?;
+
+        // assertion
+        assert_eq!(dry_run, SQLITE_MIGRATIONS.iter().collect::<Vec<_>>());
+        assert_eq!(version_after_dry_run, 0);
+        assert_eq!(applied, dry_run);
+        Ok(())
+    }
+
+    #[test]
+    fn migrate_up_to_date_database_applies_nothing() -> Result<(), String> {
+        // setup
+        let pool = temp_database::new()?;
+        setup_database(&pool, &Seed::default()).map_err(|e| e.to_string())?;
+
+        // execution
+        let dry_run = migrate(&pool, true).map_err(|e| e.to_string())?;
+        let applied = migrate(&pool, false).map_err(|e| e.to_string())?;
+
+        // assertion
+        assert!(dry_run.is_empty());
+        assert!(applied.is_empty());
+        assert_eq!(get_schema_version(&pool).map_err(|e| e.to_string())?, latest_version(SQLITE_MIGRATIONS));
+        Ok(())
+    }
+
+    #[test]
+    fn migrate_newer_database_error() -> Result<(), String> {
+        // setup
+        let pool = temp_database::new()?;
+        setup_database(&pool, &Seed::default()).map_err(|e| e.to_string())?;
+        let newer_version = latest_version(SQLITE_MIGRATIONS) + 1;
+        pool.open().map_err(|e| e.to_string())?
+            .execute(
+                "INSERT INTO schema_version (version, description, applied_at) VALUES (?1, 'From a newer server', 0)",
+                [newer_version])
+            .map_err(|e| e.to_string())?;
 
         // execution
-        let result = setup_database(&database_connector);
+        let result = setup_database(&pool, &Seed::default());
 
         // assertion
-        assert!(result.is_ok(), "Error setting up database: {}", result.unwrap_err().to_string());
+        assert!(result.is_err());
         Ok(())
     }
 
@@ -98,10 +619,16 @@ mod tests {
             idempotency_key: Option::None,
             orders: vec![
                 rest_bodies::Order {
-                    menu_item_id: 1
+                    menu_item_id: 1,
+                    seat: None,
+                    modifiers: None,
+                    note: None
                 },
                 rest_bodies::Order {
-                    menu_item_id: 2
+                    menu_item_id: 2,
+                    seat: None,
+                    modifiers: None,
+                    note: None
                 }
             ]
         };
@@ -123,6 +650,44 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn orders_post_concurrently() -> Result<(), String> {
+        // setup
+        let repository = test_repository::new()?;
+        let tablets = 30;
+
+        // execution
+        let results = std::thread::scope(|scope| {
+            let handles = (1..=tablets)
+                .map(|table_number| {
+                    let repository = &repository;
+                    scope.spawn(move || repository.orders().add_orders(
+                        &mock_clock::new(),
+                        &mock_random_number_generator::new(),
+                        &Settings::default(),
+                        table_number,
+                        rest_bodies::Orders {
+                            idempotency_key: None,
+                            orders: vec![
+                                rest_bodies::Order { menu_item_id: 1, seat: None, modifiers: None, note: None },
+                                rest_bodies::Order { menu_item_id: 2, seat: None, modifiers: None, note: None }
+                            ]
+                        }))
+                })
+                .collect::<Vec<_>>();
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect::<Vec<_>>()
+        });
+
+        // assertion
+        for result in results {
+            assert!(result.is_ok(), "Error adding orders: {}", result.unwrap_err());
+        }
+        let client = create_client_with(repository, mock_clock::new(), Settings::default())?;
+        let orders = get_orders_page(&client, "/orders".to_string())?;
+        assert_eq!(orders.orders.len(), 2 * tablets as usize);
+        Ok(())
+    }
+
     #[test]
     fn orders_get_single() -> Result<(), String> {
         // setup
@@ -131,10 +696,16 @@ mod tests {
             idempotency_key: Option::None,
             orders: vec![
                 rest_bodies::Order {
-                    menu_item_id: 1
+                    menu_item_id: 1,
+                    seat: None,
+                    modifiers: None,
+                    note: None
                 },
                 rest_bodies::Order {
-                    menu_item_id: 2
+                    menu_item_id: 2,
+                    seat: None,
+                    modifiers: None,
+                    note: None
                 }
             ]
         };
@@ -172,10 +743,16 @@ mod tests {
             idempotency_key: Option::None,
             orders: vec![
                 rest_bodies::Order {
-                    menu_item_id: 1
+                    menu_item_id: 1,
+                    seat: None,
+                    modifiers: None,
+                    note: None
                 },
                 rest_bodies::Order {
-                    menu_item_id: 2
+                    menu_item_id: 2,
+                    seat: None,
+                    modifiers: None,
+                    note: None
                 }
             ]
         };
@@ -220,7 +797,10 @@ mod tests {
             idempotency_key: Option::None,
             orders: vec![
                 rest_bodies::Order {
-                    menu_item_id: 1
+                    menu_item_id: 1,
+                    seat: None,
+                    modifiers: None,
+                    note: None
                 }
             ]
         };
@@ -250,218 +830,2954 @@ mod tests {
     }
 
     #[test]
-    fn menu_items_error_500() -> Result<(), String> {
+    fn orders_delete_does_not_reuse_id() -> Result<(), String> {
         // setup
-        let client = create_client_without_setup()?;
+        let client = create_client()?;
+        let orders = post_orders(&client, 1, vec![1, 2])?;
+        let deleted_id = orders[1].id;
+        let delete_response = client.delete(format!("/tables/1/orders/{deleted_id}")).dispatch();
+        assert_eq!(delete_response.status(), Status::NoContent);
 
         // execution
-        let req = client.get("/menu-items");
-        let response = req.dispatch();
-        
+        let added = post_orders(&client, 1, vec![3])?;
+
         // assertion
-        assert_eq!(response.status(), Status::InternalServerError);
-        assert_response_contains_error(response)?;
+        assert!(added[0].id > deleted_id);
         Ok(())
     }
 
     #[test]
-    fn orders_get_multiple_error_500() -> Result<(), String> {
+    fn orders_delete_error_404() -> Result<(), String> {
         // setup
-        let client = create_client_without_setup()?;
+        let client = create_client()?;
+        let order_id = post_orders(&client, 1, vec![1])?[0].id;
 
         // execution
-        let req = client.get("/tables/1/orders");
-        let response = req.dispatch();
-        
+        let missing_response = client.delete("/tables/1/orders/999").dispatch();
+        let other_table_response = client.delete(format!("/tables/2/orders/{order_id}")).dispatch();
+
         // assertion
-        assert_eq!(response.status(), Status::InternalServerError);
-        assert_response_contains_error(response)?;
+        assert_eq!(missing_response.status(), Status::NotFound);
+        assert_response_contains_error(missing_response)?;
+        assert_eq!(other_table_response.status(), Status::NotFound);
+        assert_response_contains_error(other_table_response)?;
+        // the order on the other table was not deleted
+        assert_eq!(client.get(format!("/tables/1/orders/{order_id}")).dispatch().status(), Status::Ok);
         Ok(())
     }
 
     #[test]
-    fn orders_post_error_400() -> Result<(), String> {
+    fn orders_delete_missing_ok() -> Result<(), String> {
         // setup
         let client = create_client()?;
+        let order_id = post_orders(&client, 1, vec![1])?[0].id;
 
         // execution
-        let post_req = client.post("/tables/1/orders")
-            .header(ContentType::JSON)
-            .body("{ \"orders\": [{ \"idempotency_key\": 1 " ); // poorly formatted body
-        let post_response = post_req.dispatch();
-        
+        let delete_response1 = client.delete(format!("/tables/1/orders/{order_id}?missing_ok=true")).dispatch();
+        let delete_response2 = client.delete(format!("/tables/1/orders/{order_id}?missing_ok=true")).dispatch();
+        let delete_response3 = client.delete(format!("/tables/1/orders/{order_id}?missing_ok=false")).dispatch();
+
         // assertion
-        assert_eq!(post_response.status(), Status::BadRequest);
-        assert_response_contains_error(post_response)?;
+        assert_eq!(delete_response1.status(), Status::NoContent);
+        assert_eq!(delete_response2.status(), Status::NoContent);
+        assert_eq!(delete_response3.status(), Status::NotFound);
         Ok(())
     }
 
     #[test]
-    fn orders_post_error_409() -> Result<(), String> {
+    fn orders_delete_all() -> Result<(), String> {
         // setup
         let client = create_client()?;
-        let orders = rest_bodies::Orders {
-            idempotency_key: Option::Some("test".to_string()),
-            orders: vec![
-                rest_bodies::Order {
-                    menu_item_id: 1
-                }
-            ]
-        };
+        let orders = post_orders(&client, 1, vec![1, 2, 3])?;
+        post_orders(&client, 2, vec![1])?;
 
         // execution
-        let post_req1 = client.post("/tables/1/orders")
-            .header(ContentType::JSON)
-            .body(to_string(&orders).map_err(|e| e.to_string())?);
-        let post_response1 = post_req1.dispatch();
+        let delete_response = client.delete("/tables/1/orders").dispatch();
 
-        // post the same request a second time
-        let post_req2 = client.post("/tables/1/orders")
-            .header(ContentType::JSON)
-            .body(to_string(&orders).map_err(|e| e.to_string())?);
-        let post_response2 = post_req2.dispatch();
-        
         // assertion
-        assert_eq!(post_response1.status(), Status::Ok);
-        assert_eq!(post_response2.status(), Status::Conflict);
-        assert_response_contains_error(post_response2)?;
+        assert_eq!(delete_response.status(), Status::Ok);
+        let deleted = from_str::<rest_responses::Orders>(&delete_response.into_string().unwrap())
+            .map_err(|e| e.to_string())?.orders;
+        assert_eq!(deleted.iter().map(|o| o.id).collect::<Vec<u32>>(), orders.iter().map(|o| o.id).collect::<Vec<u32>>());
+        let table_1_orders = from_str::<rest_responses::Orders>(&client.get("/tables/1/orders").dispatch().into_string().unwrap())
+            .map_err(|e| e.to_string())?.orders;
+        assert!(table_1_orders.is_empty());
+        // other tables are not affected
+        let table_2_orders = from_str::<rest_responses::Orders>(&client.get("/tables/2/orders").dispatch().into_string().unwrap())
+            .map_err(|e| e.to_string())?.orders;
+        assert_eq!(table_2_orders.len(), 1);
         Ok(())
     }
 
     #[test]
-    fn orders_post_error_422() -> Result<(), String> {
+    fn orders_delete_selected() -> Result<(), String> {
         // setup
         let client = create_client()?;
-        let orders = rest_bodies::Orders {
-            idempotency_key: Option::None,
-            orders: vec![
-                rest_bodies::Order {
-                    menu_item_id: 999
-                }
-            ]
-        };
+        let orders = post_orders(&client, 1, vec![1, 2, 3])?;
 
         // execution
-        let post_req = client.post("/tables/1/orders")
-            .header(ContentType::JSON)
-            .body(to_string(&orders).map_err(|e| e.to_string())?);
-        let post_response = post_req.dispatch();
-        
+        let delete_response = client.delete(format!("/tables/1/orders?ids={},{}", orders[0].id, orders[2].id)).dispatch();
+
+        // assertion
+        assert_eq!(delete_response.status(), Status::Ok);
+        let deleted = from_str::<rest_responses::Orders>(&delete_response.into_string().unwrap())
+            .map_err(|e| e.to_string())?.orders;
+        assert_eq!(deleted.iter().map(|o| o.id).collect::<Vec<u32>>(), vec![orders[0].id, orders[2].id]);
+        let remaining = from_str::<rest_responses::Orders>(&client.get("/tables/1/orders").dispatch().into_string().unwrap())
+            .map_err(|e| e.to_string())?.orders;
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, orders[1].id);
+        Ok(())
+    }
+
+    #[test]
+    fn orders_delete_selected_error_404() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let orders = post_orders(&client, 1, vec![1, 2])?;
+        let other_table_order = post_orders(&client, 2, vec![1])?[0].id;
+
+        // execution
+        let delete_response = client.delete(format!("/tables/1/orders?ids={},{other_table_order}", orders[0].id)).dispatch();
+
+        // assertion
+        assert_eq!(delete_response.status(), Status::NotFound);
+        assert_response_contains_error(delete_response)?;
+        // nothing was deleted
+        let remaining = from_str::<rest_responses::Orders>(&client.get("/tables/1/orders").dispatch().into_string().unwrap())
+            .map_err(|e| e.to_string())?.orders;
+        assert_eq!(remaining.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn orders_delete_selected_error_422() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        post_orders(&client, 1, vec![1])?;
+
+        // execution
+        let delete_response = client.delete("/tables/1/orders?ids=1,abc").dispatch();
+
+        // assertion
+        assert_eq!(delete_response.status(), Status::UnprocessableEntity);
+        assert_response_contains_error(delete_response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn orders_delete_idempotent_replay() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let order_id = post_orders(&client, 1, vec![1, 2])?[0].id;
+
+        // execution
+        let delete_response1 = client.delete(format!("/tables/1/orders/{order_id}"))
+            .header(Header::new("Idempotency-Key", "delete-key"))
+            .dispatch();
+        let delete_response2 = client.delete(format!("/tables/1/orders/{order_id}"))
+            .header(Header::new("Idempotency-Key", "delete-key"))
+            .dispatch();
+
+        // assertion
+        assert_eq!(delete_response1.status(), Status::NoContent);
+        assert_eq!(delete_response1.headers().get_one("Idempotent-Replayed"), None);
+        assert_eq!(delete_response2.status(), Status::NoContent);
+        assert_eq!(delete_response2.headers().get_one("Idempotent-Replayed"), Some("true"));
+        Ok(())
+    }
+
+    #[test]
+    fn orders_delete_error_422_reused_idempotency_key() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let orders = post_orders(&client, 1, vec![1, 2])?;
+        client.delete(format!("/tables/1/orders/{}", orders[0].id))
+            .header(Header::new("Idempotency-Key", "delete-key"))
+            .dispatch();
+
+        // execution
+        let delete_response = client.delete(format!("/tables/1/orders/{}", orders[1].id))
+            .header(Header::new("Idempotency-Key", "delete-key"))
+            .dispatch();
+
+        // assertion
+        assert_eq!(delete_response.status(), Status::UnprocessableEntity);
+        assert_response_contains_error(delete_response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn idempotency_keys_scoped_to_method_and_route() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let order_id = post_orders(&client, 1, vec![1])?[0].id;
+        post_order_with_key(&client, 1, "shared-key")?;
+
+        // execution
+        let delete_response = client.delete(format!("/tables/1/orders/{order_id}"))
+            .header(Header::new("Idempotency-Key", "shared-key"))
+            .dispatch();
+
+        // assertion
+        assert_eq!(delete_response.status(), Status::NoContent);
+        assert_eq!(delete_response.headers().get_one("Idempotent-Replayed"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn idempotency_keys_replay_client_errors() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let body = to_string(&rest_bodies::Orders {
+            idempotency_key: Option::None,
+            orders: vec![rest_bodies::Order {
+                menu_item_id: 999,
+                seat: None,
+                modifiers: None,
+                note: None
+            }]
+        }).map_err(|e| e.to_string())?;
+
+        // execution
+        let post_response1 = client.post("/tables/1/orders")
+            .header(ContentType::JSON)
+            .header(Header::new("Idempotency-Key", "invalid-order"))
+            .body(&body)
+            .dispatch();
+        let post_response2 = client.post("/tables/1/orders")
+            .header(ContentType::JSON)
+            .header(Header::new("Idempotency-Key", "invalid-order"))
+            .body(&body)
+            .dispatch();
+
+        // assertion
+        assert_eq!(post_response1.status(), Status::UnprocessableEntity);
+        assert_eq!(post_response2.status(), Status::UnprocessableEntity);
+        assert_eq!(post_response2.headers().get_one("Idempotent-Replayed"), Some("true"));
+        assert_response_contains_error(post_response2)?;
+        Ok(())
+    }
+
+    #[test]
+    fn menu_items_error_500() -> Result<(), String> {
+        // setup
+        let client = create_client_without_setup()?;
+
+        // execution
+        let req = client.get("/menu-items");
+        let response = req.dispatch();
+        
+        // assertion
+        assert_eq!(response.status(), Status::InternalServerError);
+        assert_response_contains_error(response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn orders_get_pages() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let orders = post_orders(&client, 1, vec![1, 2, 3, 4, 5])?;
+
+        // execution
+        let page1 = get_orders_page(&client, "/tables/1/orders?limit=2".to_string())?;
+        let page2 = get_orders_page(&client, format!("/tables/1/orders?limit=2&cursor={}", page1.next_cursor.clone().unwrap()))?;
+        let page3 = get_orders_page(&client, format!("/tables/1/orders?limit=2&cursor={}", page2.next_cursor.clone().unwrap()))?;
+
+        // assertion
+        let ids = |page: &rest_responses::Orders| page.orders.iter().map(|o| o.id).collect::<Vec<u32>>();
+        assert_eq!(ids(&page1), vec![orders[0].id, orders[1].id]);
+        assert_eq!(ids(&page2), vec![orders[2].id, orders[3].id]);
+        assert_eq!(ids(&page3), vec![orders[4].id]);
+        assert_eq!(page3.next_cursor, None);
+        Ok(())
+    }
+
+    #[test]
+    fn orders_get_sorted_by_minutes_to_cook() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let orders = post_orders(&client, 1, vec![1, 1, 1, 1])?;
+        for (order, minutes_to_cook) in orders.iter().zip([20, 5, 20, 10]) {
+            patch_order(&client, 1, order.id, rest_bodies::OrderUpdate {
+                menu_item_id: None,
+                table_number: None,
+                minutes_to_cook: Some(minutes_to_cook),
+                seat: None,
+                modifiers: None,
+                note: None
+            })?;
+        }
+
+        // execution
+        let page1 = get_orders_page(&client, "/tables/1/orders?sort=minutes_to_cook&limit=3".to_string())?;
+        let page2 = get_orders_page(&client, format!("/tables/1/orders?sort=minutes_to_cook&limit=3&cursor={}", page1.next_cursor.clone().unwrap()))?;
+
+        // assertion
+        // orders with the same minutes to cook are ordered by id
+        let ids = page1.orders.iter().chain(page2.orders.iter()).map(|o| o.id).collect::<Vec<u32>>();
+        assert_eq!(ids, vec![orders[1].id, orders[3].id, orders[0].id, orders[2].id]);
+        assert_eq!(page1.orders.len(), 3);
+        assert_eq!(page2.next_cursor, None);
+        Ok(())
+    }
+
+    #[test]
+    fn orders_get_filtered() -> Result<(), String> {
+        // setup
+        let clock = mock_clock::new();
+        let client = create_client_with_clock(clock.clone())?;
+        let early_orders = post_orders(&client, 1, vec![1, 2])?;
+        let created_after = early_orders[0].created_at;
+        clock.advance_seconds(60);
+        let late_orders = post_orders(&client, 1, vec![1, 2])?;
+        put_status(&client, 1, late_orders[0].id, OrderStatus::Cooking)?;
+
+        // execution
+        let by_menu_item = get_orders_page(&client, "/tables/1/orders?menu_item_id=2".to_string())?;
+        let by_status = get_orders_page(&client, "/tables/1/orders?status=cooking".to_string())?;
+        let by_created_after = get_orders_page(&client, format!("/tables/1/orders?created_after={created_after}"))?;
+        let combined = get_orders_page(&client, format!("/tables/1/orders?created_after={created_after}&menu_item_id=1&status=placed"))?;
+
+        // assertion
+        let ids = |page: &rest_responses::Orders| page.orders.iter().map(|o| o.id).collect::<Vec<u32>>();
+        assert_eq!(ids(&by_menu_item), vec![early_orders[1].id, late_orders[1].id]);
+        assert_eq!(ids(&by_status), vec![late_orders[0].id]);
+        assert_eq!(ids(&by_created_after), vec![late_orders[0].id, late_orders[1].id]);
+        assert!(combined.orders.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn orders_get_error_422() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+
+        // execution
+        let responses = [
+            client.get("/tables/1/orders?sort=price").dispatch(),
+            client.get("/tables/1/orders?limit=0").dispatch(),
+            client.get("/tables/1/orders?status=eaten").dispatch(),
+            client.get("/tables/1/orders?sort=minutes_to_cook&cursor=5").dispatch(),
+            client.get("/tables/1/orders?limit=abc").dispatch()
+        ];
+
+        // assertion
+        for response in responses {
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+            assert_response_contains_error(response)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn orders_get_multiple_error_500() -> Result<(), String> {
+        // setup
+        let client = create_client_without_setup()?;
+
+        // execution
+        let req = client.get("/tables/1/orders");
+        let response = req.dispatch();
+        
+        // assertion
+        assert_eq!(response.status(), Status::InternalServerError);
+        assert_response_contains_error(response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn orders_get_all_tables() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let table_1_orders = post_orders(&client, 1, vec![1])?;
+        let table_2_orders = post_orders(&client, 2, vec![2])?;
+        let table_5_orders = post_orders(&client, 5, vec![3])?;
+
+        // execution
+        let all = get_orders_page(&client, "/orders".to_string())?;
+        let selected = get_orders_page(&client, "/orders?tables=1,4-5".to_string())?;
+
+        // assertion
+        let orders = |page: &rest_responses::Orders| page.orders.iter().map(|o| (o.id, o.table_number)).collect::<Vec<(u32, u32)>>();
+        assert_eq!(orders(&all), vec![(table_1_orders[0].id, 1), (table_2_orders[0].id, 2), (table_5_orders[0].id, 5)]);
+        assert_eq!(orders(&selected), vec![(table_1_orders[0].id, 1), (table_5_orders[0].id, 5)]);
+        assert_eq!(all.next_cursor, None);
+        Ok(())
+    }
+
+    #[test]
+    fn orders_get_all_tables_filtered() -> Result<(), String> {
+        // setup
+        let clock = mock_clock::new();
+        let client = create_client_with_clock(clock.clone())?;
+        let early_orders = post_orders(&client, 1, vec![1, 2])?;
+        clock.advance_seconds(60);
+        let late_orders = post_orders(&client, 2, vec![1, 2, 3])?;
+        let created_before = late_orders[0].created_at;
+        clock.advance_seconds(60);
+        let latest_orders = post_orders(&client, 3, vec![1])?;
+        put_status(&client, 2, late_orders[0].id, OrderStatus::Cooking)?;
+        put_status(&client, 2, late_orders[1].id, OrderStatus::Cooking)?;
+        put_status(&client, 2, late_orders[1].id, OrderStatus::Ready)?;
+
+        // execution
+        let by_menu_items = get_orders_page(&client, "/orders?menu_item_id=2,3".to_string())?;
+        let by_statuses = get_orders_page(&client, "/orders?status=cooking,ready".to_string())?;
+        let by_window = get_orders_page(&client, format!("/orders?created_after={}&created_before={}", early_orders[0].created_at, latest_orders[0].created_at))?;
+        let before = get_orders_page(&client, format!("/orders?created_before={created_before}"))?;
+        let first_page = get_orders_page(&client, "/orders?tables=1-2&limit=3".to_string())?;
+        let second_page = get_orders_page(&client, format!("/orders?tables=1-2&limit=3&cursor={}", first_page.next_cursor.clone().unwrap_or_default()))?;
+
+        // assertion
+        let ids = |page: &rest_responses::Orders| page.orders.iter().map(|o| o.id).collect::<Vec<u32>>();
+        assert_eq!(ids(&by_menu_items), vec![early_orders[1].id, late_orders[1].id, late_orders[2].id]);
+        assert_eq!(ids(&by_statuses), vec![late_orders[0].id, late_orders[1].id]);
+        assert_eq!(ids(&by_window), late_orders.iter().map(|o| o.id).collect::<Vec<u32>>());
+        assert_eq!(ids(&before), vec![early_orders[0].id, early_orders[1].id]);
+        assert_eq!(ids(&first_page), vec![early_orders[0].id, early_orders[1].id, late_orders[0].id]);
+        assert_eq!(ids(&second_page), vec![late_orders[1].id, late_orders[2].id]);
+        assert_eq!(second_page.next_cursor, None);
+        Ok(())
+    }
+
+    #[test]
+    fn orders_get_all_tables_error_422() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+
+        // execution
+        let responses = [
+            client.get("/orders?tables=one").dispatch(),
+            client.get("/orders?tables=5-2").dispatch(),
+            client.get("/orders?tables=1-").dispatch(),
+            client.get("/orders?menu_item_id=1,x").dispatch(),
+            client.get("/orders?status=placed,eaten").dispatch(),
+            client.get("/orders?created_before=yesterday").dispatch()
+        ];
+
+        // assertion
+        for response in responses {
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+            assert_response_contains_error(response)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn events_stream_order_changes() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let orders = post_orders(&client, 1, vec![1, 2])?;
+        put_status(&client, 1, orders[0].id, OrderStatus::Cooking)?;
+        client.delete(format!("/tables/1/orders/{}", orders[1].id)).dispatch();
+        client.post("/tables/1/checkout").dispatch();
+
+        // execution
+        let mut response = client.get("/events")
+            .header(Header::new("Last-Event-ID", "0"))
+            .dispatch();
+        let events = read_events(&mut response, 5)?;
+
+        // assertion
+        let summary = events.iter().map(|(_, name, order)| (name.as_str(), order.id, order.status)).collect::<Vec<_>>();
+        assert_eq!(summary, vec![
+            ("order_created", orders[0].id, OrderStatus::Placed),
+            ("order_created", orders[1].id, OrderStatus::Placed),
+            ("order_updated", orders[0].id, OrderStatus::Cooking),
+            ("order_deleted", orders[1].id, OrderStatus::Placed),
+            ("order_deleted", orders[0].id, OrderStatus::Cooking)]);
+        assert!(events.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        Ok(())
+    }
+
+    #[test]
+    fn events_resume_from_last_event_id() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let orders = post_orders(&client, 1, vec![1, 2, 3])?;
+        let mut first_response = client.get("/events")
+            .header(Header::new("Last-Event-ID", "0"))
+            .dispatch();
+        let first_events = read_events(&mut first_response, 1)?;
+
+        // execution
+        let mut resumed_response = client.get("/events")
+            .header(Header::new("Last-Event-ID", first_events[0].0.to_string()))
+            .dispatch();
+        let resumed_events = read_events(&mut resumed_response, 2)?;
+
+        // assertion
+        assert_eq!(first_events[0].2.id, orders[0].id);
+        assert_eq!(resumed_events.iter().map(|(_, _, order)| order.id).collect::<Vec<u32>>(), vec![orders[1].id, orders[2].id]);
+        Ok(())
+    }
+
+    #[test]
+    fn events_start_with_next_change() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        post_orders(&client, 1, vec![1])?;
+        let mut response = client.get("/events").dispatch();
+
+        // execution
+        let orders = post_orders(&client, 1, vec![2])?;
+        let events = read_events(&mut response, 1)?;
+
+        // assertion
+        assert_eq!(events[0].1, "order_created");
+        assert_eq!(events[0].2.id, orders[0].id);
+        Ok(())
+    }
+
+    #[test]
+    fn events_filtered_by_table() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let table_1_orders = post_orders(&client, 1, vec![1])?;
+        let table_2_orders = post_orders(&client, 2, vec![1, 2])?;
+        transfer_orders(&client, 2, rest_bodies::Transfer {
+            table_number: 3,
+            order_ids: Some(vec![table_2_orders[1].id])
+        })?;
+        let table_4_orders = post_orders(&client, 4, vec![1])?;
+
+        // execution
+        let mut response = client.get("/events?tables=1,3-4")
+            .header(Header::new("Last-Event-ID", "0"))
+            .dispatch();
+        let events = read_events(&mut response, 3)?;
+
+        // assertion
+        let summary = events.iter().map(|(_, name, order)| (name.as_str(), order.id, order.table_number)).collect::<Vec<_>>();
+        assert_eq!(summary, vec![
+            ("order_created", table_1_orders[0].id, 1),
+            ("order_updated", table_2_orders[1].id, 3),
+            ("order_created", table_4_orders[0].id, 4)]);
+        Ok(())
+    }
+
+    #[test]
+    fn events_error_400_and_422() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+
+        // execution
+        let bad_last_event_id = client.get("/events")
+            .header(Header::new("Last-Event-ID", "latest"))
+            .dispatch();
+        let bad_tables = client.get("/events?tables=3-1").dispatch();
+
+        // assertion
+        assert_eq!(bad_last_event_id.status(), Status::BadRequest);
+        assert_eq!(bad_tables.status(), Status::UnprocessableEntity);
+        assert_response_contains_error(bad_tables)?;
+        Ok(())
+    }
+
+    #[rocket::async_test]
+    async fn kitchen_channel_subscribe_and_ack() -> Result<(), String> {
+        // setup
+        let repository = test_repository::new()?;
+        let orders = repository.orders().add_orders(
+            &mock_clock::new(),
+            &mock_random_number_generator::new(),
+            &Settings::default(),
+            1,
+            rest_bodies::Orders {
+                idempotency_key: None,
+                orders: vec![rest_bodies::Order { menu_item_id: 1, seat: None, modifiers: None, note: None }]
+            }).map_err(|e| e.to_string())?.orders;
+        let order_id = orders[0].id;
+        let (port, shutdown) = launch_server(repository).await?;
+        let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://127.0.0.1:{port}/kitchen")).await.map_err(|e| e.to_string())?;
+
+        // execution
+        send_kitchen_request(&mut socket, "{ \"type\": \"subscribe\", \"tables\": \"1-2\", \"last_event_id\": 0 }").await?;
+        let subscribed = next_kitchen_message(&mut socket).await?;
+        let created = next_kitchen_message(&mut socket).await?;
+        send_kitchen_request(&mut socket, &format!("{{ \"type\": \"ack\", \"table_number\": 1, \"order_id\": {order_id}, \"status\": \"started\" }}")).await?;
+        let acked = next_kitchen_message(&mut socket).await?;
+        let updated = next_kitchen_message(&mut socket).await?;
+        send_kitchen_request(&mut socket, &format!("{{ \"type\": \"ack\", \"table_number\": 1, \"order_id\": {order_id}, \"status\": \"started\" }}")).await?;
+        let invalid_ack = next_kitchen_message(&mut socket).await?;
+        send_kitchen_request(&mut socket, "{ \"type\": \"cook\" }").await?;
+        let unknown = next_kitchen_message(&mut socket).await?;
+        shutdown.notify();
+
+        // assertion
+        assert!(matches!(subscribed, KitchenMessage::Subscribed { last_event_id: 0 }));
+        match created {
+            KitchenMessage::Event { event, order, .. } => {
+                assert_eq!(event, "order_created");
+                assert_eq!(order.id, order_id);
+            },
+            x => return Err(format!("Expected an event, got {x:?}"))
+        }
+        match acked {
+            KitchenMessage::Acked { order } => assert_eq!(order.status, OrderStatus::Cooking),
+            x => return Err(format!("Expected an ack, got {x:?}"))
+        }
+        match updated {
+            KitchenMessage::Event { event, order, .. } => {
+                assert_eq!(event, "order_updated");
+                assert_eq!(order.status, OrderStatus::Cooking);
+            },
+            x => return Err(format!("Expected an event, got {x:?}"))
+        }
+        match invalid_ack {
+            KitchenMessage::Error { error, order_id: error_order_id } => {
+                assert_eq!(error, "An order cannot go from cooking to cooking.");
+                assert_eq!(error_order_id, Some(order_id));
+            },
+            x => return Err(format!("Expected an error, got {x:?}"))
+        }
+        assert!(matches!(unknown, KitchenMessage::Error { order_id: None, .. }));
+        Ok(())
+    }
+
+    #[test]
+    fn orders_post_error_400() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+
+        // execution
+        let post_req = client.post("/tables/1/orders")
+            .header(ContentType::JSON)
+            .body("{ \"orders\": [{ \"idempotency_key\": 1 " ); // poorly formatted body
+        let post_response = post_req.dispatch();
+        
+        // assertion
+        assert_eq!(post_response.status(), Status::BadRequest);
+        assert_response_contains_error(post_response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn orders_post_idempotent_replay() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let orders = rest_bodies::Orders {
+            idempotency_key: Option::Some("test".to_string()),
+            orders: vec![
+                rest_bodies::Order {
+                    menu_item_id: 1,
+                    seat: None,
+                    modifiers: None,
+                    note: None
+                }
+            ]
+        };
+
+        // execution
+        let post_req1 = client.post("/tables/1/orders")
+            .header(ContentType::JSON)
+            .body(to_string(&orders).map_err(|e| e.to_string())?);
+        let post_response1 = post_req1.dispatch();
+
+        // post the same request a second time
+        let post_req2 = client.post("/tables/1/orders")
+            .header(ContentType::JSON)
+            .body(to_string(&orders).map_err(|e| e.to_string())?);
+        let post_response2 = post_req2.dispatch();
+        
+        // assertion
+        assert_eq!(post_response1.status(), Status::Ok);
+        assert_eq!(post_response1.headers().get_one("Idempotent-Replayed"), None);
+        assert_eq!(post_response2.status(), Status::Ok);
+        assert_eq!(post_response2.headers().get_one("Idempotent-Replayed"), Some("true"));
+        let orders1 = from_str::<rest_responses::Orders>(&post_response1.into_string().unwrap())
+            .map_err(|e| e.to_string())?.orders;
+        let orders2 = from_str::<rest_responses::Orders>(&post_response2.into_string().unwrap())
+            .map_err(|e| e.to_string())?.orders;
+        assert_eq!(orders1.len(), 1);
+        assert_eq!(orders2.len(), 1);
+        assert_eq!(orders1[0].id, orders2[0].id);
+
+        // the orders were only added once
+        let get_response = client.get("/tables/1/orders").dispatch();
+        let table_orders = from_str::<rest_responses::Orders>(&get_response.into_string().unwrap())
+            .map_err(|e| e.to_string())?.orders;
+        assert_eq!(table_orders.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn orders_post_error_422_reused_idempotency_key() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        post_order_with_key(&client, 1, "test")?;
+        let orders = rest_bodies::Orders {
+            idempotency_key: Option::Some("test".to_string()),
+            orders: vec![
+                rest_bodies::Order {
+                    menu_item_id: 2,
+                    seat: None,
+                    modifiers: None,
+                    note: None
+                }
+            ]
+        };
+
+        // execution
+        let post_response = client.post("/tables/1/orders")
+            .header(ContentType::JSON)
+            .body(to_string(&orders).map_err(|e| e.to_string())?)
+            .dispatch();
+        // the same orders for another table are a different request
+        let other_table_response = post_order_with_key(&client, 2, "test")?;
+
+        // assertion
+        assert_eq!(post_response.status(), Status::UnprocessableEntity);
+        assert_response_contains_error(post_response)?;
+        assert_eq!(other_table_response.status(), Status::UnprocessableEntity);
+        assert_response_contains_error(other_table_response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn orders_post_idempotency_key_header() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let body = to_string(&rest_bodies::Orders {
+            idempotency_key: Option::None,
+            orders: vec![rest_bodies::Order {
+                menu_item_id: 1,
+                seat: None,
+                modifiers: None,
+                note: None
+            }]
+        }).map_err(|e| e.to_string())?;
+
+        // execution
+        let post_response1 = client.post("/tables/1/orders")
+            .header(ContentType::JSON)
+            .header(Header::new("Idempotency-Key", "header-key"))
+            .body(&body)
+            .dispatch();
+        let post_response2 = client.post("/tables/1/orders")
+            .header(ContentType::JSON)
+            .header(Header::new("Idempotency-Key", "header-key"))
+            .body(&body)
+            .dispatch();
+
+        // assertion
+        assert_eq!(post_response1.status(), Status::Ok);
+        assert_eq!(post_response1.headers().get_one("Idempotent-Replayed"), None);
+        assert_eq!(post_response2.status(), Status::Ok);
+        assert_eq!(post_response2.headers().get_one("Idempotent-Replayed"), Some("true"));
+        Ok(())
+    }
+
+    #[test]
+    fn orders_post_idempotency_key_header_precedence() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        post_order_with_key(&client, 1, "body-key")?;
+        let body = to_string(&rest_bodies::Orders {
+            idempotency_key: Option::Some("body-key".to_string()),
+            orders: vec![rest_bodies::Order {
+                menu_item_id: 1,
+                seat: None,
+                modifiers: None,
+                note: None
+            }]
+        }).map_err(|e| e.to_string())?;
+
+        // execution
+        // the header's key is used, so the body's key is not treated as a retry
+        let post_response = client.post("/tables/1/orders")
+            .header(ContentType::JSON)
+            .header(Header::new("Idempotency-Key", "header-key"))
+            .body(&body)
+            .dispatch();
+        let header_key_response = post_order_with_key(&client, 1, "header-key")?;
+
+        // assertion
+        assert_eq!(post_response.status(), Status::Ok);
+        assert_eq!(post_response.headers().get_one("Idempotent-Replayed"), None);
+        assert_eq!(header_key_response.headers().get_one("Idempotent-Replayed"), Some("true"));
+        Ok(())
+    }
+
+    #[test]
+    fn orders_post_error_400_empty_idempotency_key_header() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let body = to_string(&rest_bodies::Orders {
+            idempotency_key: Option::None,
+            orders: Vec::new()
+        }).map_err(|e| e.to_string())?;
+
+        // execution
+        let post_response = client.post("/tables/1/orders")
+            .header(ContentType::JSON)
+            .header(Header::new("Idempotency-Key", ""))
+            .body(&body)
+            .dispatch();
+
+        // assertion
+        assert_eq!(post_response.status(), Status::BadRequest);
+        assert_response_contains_error(post_response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn orders_post_error_422() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let orders = rest_bodies::Orders {
+            idempotency_key: Option::None,
+            orders: vec![
+                rest_bodies::Order {
+                    menu_item_id: 999,
+                    seat: None,
+                    modifiers: None,
+                    note: None
+                }
+            ]
+        };
+
+        // execution
+        let post_req = client.post("/tables/1/orders")
+            .header(ContentType::JSON)
+            .body(to_string(&orders).map_err(|e| e.to_string())?);
+        let post_response = post_req.dispatch();
+        
+        // assertion
+        assert_eq!(post_response.status(), Status::UnprocessableEntity);
+        assert_response_contains_error(post_response)?;
+        Ok(())
+    }
+    
+
+    #[test]
+    fn orders_post_error_500() -> Result<(), String> {
+        // setup
+        let client = create_client_without_setup()?;
+        let orders = rest_bodies::Orders {
+            idempotency_key: Option::None,
+            orders: vec![
+                rest_bodies::Order {
+                    menu_item_id: 1,
+                    seat: None,
+                    modifiers: None,
+                    note: None
+                },
+                rest_bodies::Order {
+                    menu_item_id: 2,
+                    seat: None,
+                    modifiers: None,
+                    note: None
+                }
+            ]
+        };
+
+        // execution
+        let post_req = client.post("/tables/1/orders")
+            .header(ContentType::JSON)
+            .body(to_string(&orders).map_err(|e| e.to_string())?);
+        let post_response = post_req.dispatch();
+        
+        // assertion
+        assert_eq!(post_response.status(), Status::InternalServerError);
+        assert_response_contains_error(post_response)?;
+        Ok(())
+    }
+    
+
+    #[test]
+    fn orders_get_single_error_404() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+
+        // execution
+        let req = client.get("/tables/1/orders/1");
+        let response = req.dispatch();
+        
+        // assertion
+        assert_eq!(response.status(), Status::NotFound);
+        assert_response_contains_error(response)?;
+        Ok(())
+    }
+    
+
+    #[test]
+    fn orders_get_single_different_table_error_404() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let orders = rest_bodies::Orders {
+            idempotency_key: Option::None,
+            orders: vec![
+                rest_bodies::Order {
+                    menu_item_id: 1,
+                    seat: None,
+                    modifiers: None,
+                    note: None
+                },
+                rest_bodies::Order {
+                    menu_item_id: 2,
+                    seat: None,
+                    modifiers: None,
+                    note: None
+                }
+            ]
+        };
+
+        // post execution
+        let post_req = client.post("/tables/1/orders")
+            .header(ContentType::JSON)
+            .body(to_string(&orders).map_err(|e| e.to_string())?);
+        let post_response = post_req.dispatch();
+        let post_orders = from_str::<rest_responses::Orders>(&post_response.into_string().unwrap())
+            .map_err(|e| e.to_string())
+            ?.orders;
+        let order = post_orders.iter().find(|o| o.menu_item_id == 1).unwrap();
+        
+
+        // get execution
+        let get_req = client.get(format!("/tables/2/orders/{}", order.id)); // different table from before
+        let response = get_req.dispatch();
+        
+        // assertion
+        assert_eq!(response.status(), Status::NotFound);
+        assert_response_contains_error(response)?;
+        Ok(())
+    }
+    
+
+    #[test]
+    fn orders_get_single_error_500() -> Result<(), String> {
+        // setup
+        let client = create_client_without_setup()?;
+
+        // execution
+        let req = client.get("/tables/1/orders/1");
+        let response = req.dispatch();
+        
+        // assertion
+        assert_eq!(response.status(), Status::InternalServerError);
+        assert_response_contains_error(response)?;
+        Ok(())
+    }
+    
+
+    #[test]
+    fn orders_delete_error_500() -> Result<(), String> {
+        // setup
+        let client = create_client_without_setup()?;
+
+        // execution
+        let req = client.delete("/tables/1/orders/1");
+        let response = req.dispatch();
+        
+        // assertion
+        assert_eq!(response.status(), Status::InternalServerError);
+        assert_response_contains_error(response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn orders_post_status_placed() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+
+        // execution
+        let post_orders = post_orders(&client, 1, vec![1])?;
+
+        // assertion
+        let order = post_orders.first().unwrap();
+        assert_eq!(order.status, OrderStatus::Placed);
+        assert!(order.status_times.placed_at.is_some());
+        assert!(order.status_times.cooking_at.is_none());
+        assert!(order.status_times.ready_at.is_none());
+        assert!(order.status_times.served_at.is_none());
+        assert!(order.status_times.cancelled_at.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn orders_put_status() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let order_id = post_orders(&client, 1, vec![1])?.first().unwrap().id;
+
+        // execution
+        let put_response = put_status(&client, 1, order_id, OrderStatus::Cooking)?;
+
+        // assertion
+        assert_eq!(put_response.status(), Status::Ok);
+        let order = from_str::<rest_responses::Order>(&put_response.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(order.id, order_id);
+        assert_eq!(order.status, OrderStatus::Cooking);
+        assert!(order.status_times.placed_at.is_some());
+        assert!(order.status_times.cooking_at.is_some());
+
+        let get_response = client.get(format!("/tables/1/orders/{order_id}")).dispatch();
+        let order = from_str::<rest_responses::Order>(&get_response.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(order.status, OrderStatus::Cooking);
+        Ok(())
+    }
+
+    #[test]
+    fn orders_put_status_full_lifecycle() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let order_id = post_orders(&client, 1, vec![1])?.first().unwrap().id;
+
+        // execution
+        let cooking_response = put_status(&client, 1, order_id, OrderStatus::Cooking)?;
+        assert_eq!(cooking_response.status(), Status::Ok);
+        let ready_response = put_status(&client, 1, order_id, OrderStatus::Ready)?;
+        assert_eq!(ready_response.status(), Status::Ok);
+        let served_response = put_status(&client, 1, order_id, OrderStatus::Served)?;
+
+        // assertion
+        assert_eq!(served_response.status(), Status::Ok);
+        let order = from_str::<rest_responses::Order>(&served_response.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(order.status, OrderStatus::Served);
+        assert!(order.status_times.placed_at.is_some());
+        assert!(order.status_times.cooking_at.is_some());
+        assert!(order.status_times.ready_at.is_some());
+        assert!(order.status_times.served_at.is_some());
+        assert!(order.status_times.cancelled_at.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn orders_put_status_cancelled() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let order_id = post_orders(&client, 1, vec![1])?.first().unwrap().id;
+
+        // execution
+        let put_response = put_status(&client, 1, order_id, OrderStatus::Cancelled)?;
+
+        // assertion
+        assert_eq!(put_response.status(), Status::Ok);
+        let order = from_str::<rest_responses::Order>(&put_response.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(order.status, OrderStatus::Cancelled);
+        assert!(order.status_times.cancelled_at.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn orders_put_status_error_409() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let order_id = post_orders(&client, 1, vec![1])?.first().unwrap().id;
+        put_status(&client, 1, order_id, OrderStatus::Cooking)?;
+        put_status(&client, 1, order_id, OrderStatus::Ready)?;
+        put_status(&client, 1, order_id, OrderStatus::Served)?;
+
+        // execution
+        let put_response = put_status(&client, 1, order_id, OrderStatus::Cooking)?;
+
+        // assertion
+        assert_eq!(put_response.status(), Status::Conflict);
+        assert_response_contains_error(put_response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn orders_put_status_skip_error_409() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let order_id = post_orders(&client, 1, vec![1])?.first().unwrap().id;
+
+        // execution
+        let put_response = put_status(&client, 1, order_id, OrderStatus::Served)?;
+
+        // assertion
+        assert_eq!(put_response.status(), Status::Conflict);
+        assert_response_contains_error(put_response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn orders_put_status_error_404() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let order_id = post_orders(&client, 1, vec![1])?.first().unwrap().id;
+
+        // execution
+        let put_response = put_status(&client, 2, order_id, OrderStatus::Cooking)?; // different table from before
+
+        // assertion
+        assert_eq!(put_response.status(), Status::NotFound);
+        assert_response_contains_error(put_response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn orders_put_status_concurrently() -> Result<(), String> {
+        // setup
+        let repository = test_repository::new()?;
+        let client = create_client_with(repository.clone(), mock_clock::new(), Settings::default())?;
+        let order_ids = post_orders(&client, 1, vec![1; 50])?.iter().map(|o| o.id).collect::<Vec<_>>();
+        let changes = order_ids.iter()
+            .flat_map(|id| [(*id, OrderStatus::Cancelled), (*id, OrderStatus::Cooking)])
+            .collect::<Vec<_>>();
+        let start = Barrier::new(changes.len());
+
+        // execution
+        let results = std::thread::scope(|scope| {
+            let handles = changes.iter()
+                .map(|(order_id, status)| {
+                    let (repository, start) = (&repository, &start);
+                    scope.spawn(move || {
+                        start.wait();
+                        (*status, repository.orders().update_order_status(&mock_clock::new(), 1, *order_id, *status))
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect::<Vec<_>>()
+        });
+
+        // assertion
+        // an order can be cancelled whether or not it has started cooking, but once cancelled it cannot start
+        for (status, result) in results {
+            if status == OrderStatus::Cancelled {
+                assert!(result.is_ok(), "Error cancelling an order: {}", result.unwrap_err());
+            }
+        }
+        let orders = get_orders_page(&client, "/orders".to_string())?.orders;
+        assert!(orders.iter().all(|o| o.status == OrderStatus::Cancelled));
+        Ok(())
+    }
+
+    #[test]
+    fn orders_put_status_error_500() -> Result<(), String> {
+        // setup
+        let client = create_client_without_setup()?;
+
+        // execution
+        let put_response = put_status(&client, 1, 1, OrderStatus::Cooking)?;
+
+        // assertion
+        assert_eq!(put_response.status(), Status::InternalServerError);
+        assert_response_contains_error(put_response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn orders_patch_menu_item() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let order_id = post_orders(&client, 1, vec![1])?.first().unwrap().id;
+
+        // execution
+        let patch_response = patch_order(&client, 1, order_id, rest_bodies::OrderUpdate {
+            menu_item_id: Some(2),
+            table_number: None,
+            minutes_to_cook: None,
+            seat: None,
+            modifiers: None,
+            note: None
+        })?;
+
+        // assertion
+        assert_eq!(patch_response.status(), Status::Ok);
+        let order = from_str::<rest_responses::Order>(&patch_response.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(order.id, order_id);
+        assert_eq!(order.menu_item_id, 2);
+        assert!(!order.menu_item_name.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn orders_patch_table_number() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let order_id = post_orders(&client, 1, vec![1])?.first().unwrap().id;
+
+        // execution
+        let patch_response = patch_order(&client, 1, order_id, rest_bodies::OrderUpdate {
+            menu_item_id: None,
+            table_number: Some(2),
+            minutes_to_cook: None,
+            seat: None,
+            modifiers: None,
+            note: None
+        })?;
+
+        // assertion
+        assert_eq!(patch_response.status(), Status::Ok);
+        let old_table_response = client.get(format!("/tables/1/orders/{order_id}")).dispatch();
+        assert_eq!(old_table_response.status(), Status::NotFound);
+        let new_table_response = client.get(format!("/tables/2/orders/{order_id}")).dispatch();
+        assert_eq!(new_table_response.status(), Status::Ok);
+        Ok(())
+    }
+
+    #[test]
+    fn orders_patch_minutes_to_cook() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let order_id = post_orders(&client, 1, vec![1])?.first().unwrap().id;
+
+        // execution
+        let patch_response = patch_order(&client, 1, order_id, rest_bodies::OrderUpdate {
+            menu_item_id: None,
+            table_number: None,
+            minutes_to_cook: Some(30),
+            seat: None,
+            modifiers: None,
+            note: None
+        })?;
+
+        // assertion
+        assert_eq!(patch_response.status(), Status::Ok);
+        let order = from_str::<rest_responses::Order>(&patch_response.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(order.menu_item_id, 1);
+        assert_eq!(order.minutes_to_cook, 30);
+        Ok(())
+    }
+
+    #[test]
+    fn orders_patch_nothing() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let posted_order = post_orders(&client, 1, vec![1])?.pop().unwrap();
+
+        // execution
+        let patch_response = patch_order(&client, 1, posted_order.id, rest_bodies::OrderUpdate {
+            menu_item_id: None,
+            table_number: None,
+            minutes_to_cook: None,
+            seat: None,
+            modifiers: None,
+            note: None
+        })?;
+
+        // assertion
+        assert_eq!(patch_response.status(), Status::Ok);
+        let order = from_str::<rest_responses::Order>(&patch_response.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(order.menu_item_id, posted_order.menu_item_id);
+        assert_eq!(order.minutes_to_cook, posted_order.minutes_to_cook);
+        Ok(())
+    }
+
+    #[test]
+    fn orders_patch_error_404() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let order_id = post_orders(&client, 1, vec![1])?.first().unwrap().id;
+
+        // execution
+        let patch_response = patch_order(&client, 2, order_id, rest_bodies::OrderUpdate { // different table from before
+            menu_item_id: None,
+            table_number: None,
+            minutes_to_cook: Some(30),
+            seat: None,
+            modifiers: None,
+            note: None
+        })?;
+
+        // assertion
+        assert_eq!(patch_response.status(), Status::NotFound);
+        assert_response_contains_error(patch_response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn orders_patch_final_status_error_409() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let served_id = post_orders(&client, 1, vec![1])?.first().unwrap().id;
+        for status in [OrderStatus::Cooking, OrderStatus::Ready, OrderStatus::Served] {
+            put_status(&client, 1, served_id, status)?;
+        }
+        let cancelled_id = post_orders(&client, 1, vec![2])?.first().unwrap().id;
+        put_status(&client, 1, cancelled_id, OrderStatus::Cancelled)?;
+
+        // execution
+        let served_response = patch_order(&client, 1, served_id, rest_bodies::OrderUpdate {
+            menu_item_id: Some(3),
+            table_number: None,
+            minutes_to_cook: None,
+            seat: None,
+            modifiers: None,
+            note: None
+        })?;
+        let cancelled_response = patch_order(&client, 1, cancelled_id, rest_bodies::OrderUpdate {
+            menu_item_id: None,
+            table_number: Some(2),
+            minutes_to_cook: None,
+            seat: None,
+            modifiers: None,
+            note: None
+        })?;
+
+        // assertion
+        assert_eq!(served_response.status(), Status::Conflict);
+        assert_response_contains_error(served_response)?;
+        assert_eq!(cancelled_response.status(), Status::Conflict);
+        assert_response_contains_error(cancelled_response)?;
+        let orders = from_str::<rest_responses::Orders>(&client.get("/tables/1/orders").dispatch().into_string().unwrap())
+            .map_err(|e| e.to_string())?.orders;
+        assert_eq!(orders.len(), 2);
+        assert_eq!(orders[0].menu_item_id, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn orders_patch_error_422() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let order_id = post_orders(&client, 1, vec![1])?.first().unwrap().id;
+
+        // execution
+        let patch_response = patch_order(&client, 1, order_id, rest_bodies::OrderUpdate {
+            menu_item_id: Some(999),
+            table_number: None,
+            minutes_to_cook: None,
+            seat: None,
+            modifiers: None,
+            note: None
+        })?;
+
+        // assertion
+        assert_eq!(patch_response.status(), Status::UnprocessableEntity);
+        assert_response_contains_error(patch_response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn orders_patch_error_500() -> Result<(), String> {
+        // setup
+        let client = create_client_without_setup()?;
+
+        // execution
+        let patch_response = patch_order(&client, 1, 1, rest_bodies::OrderUpdate {
+            menu_item_id: None,
+            table_number: None,
+            minutes_to_cook: Some(30),
+            seat: None,
+            modifiers: None,
+            note: None
+        })?;
+
+        // assertion
+        assert_eq!(patch_response.status(), Status::InternalServerError);
+        assert_response_contains_error(patch_response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn orders_post_minutes_remaining() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+
+        // execution
+        let order = post_orders(&client, 1, vec![1])?.pop().unwrap();
+
+        // assertion
+        assert_eq!(order.created_at, mock_clock::DEFAULT_START_TIME);
+        assert_eq!(order.status_times.placed_at, Some(mock_clock::DEFAULT_START_TIME));
+        assert_eq!(order.minutes_remaining, order.minutes_to_cook);
+        assert_eq!(order.ready_at, mock_clock::DEFAULT_START_TIME + u64::from(order.minutes_to_cook) * 60);
+        Ok(())
+    }
+
+    #[test]
+    fn orders_get_minutes_remaining_counts_down() -> Result<(), String> {
+        // setup
+        let clock = mock_clock::new();
+        let client = create_client_with_clock(clock.clone())?;
+        let posted_order = post_orders(&client, 1, vec![1])?.pop().unwrap();
+
+        // execution
+        clock.advance_seconds(3 * 60);
+        let get_response = client.get(format!("/tables/1/orders/{}", posted_order.id)).dispatch();
+
+        // assertion
+        let order = from_str::<rest_responses::Order>(&get_response.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(order.minutes_to_cook, posted_order.minutes_to_cook);
+        assert_eq!(order.minutes_remaining, posted_order.minutes_to_cook - 3);
+        assert_eq!(order.ready_at, posted_order.ready_at);
+        Ok(())
+    }
+
+    #[test]
+    fn orders_get_minutes_remaining_rounds_up() -> Result<(), String> {
+        // setup
+        let clock = mock_clock::new();
+        let client = create_client_with_clock(clock.clone())?;
+        let posted_order = post_orders(&client, 1, vec![1])?.pop().unwrap();
+
+        // execution
+        clock.advance_seconds(30);
+        let get_response = client.get("/tables/1/orders").dispatch();
+
+        // assertion
+        let orders = from_str::<rest_responses::Orders>(&get_response.into_string().unwrap())
+            .map_err(|e| e.to_string())?
+            .orders;
+        assert_eq!(orders.first().unwrap().minutes_remaining, posted_order.minutes_to_cook);
+        Ok(())
+    }
+
+    #[test]
+    fn orders_get_minutes_remaining_after_ready_at() -> Result<(), String> {
+        // setup
+        let clock = mock_clock::new();
+        let client = create_client_with_clock(clock.clone())?;
+        let posted_order = post_orders(&client, 1, vec![1])?.pop().unwrap();
+
+        // execution
+        clock.advance_seconds(u64::from(posted_order.minutes_to_cook) * 60 + 60);
+        let get_response = client.get(format!("/tables/1/orders/{}", posted_order.id)).dispatch();
+
+        // assertion
+        let order = from_str::<rest_responses::Order>(&get_response.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(order.minutes_remaining, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn orders_get_minutes_remaining_when_ready() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let order_id = post_orders(&client, 1, vec![1])?.pop().unwrap().id;
+        put_status(&client, 1, order_id, OrderStatus::Cooking)?;
+
+        // execution
+        let put_response = put_status(&client, 1, order_id, OrderStatus::Ready)?;
+
+        // assertion
+        let order = from_str::<rest_responses::Order>(&put_response.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(order.minutes_remaining, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn orders_patch_minutes_to_cook_updates_ready_at() -> Result<(), String> {
+        // setup
+        let clock = mock_clock::new();
+        let client = create_client_with_clock(clock.clone())?;
+        let order_id = post_orders(&client, 1, vec![1])?.pop().unwrap().id;
+        clock.advance_seconds(5 * 60);
+
+        // execution
+        let patch_response = patch_order(&client, 1, order_id, rest_bodies::OrderUpdate {
+            menu_item_id: None,
+            table_number: None,
+            minutes_to_cook: Some(30),
+            seat: None,
+            modifiers: None,
+            note: None
+        })?;
+
+        // assertion
+        let order = from_str::<rest_responses::Order>(&patch_response.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(order.ready_at, mock_clock::DEFAULT_START_TIME + 30 * 60);
+        assert_eq!(order.minutes_remaining, 25);
+        Ok(())
+    }
+
+    #[test]
+    fn menu_items_get_has_cook_time_ranges() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+
+        // execution
+        let response = client.get("/menu-items").dispatch();
+
+        // assertion
+        assert_eq!(response.status(), Status::Ok);
+        let menu_items = from_str::<rest_responses::MenuItems>(&response.into_string().unwrap())
+            .map_err(|e| e.to_string())
+            ?.menu_items;
+        let hamburger = menu_items.iter().find(|m| m.name == "Hamburger").unwrap();
+        assert_eq!(hamburger.min_minutes_to_cook, 10);
+        assert_eq!(hamburger.max_minutes_to_cook, 15);
+        let soda = menu_items.iter().find(|m| m.name == "Soda").unwrap();
+        assert_eq!(soda.min_minutes_to_cook, 1);
+        assert_eq!(soda.max_minutes_to_cook, 2);
+        assert!(menu_items.iter().all(|m| m.min_minutes_to_cook <= m.max_minutes_to_cook));
+        Ok(())
+    }
+
+    #[test]
+    fn orders_post_cook_time_from_menu_item() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+
+        // execution
+        let orders = post_orders(&client, 1, vec![1, 5])?;
+
+        // assertion
+        let hamburger = orders.iter().find(|o| o.menu_item_id == 1).unwrap();
+        assert_eq!(hamburger.minutes_to_cook, 10);
+        let soda = orders.iter().find(|o| o.menu_item_id == 5).unwrap();
+        assert_eq!(soda.minutes_to_cook, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn orders_patch_menu_item_changes_cook_time() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let order_id = post_orders(&client, 1, vec![1])?.pop().unwrap().id;
+
+        // execution
+        let patch_response = patch_order(&client, 1, order_id, rest_bodies::OrderUpdate {
+            menu_item_id: Some(5),
+            table_number: None,
+            minutes_to_cook: None,
+            seat: None,
+            modifiers: None,
+            note: None
+        })?;
+
+        // assertion
+        let order = from_str::<rest_responses::Order>(&patch_response.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(order.menu_item_id, 5);
+        assert_eq!(order.minutes_to_cook, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn orders_patch_menu_item_keeps_provided_cook_time() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let order_id = post_orders(&client, 1, vec![1])?.pop().unwrap().id;
+
+        // execution
+        let patch_response = patch_order(&client, 1, order_id, rest_bodies::OrderUpdate {
+            menu_item_id: Some(5),
+            table_number: None,
+            minutes_to_cook: Some(20),
+            seat: None,
+            modifiers: None,
+            note: None
+        })?;
+
+        // assertion
+        let order = from_str::<rest_responses::Order>(&patch_response.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(order.menu_item_id, 5);
+        assert_eq!(order.minutes_to_cook, 20);
+        Ok(())
+    }
+
+    #[test]
+    fn menu_items_post() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+
+        // execution
+        let post_response = create_menu_item(&client, "Ramen", 6, 9)?;
+
+        // assertion
+        assert_eq!(post_response.status(), Status::Created);
+        let menu_item = from_str::<rest_responses::MenuItem>(&post_response.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(menu_item.name, "Ramen");
+        assert_eq!(menu_item.min_minutes_to_cook, 6);
+        assert_eq!(menu_item.max_minutes_to_cook, 9);
+        assert!(!menu_item.retired);
+
+        let menu_items = get_menu_item_list(&client, "/menu-items")?;
+        assert_eq!(menu_items.len(), 6);
+        assert!(menu_items.iter().any(|m| m.id == menu_item.id && m.name == "Ramen"));
+
+        let order = post_orders(&client, 1, vec![menu_item.id])?.pop().unwrap();
+        assert_eq!(order.menu_item_name, "Ramen");
+        assert_eq!(order.minutes_to_cook, 6);
+        Ok(())
+    }
+
+    #[test]
+    fn menu_items_post_error_409() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+
+        // execution
+        let post_response = create_menu_item(&client, "Hamburger", 6, 9)?;
+
+        // assertion
+        assert_eq!(post_response.status(), Status::Conflict);
+        assert_response_contains_error(post_response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn menu_items_post_error_422() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+
+        // execution
+        let range_response = create_menu_item(&client, "Ramen", 9, 6)?;
+        let name_response = create_menu_item(&client, " ", 6, 9)?;
+
+        // assertion
+        assert_eq!(range_response.status(), Status::UnprocessableEntity);
+        assert_response_contains_error(range_response)?;
+        assert_eq!(name_response.status(), Status::UnprocessableEntity);
+        assert_response_contains_error(name_response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn menu_items_post_error_500() -> Result<(), String> {
+        // setup
+        let client = create_client_without_setup()?;
+
+        // execution
+        let post_response = create_menu_item(&client, "Ramen", 6, 9)?;
+
+        // assertion
+        assert_eq!(post_response.status(), Status::InternalServerError);
+        assert_response_contains_error(post_response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn menu_items_patch() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let order_id = post_orders(&client, 1, vec![1])?.pop().unwrap().id;
+
+        // execution
+        let patch_response = update_menu_item(&client, 1, rest_bodies::MenuItemUpdate {
+            name: Some("Cheeseburger".to_string()),
+            min_minutes_to_cook: None,
+            max_minutes_to_cook: Some(20),
+            price: None,
+            modifiers: None
+        })?;
+
+        // assertion
+        assert_eq!(patch_response.status(), Status::Ok);
+        let menu_item = from_str::<rest_responses::MenuItem>(&patch_response.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(menu_item.id, 1);
+        assert_eq!(menu_item.name, "Cheeseburger");
+        assert_eq!(menu_item.min_minutes_to_cook, 10);
+        assert_eq!(menu_item.max_minutes_to_cook, 20);
+
+        let get_response = client.get(format!("/tables/1/orders/{order_id}")).dispatch();
+        let order = from_str::<rest_responses::Order>(&get_response.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(order.menu_item_name, "Cheeseburger");
+        Ok(())
+    }
+
+    #[test]
+    fn menu_items_patch_error_404() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+
+        // execution
+        let patch_response = update_menu_item(&client, 999, rest_bodies::MenuItemUpdate {
+            name: Some("Cheeseburger".to_string()),
+            min_minutes_to_cook: None,
+            max_minutes_to_cook: None,
+            price: None,
+            modifiers: None
+        })?;
+
+        // assertion
+        assert_eq!(patch_response.status(), Status::NotFound);
+        assert_response_contains_error(patch_response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn menu_items_patch_error_409() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+
+        // execution
+        let patch_response = update_menu_item(&client, 1, rest_bodies::MenuItemUpdate {
+            name: Some("Salad".to_string()),
+            min_minutes_to_cook: None,
+            max_minutes_to_cook: None,
+            price: None,
+            modifiers: None
+        })?;
+
+        // assertion
+        assert_eq!(patch_response.status(), Status::Conflict);
+        assert_response_contains_error(patch_response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn menu_items_patch_error_422() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+
+        // execution
+        let patch_response = update_menu_item(&client, 1, rest_bodies::MenuItemUpdate { // Hamburger has a max of 15
+            name: None,
+            min_minutes_to_cook: Some(16),
+            max_minutes_to_cook: None,
+            price: None,
+            modifiers: None
+        })?;
+
+        // assertion
+        assert_eq!(patch_response.status(), Status::UnprocessableEntity);
+        assert_response_contains_error(patch_response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn menu_items_post_and_patch_outside_cook_time_range_error_422() -> Result<(), String> {
+        // setup
+        let client = create_client_with_settings(Settings {
+            cook_time_range: CookTimeRange { min_minutes: 1, max_minutes: 30 },
+            ..Settings::default()
+        })?;
+
+        // execution
+        let post_response = create_menu_item(&client, "Brisket", 20, 480)?;
+        let patch_response = update_menu_item(&client, 1, rest_bodies::MenuItemUpdate {
+            name: None,
+            min_minutes_to_cook: None,
+            max_minutes_to_cook: Some(45),
+            price: None,
+            modifiers: None
+        })?;
+        let unchanged_times_response = update_menu_item(&client, 1, rest_bodies::MenuItemUpdate {
+            name: None,
+            min_minutes_to_cook: None,
+            max_minutes_to_cook: None,
+            price: Some(950),
+            modifiers: None
+        })?;
+
+        // assertion
+        assert_eq!(post_response.status(), Status::UnprocessableEntity);
+        assert!(assert_response_contains_error(post_response)?.error.contains("between 1 and 30 minutes"));
+        assert_eq!(patch_response.status(), Status::UnprocessableEntity);
+        assert_response_contains_error(patch_response)?;
+        assert_eq!(unchanged_times_response.status(), Status::Ok);
+        Ok(())
+    }
+
+    #[test]
+    fn menu_items_delete() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let order_id = post_orders(&client, 1, vec![1])?.pop().unwrap().id;
+
+        // execution
+        let delete_response = client.delete("/menu-items/1").dispatch();
+
+        // assertion
+        assert_eq!(delete_response.status(), Status::NoContent);
+
+        let menu_items = get_menu_item_list(&client, "/menu-items")?;
+        assert_eq!(menu_items.len(), 4);
+        assert!(menu_items.iter().all(|m| m.id != 1));
+
+        let all_menu_items = get_menu_item_list(&client, "/menu-items?include_retired=true")?;
+        assert_eq!(all_menu_items.len(), 5);
+        assert!(all_menu_items.iter().any(|m| m.id == 1 && m.retired));
+
+        // orders for the retired item are still available
+        let get_response = client.get(format!("/tables/1/orders/{order_id}")).dispatch();
+        assert_eq!(get_response.status(), Status::Ok);
+        let order = from_str::<rest_responses::Order>(&get_response.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(order.menu_item_name, "Hamburger");
+        Ok(())
+    }
+
+    #[test]
+    fn menu_items_delete_cannot_be_ordered() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let order_id = post_orders(&client, 1, vec![2])?.pop().unwrap().id;
+        client.delete("/menu-items/1").dispatch();
+        let orders = rest_bodies::Orders {
+            idempotency_key: Option::None,
+            orders: vec![
+                rest_bodies::Order {
+                    menu_item_id: 1,
+                    seat: None,
+                    modifiers: None,
+                    note: None
+                }
+            ]
+        };
+
+        // execution
+        let post_response = client.post("/tables/1/orders")
+            .header(ContentType::JSON)
+            .body(to_string(&orders).map_err(|e| e.to_string())?)
+            .dispatch();
+        let patch_response = patch_order(&client, 1, order_id, rest_bodies::OrderUpdate {
+            menu_item_id: Some(1),
+            table_number: None,
+            minutes_to_cook: None,
+            seat: None,
+            modifiers: None,
+            note: None
+        })?;
+
+        // assertion
+        assert_eq!(post_response.status(), Status::UnprocessableEntity);
+        assert_response_contains_error(post_response)?;
+        assert_eq!(patch_response.status(), Status::UnprocessableEntity);
+        assert_response_contains_error(patch_response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn menu_items_delete_error_404() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+
+        // execution
+        let delete_response = client.delete("/menu-items/999").dispatch();
+
+        // assertion
+        assert_eq!(delete_response.status(), Status::NotFound);
+        assert_response_contains_error(delete_response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn orders_post_price_from_menu_item() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+
+        // execution
+        let orders = post_orders(&client, 1, vec![1, 5])?;
+
+        // assertion
+        assert_eq!(orders.iter().find(|o| o.menu_item_id == 1).unwrap().price, 900);
+        assert_eq!(orders.iter().find(|o| o.menu_item_id == 5).unwrap().price, 250);
+        Ok(())
+    }
+
+    #[test]
+    fn bill_get() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        post_orders(&client, 1, vec![1, 1, 5])?;
+        post_orders(&client, 2, vec![3])?; // different table, not on the bill
+
+        // execution
+        let response = client.get("/tables/1/bill").dispatch();
+
+        // assertion
+        assert_eq!(response.status(), Status::Ok);
+        let bill = from_str::<rest_responses::Bill>(&response.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(bill.table_number, 1);
+        assert_eq!(bill.line_items.len(), 3);
+        assert_eq!(bill.line_items.iter().filter(|i| i.menu_item_name == "Hamburger").count(), 2);
+        assert_eq!(bill.subtotal, 2050);
+        assert_eq!(bill.tax, 205);
+        assert_eq!(bill.total, 2255);
+        Ok(())
+    }
+
+    #[test]
+    fn bill_get_tax_rounding() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let menu_item = from_str::<rest_responses::MenuItem>(&create_menu_item(&client, "Gum", 1, 1)?.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        update_menu_item(&client, menu_item.id, rest_bodies::MenuItemUpdate {
+            name: None,
+            min_minutes_to_cook: None,
+            max_minutes_to_cook: None,
+            price: Some(105),
+            modifiers: None
+        })?;
+        post_orders(&client, 1, vec![menu_item.id])?;
+
+        // execution
+        let bill = get_bill(&client, 1)?;
+
+        // assertion
+        assert_eq!(bill.subtotal, 105);
+        assert_eq!(bill.tax, 11); // 10.5 rounds up
+        assert_eq!(bill.total, 116);
+        Ok(())
+    }
+
+    #[test]
+    fn bill_get_excludes_cancelled() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let orders = post_orders(&client, 1, vec![1, 5])?;
+        let hamburger_id = orders.iter().find(|o| o.menu_item_id == 1).unwrap().id;
+        put_status(&client, 1, hamburger_id, OrderStatus::Cancelled)?;
+
+        // execution
+        let bill = get_bill(&client, 1)?;
+
+        // assertion
+        assert_eq!(bill.line_items.len(), 1);
+        assert_eq!(bill.line_items.first().unwrap().menu_item_id, 5);
+        assert_eq!(bill.subtotal, 250);
+        Ok(())
+    }
+
+    #[test]
+    fn bill_get_uses_price_at_order_time() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        post_orders(&client, 1, vec![1])?;
+        update_menu_item(&client, 1, rest_bodies::MenuItemUpdate {
+            name: None,
+            min_minutes_to_cook: None,
+            max_minutes_to_cook: None,
+            price: Some(5000),
+            modifiers: None
+        })?;
+
+        // execution
+        let bill = get_bill(&client, 1)?;
+
+        // assertion
+        assert_eq!(bill.subtotal, 900);
+        let order = post_orders(&client, 1, vec![1])?.pop().unwrap();
+        assert_eq!(order.price, 5000);
+        Ok(())
+    }
+
+    #[test]
+    fn bill_get_empty() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+
+        // execution
+        let bill = get_bill(&client, 1)?;
+
+        // assertion
+        assert!(bill.line_items.is_empty());
+        assert_eq!(bill.total, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn bill_get_error_500() -> Result<(), String> {
+        // setup
+        let client = create_client_without_setup()?;
+
+        // execution
+        let response = client.get("/tables/1/bill").dispatch();
+
+        // assertion
+        assert_eq!(response.status(), Status::InternalServerError);
+        assert_response_contains_error(response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn orders_post_and_patch_seat() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let order = post_seated_orders(&client, 1, vec![(1, Some(2))])?.pop().unwrap();
+        assert_eq!(order.seat, Some(2));
+
+        // execution
+        let patch_response = patch_order(&client, 1, order.id, rest_bodies::OrderUpdate {
+            menu_item_id: None,
+            table_number: None,
+            minutes_to_cook: None,
+            seat: Some(3),
+            modifiers: None,
+            note: None
+        })?;
+
+        // assertion
+        assert_eq!(patch_response.status(), Status::Ok);
+        let patched_order = from_str::<rest_responses::Order>(&patch_response.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(patched_order.seat, Some(3));
+        assert_eq!(get_bill(&client, 1)?.line_items.first().unwrap().seat, Some(3));
+        Ok(())
+    }
+
+    #[test]
+    fn split_bill_get_by_seat() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        post_seated_orders(&client, 1, vec![(3, Some(2)), (1, Some(1)), (2, None), (5, Some(1))])?;
+
+        // execution
+        let response = client.get("/tables/1/bill/split?by=seat").dispatch();
+
+        // assertion
+        assert_eq!(response.status(), Status::Ok);
+        let split_bill = from_str::<rest_responses::SplitBill>(&response.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(split_bill.total, 3300);
+        let seats = split_bill.bills.iter().map(|b| b.seat).collect::<Vec<Option<u32>>>();
+        assert_eq!(seats, vec![Some(1), Some(2), None]); // orders without a seat come last
+        let totals = split_bill.bills.iter().map(|b| (b.subtotal, b.tax, b.total)).collect::<Vec<(u64, u64, u64)>>();
+        assert_eq!(totals, vec![(1150, 115, 1265), (1200, 120, 1320), (650, 65, 715)]);
+        assert_eq!(split_bill.bills.first().unwrap().line_items.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn split_bill_get_by_seat_tax_rounding() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let menu_item_id = create_priced_menu_item(&client, "Gum", 105)?;
+        post_seated_orders(&client, 1, vec![(menu_item_id, Some(1)), (menu_item_id, Some(2))])?;
+
+        // execution
+        let split_bill = get_split_bill(&client, "/tables/1/bill/split?by=seat".to_string())?;
+
+        // assertion
+        // the bill's tax of 21 cannot be shared equally, so the extra unit goes to the first seat
+        assert_eq!(split_bill.tax, 21);
+        let taxes = split_bill.bills.iter().map(|b| b.tax).collect::<Vec<u64>>();
+        assert_eq!(taxes, vec![11, 10]);
+        assert_eq!(split_bill.bills.iter().map(|b| b.total).sum::<u64>(), split_bill.total);
+        Ok(())
+    }
+
+    #[test]
+    fn split_bill_get_even() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        post_orders(&client, 1, vec![1, 5])?;
+
+        // execution
+        let split_bill = get_split_bill(&client, "/tables/1/bill/split?by=even&ways=3".to_string())?;
+
+        // assertion
+        assert_eq!(split_bill.total, 1265);
+        let totals = split_bill.bills.iter().map(|b| (b.subtotal, b.tax, b.total)).collect::<Vec<(u64, u64, u64)>>();
+        assert_eq!(totals, vec![(384, 39, 423), (383, 38, 421), (383, 38, 421)]);
+        assert!(split_bill.bills.iter().all(|b| b.line_items.is_empty()));
+        Ok(())
+    }
+
+    #[test]
+    fn split_bill_get_by_orders() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let orders = post_orders(&client, 1, vec![1, 3, 5])?;
+        let hamburger_id = orders.iter().find(|o| o.menu_item_id == 1).unwrap().id;
+        let sushi_id = orders.iter().find(|o| o.menu_item_id == 3).unwrap().id;
+        let soda_id = orders.iter().find(|o| o.menu_item_id == 5).unwrap().id;
+
+        // execution
+        let split_bill = get_split_bill(&client, format!("/tables/1/bill/split?by=orders&groups={hamburger_id},{soda_id}"))?;
+
+        // assertion
+        // the sushi was not in any group, so it is put on a bill of its own
+        assert_eq!(split_bill.bills.len(), 2);
+        let group = split_bill.bills.first().unwrap();
+        assert_eq!(group.line_items.iter().map(|i| i.order_id).collect::<Vec<u32>>(), vec![hamburger_id, soda_id]);
+        assert_eq!((group.subtotal, group.tax, group.total), (1150, 115, 1265));
+        let remaining = split_bill.bills.last().unwrap();
+        assert_eq!(remaining.line_items.iter().map(|i| i.order_id).collect::<Vec<u32>>(), vec![sushi_id]);
+        assert_eq!((remaining.subtotal, remaining.tax, remaining.total), (1200, 120, 1320));
+        Ok(())
+    }
+
+    #[test]
+    fn split_bill_get_error_422() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let order_id = post_orders(&client, 1, vec![1])?.first().unwrap().id;
+        let other_table_order_id = post_orders(&client, 2, vec![1])?.first().unwrap().id;
+
+        // execution
+        let uris = vec![
+            "/tables/1/bill/split?by=plate".to_string(),
+            "/tables/1/bill/split?by=even".to_string(),
+            "/tables/1/bill/split?by=even&ways=0".to_string(),
+            "/tables/1/bill/split?by=orders&groups=one".to_string(),
+            format!("/tables/1/bill/split?by=orders&groups={order_id}&groups={order_id}"),
+            format!("/tables/1/bill/split?by=orders&groups={other_table_order_id}")];
+
+        // assertion
+        for uri in uris {
+            let response = client.get(uri.clone()).dispatch();
+            assert_eq!(response.status(), Status::UnprocessableEntity, "Unexpected status for {uri}");
+            assert_response_contains_error(response)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn split_bill_get_error_500() -> Result<(), String> {
+        // setup
+        let client = create_client_without_setup()?;
+
+        // execution
+        let response = client.get("/tables/1/bill/split?by=seat").dispatch();
+
+        // assertion
+        assert_eq!(response.status(), Status::InternalServerError);
+        assert_response_contains_error(response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn menu_items_get_has_default_modifiers() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+
+        // execution
+        let menu_items = get_menu_item_list(&client, "/menu-items")?;
+
+        // assertion
+        let hamburger = menu_items.iter().find(|m| m.name == "Hamburger").unwrap();
+        let modifiers = hamburger.modifiers.iter().map(|m| (m.name.as_str(), m.price_delta)).collect::<Vec<(&str, u32)>>();
+        assert_eq!(modifiers, vec![("no onions", 0), ("extra cheese", 100)]);
+        assert!(menu_items.iter().find(|m| m.name == "Sushi").unwrap().modifiers.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn orders_post_with_modifiers_and_note() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+
+        // execution
+        let response = post_order(&client, 1, rest_bodies::Order {
+            menu_item_id: 1,
+            seat: None,
+            modifiers: Some(vec!["extra cheese".to_string(), "no onions".to_string()]),
+            note: Some("  Well done, please  ".to_string())
+        })?;
+
+        // assertion
+        assert_eq!(response.status(), Status::Ok);
+        let order = from_str::<rest_responses::Orders>(&response.into_string().unwrap())
+            .map_err(|e| e.to_string())?
+            .orders.pop().unwrap();
+        assert_eq!(order.modifiers.iter().map(|m| m.name.as_str()).collect::<Vec<&str>>(), vec!["extra cheese", "no onions"]);
+        assert_eq!(order.note, Some("Well done, please".to_string()));
+        assert_eq!(order.price, 1000); // the hamburger's price plus extra cheese
+        assert_eq!(get_bill(&client, 1)?.subtotal, 1000);
+
+        let queried_order = from_str::<rest_responses::Order>(&client.get(format!("/tables/1/orders/{}", order.id)).dispatch().into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(queried_order.modifiers.len(), 2);
+        assert_eq!(queried_order.note, order.note);
+        Ok(())
+    }
+
+    #[test]
+    fn orders_post_error_422_unknown_modifier() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+
+        // execution
+        let response = post_order(&client, 1, rest_bodies::Order {
+            menu_item_id: 1,
+            seat: None,
+            modifiers: Some(vec!["no \"ice\"".to_string()]), // quotes must not break the error response
+            note: None
+        })?;
+
+        // assertion
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        let error_response = assert_response_contains_error(response)?;
+        assert_eq!(error_response.error, "'no \"ice\"' is not a modifier of Hamburger.");
+        let orders = from_str::<rest_responses::Orders>(&client.get("/tables/1/orders").dispatch().into_string().unwrap())
+            .map_err(|e| e.to_string())?
+            .orders;
+        assert!(orders.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn orders_post_error_422_invalid_modifiers_or_note() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let orders = vec![
+            rest_bodies::Order {
+                menu_item_id: 1,
+                seat: None,
+                modifiers: Some(vec!["no onions".to_string(), "no onions".to_string()]),
+                note: None
+            },
+            rest_bodies::Order {
+                menu_item_id: 1,
+                seat: None,
+                modifiers: None,
+                note: Some("a".repeat(201))
+            }];
+
+        for order in orders {
+            // execution
+            let response = post_order(&client, 1, order)?;
+
+            // assertion
+            assert_eq!(response.status(), Status::UnprocessableEntity);
+            assert_response_contains_error(response)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn orders_patch_modifiers_and_note() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let order_response = post_order(&client, 1, rest_bodies::Order {
+            menu_item_id: 1,
+            seat: None,
+            modifiers: Some(vec!["no onions".to_string()]),
+            note: Some("Allergic to onions".to_string())
+        })?;
+        let order_id = from_str::<rest_responses::Orders>(&order_response.into_string().unwrap())
+            .map_err(|e| e.to_string())?
+            .orders.pop().unwrap().id;
+
+        // execution
+        let patch_response = patch_order(&client, 1, order_id, rest_bodies::OrderUpdate {
+            menu_item_id: None,
+            table_number: None,
+            minutes_to_cook: None,
+            seat: None,
+            modifiers: Some(vec!["extra cheese".to_string()]),
+            note: Some("".to_string())
+        })?;
+
+        // assertion
+        assert_eq!(patch_response.status(), Status::Ok);
+        let order = from_str::<rest_responses::Order>(&patch_response.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(order.modifiers.iter().map(|m| m.name.as_str()).collect::<Vec<&str>>(), vec!["extra cheese"]);
+        assert_eq!(order.price, 1000);
+        assert_eq!(order.note, None);
+        Ok(())
+    }
+
+    #[test]
+    fn orders_patch_menu_item_revalidates_modifiers() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let order_response = post_order(&client, 1, rest_bodies::Order {
+            menu_item_id: 1,
+            seat: None,
+            modifiers: Some(vec!["extra cheese".to_string()]),
+            note: None
+        })?;
+        let order_id = from_str::<rest_responses::Orders>(&order_response.into_string().unwrap())
+            .map_err(|e| e.to_string())?
+            .orders.pop().unwrap().id;
+        let to_salad = |modifiers| rest_bodies::OrderUpdate {
+            menu_item_id: Some(2),
+            table_number: None,
+            minutes_to_cook: None,
+            seat: None,
+            modifiers,
+            note: None
+        };
+
+        // execution
+        let keep_modifiers_response = patch_order(&client, 1, order_id, to_salad(None))?;
+        let keep_modifiers_status = keep_modifiers_response.status();
+        let keep_modifiers_error = assert_response_contains_error(keep_modifiers_response)?;
+        let replace_modifiers_response = patch_order(&client, 1, order_id, to_salad(Some(vec!["dressing on the side".to_string()])))?;
+
+        // assertion
+        // salads do not come with extra cheese
+        assert_eq!(keep_modifiers_status, Status::UnprocessableEntity);
+        assert_eq!(keep_modifiers_error.error, "'extra cheese' is not a modifier of Salad.");
+        assert_eq!(replace_modifiers_response.status(), Status::Ok);
+        let order = from_str::<rest_responses::Order>(&replace_modifiers_response.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(order.menu_item_id, 2);
+        assert_eq!(order.price, 650);
+        Ok(())
+    }
+
+    #[test]
+    fn menu_items_post_and_patch_modifiers() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let response = client.post("/menu-items")
+            .header(ContentType::JSON)
+            .body(to_string(&rest_bodies::MenuItem {
+                name: "Pizza".to_string(),
+                min_minutes_to_cook: 10,
+                max_minutes_to_cook: 10,
+                price: 1100,
+                modifiers: Some(vec![
+                    rest_bodies::MenuItemModifier {
+                        name: "extra pepperoni".to_string(),
+                        price_delta: Some(200)
+                    },
+                    rest_bodies::MenuItemModifier {
+                        name: "well done".to_string(),
+                        price_delta: None
+                    }])
+            }).map_err(|e| e.to_string())?)
+            .dispatch();
+        assert_eq!(response.status(), Status::Created);
+        let menu_item = from_str::<rest_responses::MenuItem>(&response.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(menu_item.modifiers.iter().map(|m| m.price_delta).collect::<Vec<u32>>(), vec![200, 0]);
+        let order_response = post_order(&client, 1, rest_bodies::Order {
+            menu_item_id: menu_item.id,
+            seat: None,
+            modifiers: Some(vec!["extra pepperoni".to_string()]),
+            note: None
+        })?;
+        assert_eq!(order_response.status(), Status::Ok);
+
+        // execution
+        let patch_response = update_menu_item(&client, menu_item.id, rest_bodies::MenuItemUpdate {
+            name: None,
+            min_minutes_to_cook: None,
+            max_minutes_to_cook: None,
+            price: None,
+            modifiers: Some(vec![rest_bodies::MenuItemModifier {
+                name: "extra pepperoni".to_string(),
+                price_delta: Some(300)
+            }])
+        })?;
+
+        // assertion
+        assert_eq!(patch_response.status(), Status::Ok);
+        let updated = from_str::<rest_responses::MenuItem>(&patch_response.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(updated.modifiers.len(), 1);
+        assert_eq!(updated.modifiers.first().unwrap().price_delta, 300);
+        // the existing order keeps the price it was made at
+        assert_eq!(get_bill(&client, 1)?.subtotal, 1300);
+        Ok(())
+    }
+
+    #[test]
+    fn menu_items_patch_error_422_duplicate_modifiers() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let modifier = || rest_bodies::MenuItemModifier {
+            name: "no onions".to_string(),
+            price_delta: None
+        };
+
+        // execution
+        let response = update_menu_item(&client, 1, rest_bodies::MenuItemUpdate {
+            name: None,
+            min_minutes_to_cook: None,
+            max_minutes_to_cook: None,
+            price: None,
+            modifiers: Some(vec![modifier(), modifier()])
+        })?;
+
+        // assertion
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        assert_response_contains_error(response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn menu_items_error_422_price_too_large() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+
+        // execution
+        let post_response = client.post("/menu-items")
+            .header(ContentType::JSON)
+            .body(to_string(&rest_bodies::MenuItem {
+                name: "Wagyu".to_string(),
+                min_minutes_to_cook: 10,
+                max_minutes_to_cook: 20,
+                price: u32::MAX,
+                modifiers: Some(vec![rest_bodies::MenuItemModifier {
+                    name: "truffle".to_string(),
+                    price_delta: Some(1)
+                }])
+            }).map_err(|e| e.to_string())?)
+            .dispatch();
+        let patch_response = update_menu_item(&client, 1, rest_bodies::MenuItemUpdate { // Hamburger has an extra cheese modifier
+            name: None,
+            min_minutes_to_cook: None,
+            max_minutes_to_cook: None,
+            price: Some(u32::MAX),
+            modifiers: None
+        })?;
+
+        // assertion
+        assert_eq!(post_response.status(), Status::UnprocessableEntity);
+        assert_response_contains_error(post_response)?;
+        assert_eq!(patch_response.status(), Status::UnprocessableEntity);
+        assert_response_contains_error(patch_response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn tables_post_and_get() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+
+        // execution
+        let post_response = create_table(&client, 1, 4, Some("Patio"))?;
+        let get_response = client.get("/tables/1").dispatch();
+
+        // assertion
+        assert_eq!(post_response.status(), Status::Created);
+        let posted_table = from_str::<rest_responses::Table>(&post_response.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(posted_table.number, 1);
+        assert_eq!(posted_table.capacity, 4);
+        assert_eq!(posted_table.section, Some("Patio".to_string()));
+        assert!(posted_table.open);
+        assert_eq!(posted_table.order_count, 0);
+
+        assert_eq!(get_response.status(), Status::Ok);
+        let table = from_str::<rest_responses::Table>(&get_response.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(table.capacity, 4);
+        Ok(())
+    }
+
+    #[test]
+    fn tables_get_has_order_counts() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        create_table(&client, 2, 2, None)?;
+        create_table(&client, 1, 4, None)?;
+        let orders = post_orders(&client, 1, vec![1, 2, 3])?;
+        put_status(&client, 1, orders.first().unwrap().id, OrderStatus::Cancelled)?; // cancelled orders are not counted
+        post_orders(&client, 3, vec![1])?; // unregistered table, not listed
+
+        // execution
+        let response = client.get("/tables").dispatch();
+
+        // assertion
+        assert_eq!(response.status(), Status::Ok);
+        let tables = from_str::<rest_responses::Tables>(&response.into_string().unwrap())
+            .map_err(|e| e.to_string())?
+            .tables;
+        let order_counts = tables.iter().map(|t| (t.number, t.order_count)).collect::<Vec<(u32, u32)>>();
+        assert_eq!(order_counts, vec![(1, 2), (2, 0)]);
+        Ok(())
+    }
+
+    #[test]
+    fn tables_post_error_409() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        create_table(&client, 1, 4, None)?;
+
+        // execution
+        let response = create_table(&client, 1, 2, None)?;
+
         // assertion
-        assert_eq!(post_response.status(), Status::UnprocessableEntity);
-        assert_response_contains_error(post_response)?;
+        assert_eq!(response.status(), Status::Conflict);
+        assert_response_contains_error(response)?;
         Ok(())
     }
-    
 
     #[test]
-    fn orders_post_error_500() -> Result<(), String> {
+    fn tables_post_error_422() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+
+        // execution
+        let response = create_table(&client, 1, 0, None)?;
+
+        // assertion
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        assert_response_contains_error(response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn tables_get_error_404() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        post_orders(&client, 1, vec![1])?; // orders alone do not register a table
+
+        // execution
+        let response = client.get("/tables/1").dispatch();
+
+        // assertion
+        assert_eq!(response.status(), Status::NotFound);
+        assert_response_contains_error(response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn tables_get_error_500() -> Result<(), String> {
         // setup
         let client = create_client_without_setup()?;
-        let orders = rest_bodies::Orders {
-            idempotency_key: Option::None,
-            orders: vec![
-                rest_bodies::Order {
-                    menu_item_id: 1
-                },
-                rest_bodies::Order {
-                    menu_item_id: 2
-                }
-            ]
-        };
 
         // execution
-        let post_req = client.post("/tables/1/orders")
-            .header(ContentType::JSON)
-            .body(to_string(&orders).map_err(|e| e.to_string())?);
-        let post_response = post_req.dispatch();
-        
+        let response = client.get("/tables").dispatch();
+
         // assertion
-        assert_eq!(post_response.status(), Status::InternalServerError);
-        assert_response_contains_error(post_response)?;
+        assert_eq!(response.status(), Status::InternalServerError);
+        assert_response_contains_error(response)?;
         Ok(())
     }
-    
 
     #[test]
-    fn orders_get_single_error_404() -> Result<(), String> {
+    fn tables_patch() -> Result<(), String> {
         // setup
         let client = create_client()?;
+        create_table(&client, 1, 4, Some("Patio"))?;
 
         // execution
-        let req = client.get("/tables/1/orders/1");
-        let response = req.dispatch();
-        
+        let response = update_table(&client, 1, rest_bodies::TableUpdate {
+            capacity: None,
+            section: Some("".to_string()),
+            open: Some(false)
+        })?;
+
+        // assertion
+        assert_eq!(response.status(), Status::Ok);
+        let table = from_str::<rest_responses::Table>(&response.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(table.capacity, 4);
+        assert_eq!(table.section, None);
+        assert!(!table.open);
+        Ok(())
+    }
+
+    #[test]
+    fn tables_patch_error_404() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+
+        // execution
+        let response = update_table(&client, 1, rest_bodies::TableUpdate {
+            capacity: Some(2),
+            section: None,
+            open: None
+        })?;
+
         // assertion
         assert_eq!(response.status(), Status::NotFound);
         assert_response_contains_error(response)?;
         Ok(())
     }
-    
 
     #[test]
-    fn orders_get_single_different_table_error_404() -> Result<(), String> {
+    fn tables_patch_error_422() -> Result<(), String> {
         // setup
         let client = create_client()?;
-        let orders = rest_bodies::Orders {
-            idempotency_key: Option::None,
-            orders: vec![
-                rest_bodies::Order {
-                    menu_item_id: 1
-                },
-                rest_bodies::Order {
-                    menu_item_id: 2
-                }
-            ]
+        create_table(&client, 1, 4, None)?;
+
+        // execution
+        let response = update_table(&client, 1, rest_bodies::TableUpdate {
+            capacity: Some(0),
+            section: None,
+            open: None
+        })?;
+
+        // assertion
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        assert_response_contains_error(response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn tables_delete() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        create_table(&client, 1, 4, None)?;
+        post_orders(&client, 1, vec![1])?;
+
+        // execution
+        let open_orders_response = client.delete("/tables/1").dispatch();
+        let open_orders_status = open_orders_response.status();
+        client.post("/tables/1/checkout").dispatch();
+        let checked_out_response = client.delete("/tables/1").dispatch();
+
+        // assertion
+        // the table cannot be removed until its orders have been checked out
+        assert_eq!(open_orders_status, Status::Conflict);
+        assert_eq!(checked_out_response.status(), Status::NoContent);
+        assert_eq!(client.get("/tables/1").dispatch().status(), Status::NotFound);
+        Ok(())
+    }
+
+    #[test]
+    fn tables_delete_concurrently() -> Result<(), String> {
+        // setup
+        let repository = test_repository::new()?;
+        let client = create_client_with(repository.clone(), mock_clock::new(), Settings::default())?;
+        let table_numbers = 1..=30;
+        for table_number in table_numbers.clone() {
+            create_table(&client, table_number, 4, None)?;
+        }
+        let settings = Settings {
+            require_registered_tables: true,
+            ..Settings::default()
         };
+        let start = Barrier::new(2 * table_numbers.clone().count());
 
-        // post execution
-        let post_req = client.post("/tables/1/orders")
-            .header(ContentType::JSON)
-            .body(to_string(&orders).map_err(|e| e.to_string())?);
-        let post_response = post_req.dispatch();
-        let post_orders = from_str::<rest_responses::Orders>(&post_response.into_string().unwrap())
-            .map_err(|e| e.to_string())
-            ?.orders;
-        let order = post_orders.iter().find(|o| o.menu_item_id == 1).unwrap();
-        
+        // execution
+        let results = std::thread::scope(|scope| {
+            let handles = table_numbers
+                .map(|table_number| {
+                    let (repository, settings, start) = (&repository, &settings, &start);
+                    let deleted = scope.spawn(move || {
+                        start.wait();
+                        repository.tables().delete_table(table_number)
+                    });
+                    let added = scope.spawn(move || {
+                        start.wait();
+                        repository.orders().add_orders(
+                            &mock_clock::new(),
+                            &mock_random_number_generator::new(),
+                            settings,
+                            table_number,
+                            rest_bodies::Orders {
+                                idempotency_key: None,
+                                orders: vec![rest_bodies::Order { menu_item_id: 1, seat: None, modifiers: None, note: None }]
+                            })
+                    });
+                    (deleted, added)
+                })
+                .collect::<Vec<_>>();
+            handles.into_iter()
+                .map(|(deleted, added)| (deleted.join().unwrap().is_ok(), added.join().unwrap().is_ok()))
+                .collect::<Vec<_>>()
+        });
+
+        // assertion
+        // either the orders were added before the table was deleted, which then fails, or the table was deleted first
+        for (deleted, added) in results {
+            assert!(deleted != added);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn tables_delete_error_404() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+
+        // execution
+        let response = client.delete("/tables/1").dispatch();
 
-        // get execution
-        let get_req = client.get(format!("/tables/2/orders/{}", order.id)); // different table from before
-        let response = get_req.dispatch();
-        
         // assertion
         assert_eq!(response.status(), Status::NotFound);
         assert_response_contains_error(response)?;
         Ok(())
     }
-    
 
     #[test]
-    fn orders_get_single_error_500() -> Result<(), String> {
+    fn tables_transfer_all() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let moved = post_orders(&client, 1, vec![1, 2])?;
+        let existing = post_orders(&client, 2, vec![3])?;
+
+        // execution
+        let response = transfer_orders(&client, 1, rest_bodies::Transfer {
+            table_number: 2,
+            order_ids: None
+        })?;
+
+        // assertion
+        assert_eq!(response.status(), Status::Ok);
+        let mut ids = from_str::<rest_responses::Orders>(&response.into_string().unwrap())
+            .map_err(|e| e.to_string())?.orders
+            .iter().map(|o| o.id).collect::<Vec<u32>>();
+        ids.sort();
+        assert_eq!(ids, vec![moved[0].id, moved[1].id, existing[0].id]);
+        let source_orders = from_str::<rest_responses::Orders>(&client.get("/tables/1/orders").dispatch().into_string().unwrap())
+            .map_err(|e| e.to_string())?.orders;
+        assert!(source_orders.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn tables_transfer_selected() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let orders = post_orders(&client, 1, vec![1, 2, 3])?;
+
+        // execution
+        let response = transfer_orders(&client, 1, rest_bodies::Transfer {
+            table_number: 2,
+            order_ids: Some(vec![orders[1].id])
+        })?;
+
+        // assertion
+        assert_eq!(response.status(), Status::Ok);
+        let destination_orders = from_str::<rest_responses::Orders>(&response.into_string().unwrap())
+            .map_err(|e| e.to_string())?.orders;
+        assert_eq!(destination_orders.len(), 1);
+        assert_eq!(destination_orders[0].id, orders[1].id);
+        let source_orders = from_str::<rest_responses::Orders>(&client.get("/tables/1/orders").dispatch().into_string().unwrap())
+            .map_err(|e| e.to_string())?.orders;
+        assert_eq!(source_orders.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn tables_transfer_error_404() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let orders = post_orders(&client, 1, vec![1, 2])?;
+        let other_table_order = post_orders(&client, 3, vec![1])?[0].id;
+
+        // execution
+        let response = transfer_orders(&client, 1, rest_bodies::Transfer {
+            table_number: 2,
+            order_ids: Some(vec![orders[0].id, other_table_order])
+        })?;
+
+        // assertion
+        assert_eq!(response.status(), Status::NotFound);
+        assert_response_contains_error(response)?;
+        // nothing was moved
+        let source_orders = from_str::<rest_responses::Orders>(&client.get("/tables/1/orders").dispatch().into_string().unwrap())
+            .map_err(|e| e.to_string())?.orders;
+        assert_eq!(source_orders.len(), 2);
+        let destination_orders = from_str::<rest_responses::Orders>(&client.get("/tables/2/orders").dispatch().into_string().unwrap())
+            .map_err(|e| e.to_string())?.orders;
+        assert!(destination_orders.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn tables_transfer_error_422() -> Result<(), String> {
+        // setup
+        let client = create_client_with_settings(Settings {
+            require_registered_tables: true,
+            ..Settings::default()
+        })?;
+        create_table(&client, 1, 4, None)?;
+        post_orders(&client, 1, vec![1])?;
+
+        // execution
+        let same_table_response = transfer_orders(&client, 1, rest_bodies::Transfer {
+            table_number: 1,
+            order_ids: None
+        })?;
+        let unregistered_table_response = transfer_orders(&client, 1, rest_bodies::Transfer {
+            table_number: 2,
+            order_ids: None
+        })?;
+
+        // assertion
+        assert_eq!(same_table_response.status(), Status::UnprocessableEntity);
+        assert_response_contains_error(same_table_response)?;
+        assert_eq!(unregistered_table_response.status(), Status::UnprocessableEntity);
+        assert_response_contains_error(unregistered_table_response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn orders_post_requires_registered_tables() -> Result<(), String> {
+        // setup
+        let client = create_client_with_settings(Settings {
+            require_registered_tables: true,
+            ..Settings::default()
+        })?;
+        create_table(&client, 1, 4, None)?;
+        create_table(&client, 2, 4, None)?;
+        update_table(&client, 2, rest_bodies::TableUpdate {
+            capacity: None,
+            section: None,
+            open: Some(false)
+        })?;
+
+        // execution
+        let open_table_orders = post_orders(&client, 1, vec![1])?;
+        let closed_table_response = post_order(&client, 2, rest_bodies::Order {
+            menu_item_id: 1,
+            seat: None,
+            modifiers: None,
+            note: None
+        })?;
+        let closed_table_status = closed_table_response.status();
+        assert_response_contains_error(closed_table_response)?;
+        let unknown_table_response = post_order(&client, 4000000, rest_bodies::Order {
+            menu_item_id: 1,
+            seat: None,
+            modifiers: None,
+            note: None
+        })?;
+
+        // assertion
+        assert_eq!(open_table_orders.len(), 1);
+        assert_eq!(closed_table_status, Status::Conflict);
+        assert_eq!(unknown_table_response.status(), Status::NotFound);
+        assert_response_contains_error(unknown_table_response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn orders_patch_table_number_requires_registered_tables() -> Result<(), String> {
+        // setup
+        let client = create_client_with_settings(Settings {
+            require_registered_tables: true,
+            ..Settings::default()
+        })?;
+        create_table(&client, 1, 4, None)?;
+        let order_id = post_orders(&client, 1, vec![1])?.first().unwrap().id;
+
+        // execution
+        let response = patch_order(&client, 1, order_id, rest_bodies::OrderUpdate {
+            menu_item_id: None,
+            table_number: Some(4000000),
+            minutes_to_cook: None,
+            seat: None,
+            modifiers: None,
+            note: None
+        })?;
+
+        // assertion
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+        assert_response_contains_error(response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn idempotency_keys_get() -> Result<(), String> {
+        // setup
+        let clock = mock_clock::new();
+        let client = create_client_with_clock(clock.clone())?;
+        post_order_with_key(&client, 1, "first")?;
+        clock.advance_seconds(60);
+        post_order_with_key(&client, 1, "second")?;
+        post_orders(&client, 1, vec![1])?; // no key, so nothing is stored
+
+        // execution
+        let response = client.get("/admin/idempotency-keys").dispatch();
+
+        // assertion
+        assert_eq!(response.status(), Status::Ok);
+        let keys = from_str::<rest_responses::IdempotencyKeys>(&response.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(keys.count, 2);
+        assert_eq!(keys.oldest_created_at, Some(mock_clock::DEFAULT_START_TIME));
+        Ok(())
+    }
+
+    #[test]
+    fn idempotency_keys_get_error_500() -> Result<(), String> {
         // setup
         let client = create_client_without_setup()?;
 
         // execution
-        let req = client.get("/tables/1/orders/1");
-        let response = req.dispatch();
-        
+        let response = client.get("/admin/idempotency-keys").dispatch();
+
         // assertion
         assert_eq!(response.status(), Status::InternalServerError);
         assert_response_contains_error(response)?;
         Ok(())
     }
-    
 
     #[test]
-    fn orders_delete_error_500() -> Result<(), String> {
+    fn idempotency_keys_purge() -> Result<(), String> {
+        // setup
+        let clock = mock_clock::new();
+        let repository = test_repository::new()?;
+        let client = create_client_with(repository.clone(), clock.clone(), Settings::default())?;
+        post_order_with_key(&client, 1, "expired")?;
+        clock.advance_seconds(1800);
+        post_order_with_key(&client, 1, "kept")?;
+        clock.advance_seconds(1800); // the first key is now exactly as old as the TTL
+
+        // execution
+        let deleted = repository.idempotency().purge_idempotency_keys(&clock, 3600).map_err(|e| e.to_string())?;
+
+        // assertion
+        assert_eq!(deleted, 1);
+        let keys = from_str::<rest_responses::IdempotencyKeys>(&client.get("/admin/idempotency-keys").dispatch().into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(keys.count, 1);
+
+        // a purged key can be used again, while one which has not expired is still replayed
+        let expired_response = post_order_with_key(&client, 1, "expired")?;
+        assert_eq!(expired_response.status(), Status::Ok);
+        assert_eq!(expired_response.headers().get_one("Idempotent-Replayed"), None);
+        let kept_response = post_order_with_key(&client, 1, "kept")?;
+        assert_eq!(kept_response.status(), Status::Ok);
+        assert_eq!(kept_response.headers().get_one("Idempotent-Replayed"), Some("true"));
+        Ok(())
+    }
+
+    #[test]
+    fn idempotency_keys_purge_error() -> Result<(), String> {
+        // setup
+        let repository = test_repository::without_setup()?;
+
+        // execution
+        let purged = repository.idempotency().purge_idempotency_keys(&mock_clock::new(), 3600);
+
+        // assertion
+        assert!(purged.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn checkout_post() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        let order_id = post_orders(&client, 1, vec![1, 5])?.first().unwrap().id;
+        post_orders(&client, 2, vec![3])?; // different table, stays open
+
+        // execution
+        let response = client.post("/tables/1/checkout").dispatch();
+
+        // assertion
+        assert_eq!(response.status(), Status::Ok);
+        let bill = from_str::<rest_responses::Bill>(&response.into_string().unwrap())
+            .map_err(|e| e.to_string())?;
+        assert_eq!(bill.line_items.len(), 2);
+        assert_eq!(bill.total, 1265);
+
+        // checked out orders are closed
+        let orders_response = client.get("/tables/1/orders").dispatch();
+        let orders = from_str::<rest_responses::Orders>(&orders_response.into_string().unwrap())
+            .map_err(|e| e.to_string())?
+            .orders;
+        assert!(orders.is_empty());
+        let order_response = client.get(format!("/tables/1/orders/{order_id}")).dispatch();
+        assert_eq!(order_response.status(), Status::NotFound);
+        assert!(get_bill(&client, 1)?.line_items.is_empty());
+        assert_eq!(get_bill(&client, 2)?.line_items.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn checkout_post_error_404() -> Result<(), String> {
+        // setup
+        let client = create_client()?;
+        post_orders(&client, 1, vec![1])?;
+        client.post("/tables/1/checkout").dispatch();
+
+        // execution
+        let response = client.post("/tables/1/checkout").dispatch(); // already checked out
+
+        // assertion
+        assert_eq!(response.status(), Status::NotFound);
+        assert_response_contains_error(response)?;
+        Ok(())
+    }
+
+    #[test]
+    fn checkout_post_error_500() -> Result<(), String> {
         // setup
         let client = create_client_without_setup()?;
 
         // execution
-        let req = client.delete("/tables/1/orders/1");
-        let response = req.dispatch();
-        
+        let response = client.post("/tables/1/checkout").dispatch();
+
         // assertion
         assert_eq!(response.status(), Status::InternalServerError);
         assert_response_contains_error(response)?;
@@ -0,0 +1,31 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use server::clock::Clock;
+
+pub const DEFAULT_START_TIME: u64 = 1_700_000_000;
+
+pub fn new() -> MockClock {
+    MockClock {
+        seconds: Arc::new(AtomicU64::new(DEFAULT_START_TIME))
+    }
+}
+
+// Clones share the same time, so a test can keep a copy to advance
+// the clock after the original has been handed to the server.
+#[derive(Clone)]
+pub struct MockClock {
+    seconds: Arc<AtomicU64>
+}
+
+impl MockClock {
+    pub fn advance_seconds(&self, seconds: u64) {
+        self.seconds.fetch_add(seconds, Ordering::SeqCst);
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> u64 {
+        self.seconds.load(Ordering::SeqCst)
+    }
+}
\ No newline at end of file
@@ -1,22 +0,0 @@
-use std::path::PathBuf;
-
-use server::database_connector::DatabaseConnector;
-use rusqlite::Connection;
-use tempfile::NamedTempFile;
-
-pub fn new() -> Result<MockDatabaseConnector, String> {
-    let temp_file = NamedTempFile::new().map_err(|e| e.to_string())?;
-    Ok(MockDatabaseConnector {
-        temp_file_path: temp_file.path().to_path_buf()
-    })
-}
-
-pub struct MockDatabaseConnector {
-    temp_file_path: PathBuf
-}
-
-impl DatabaseConnector for MockDatabaseConnector {
-    fn open(&self) -> Result<rusqlite::Connection, rusqlite::Error> {
-        Connection::open(&self.temp_file_path)
-    }
-}
\ No newline at end of file
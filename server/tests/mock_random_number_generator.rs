@@ -0,0 +1,14 @@
+use server::random_number_generator::RandomNumberGenerator;
+
+pub fn new() -> MockRandomNumberGenerator {
+    MockRandomNumberGenerator {}
+}
+
+// Always picks the lowest number in the range, so tests can assert exact values
+pub struct MockRandomNumberGenerator {}
+
+impl RandomNumberGenerator for MockRandomNumberGenerator {
+    fn gen_range(&self, min: u32, _max: u32) -> u32 {
+        min
+    }
+}
\ No newline at end of file
@@ -0,0 +1,12 @@
+use server::database_pool::DatabasePool;
+use tempfile::NamedTempFile;
+
+// Small, since each test only makes a few requests at a time
+const POOL_SIZE: u32 = 4;
+
+// A pool for a database in a new temporary file, so that tests do not conflict with each other.
+// Clones of the pool open the same database, so that a test can also use it outside of the server.
+pub fn new() -> Result<DatabasePool, String> {
+    let temp_file = NamedTempFile::new().map_err(|e| e.to_string())?;
+    DatabasePool::new(temp_file.path(), POOL_SIZE).map_err(|e| e.to_string())
+}
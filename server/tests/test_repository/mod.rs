@@ -0,0 +1,115 @@
+use std::env;
+use rocket::{ Build, Rocket };
+
+use server::repositories::{ self, DatabaseRepository, IdempotencyRepository, OrderRepository, TableRepository };
+use server::seed::Seed;
+use server::repositories::sqlite::SqliteRepository;
+use server::repositories::in_memory::InMemoryRepository;
+#[cfg(feature = "postgres")]
+use server::repositories::postgres::PostgresRepository;
+
+use crate::temp_database;
+
+// The environment variable choosing the repository which the integration tests run against,
+// e.g. SERVER_TEST_STORAGE=memory cargo test. Without it, they run against SQLite.
+pub const STORAGE_VARIABLE: &str = "SERVER_TEST_STORAGE";
+// The Postgres database to run the tests against when SERVER_TEST_STORAGE=postgres.
+// Each test creates its own schema in it, which is not dropped afterwards, so this should be a throwaway database.
+#[cfg(feature = "postgres")]
+pub const DATABASE_URL_VARIABLE: &str = "SERVER_TEST_DATABASE_URL";
+#[cfg(feature = "postgres")]
+const POSTGRES_POOL_SIZE: u32 = 4;
+
+// Clones share the same storage, so that a test can also use it outside of the server
+#[derive(Clone)]
+pub enum TestRepository {
+    Sqlite(SqliteRepository),
+    Memory(InMemoryRepository),
+    #[cfg(feature = "postgres")]
+    Postgres(PostgresRepository)
+}
+
+// A repository which has been set up, with the default menu and nothing else
+pub fn new() -> Result<TestRepository, String> {
+    with_seed(&Seed::default())
+}
+
+// A repository which has been set up with the seed's menu
+pub fn with_seed(seed: &Seed) -> Result<TestRepository, String> {
+    match env::var(STORAGE_VARIABLE).as_deref() {
+        Err(_) | Ok("sqlite") => {
+            let repository = SqliteRepository::new(temp_database::new()?);
+            repository.setup(seed).map_err(|e| e.to_string())?;
+            Ok(TestRepository::Sqlite(repository))
+        },
+        Ok("memory") => Ok(TestRepository::Memory(InMemoryRepository::with_seed(seed))),
+        #[cfg(feature = "postgres")]
+        Ok("postgres") => {
+            let repository = postgres_repository()?;
+            repository.setup(seed).map_err(|e| e.to_string())?;
+            Ok(TestRepository::Postgres(repository))
+        },
+        Ok(storage) => Err(format!("Unknown {STORAGE_VARIABLE}: {storage}"))
+    }
+}
+
+// A SQLite database which has not been set up, so that every request fails, whichever repository is being tested
+pub fn without_setup() -> Result<TestRepository, String> {
+    Ok(TestRepository::Sqlite(SqliteRepository::new(temp_database::new()?)))
+}
+
+impl TestRepository {
+    pub fn manage(self, rocket: Rocket<Build>) -> Rocket<Build> {
+        match self {
+            TestRepository::Sqlite(repository) => repositories::manage(rocket, repository),
+            TestRepository::Memory(repository) => repositories::manage(rocket, repository),
+            #[cfg(feature = "postgres")]
+            TestRepository::Postgres(repository) => repositories::manage(rocket, repository)
+        }
+    }
+
+    pub fn orders(&self) -> &dyn OrderRepository {
+        match self {
+            TestRepository::Sqlite(repository) => repository,
+            TestRepository::Memory(repository) => repository,
+            #[cfg(feature = "postgres")]
+            TestRepository::Postgres(repository) => repository
+        }
+    }
+
+    pub fn tables(&self) -> &dyn TableRepository {
+        match self {
+            TestRepository::Sqlite(repository) => repository,
+            TestRepository::Memory(repository) => repository,
+            #[cfg(feature = "postgres")]
+            TestRepository::Postgres(repository) => repository
+        }
+    }
+
+    pub fn idempotency(&self) -> &dyn IdempotencyRepository {
+        match self {
+            TestRepository::Sqlite(repository) => repository,
+            TestRepository::Memory(repository) => repository,
+            #[cfg(feature = "postgres")]
+            TestRepository::Postgres(repository) => repository
+        }
+    }
+}
+
+// A repository in a new schema of the test database, so that tests running at the same time do not see each other's data
+#[cfg(feature = "postgres")]
+fn postgres_repository() -> Result<PostgresRepository, String> {
+    let url = env::var(DATABASE_URL_VARIABLE)
+        .map_err(|_| format!("{DATABASE_URL_VARIABLE} must be set to run the tests against Postgres"))?;
+    let mut config: postgres::Config = url.parse().map_err(|e: postgres::Error| e.to_string())?;
+    let schema = format!("test_{}", rand::random::<u64>());
+
+    // the client cannot be used directly from the async tests' runtime
+    rocket::tokio::task::block_in_place(|| {
+        let mut client = config.connect(postgres::NoTls).map_err(|e| e.to_string())?;
+        client.batch_execute(&format!("CREATE SCHEMA {schema}")).map_err(|e| e.to_string())
+    })?;
+
+    config.options(&format!("-c search_path={schema}"));
+    PostgresRepository::new(config, POSTGRES_POOL_SIZE).map_err(|e| e.to_string())
+}